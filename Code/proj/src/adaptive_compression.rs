@@ -0,0 +1,712 @@
+// Decomposed cracking
+// Adaptive, per-piece compression
+
+// uses map! macro.
+
+// A decomposed cracking table which picks a compression scheme for each piece of the cracker column
+// rather than for the whole table. The cracker column is held as separate pieces, with an
+// AVLCrackerIndex from the smallest value a piece may hold to the piece, as in ConcurrentTable. Each
+// time a query reads a piece, the piece is looked over: once it has been read often enough, a piece
+// holding a single value in enough rows is compacted to the value and its frame-of-reference encoded
+// base indices, as CoCoTable compacts its entries, and a piece whose runs of equal values are long
+// enough is run-length encoded, as the RLE tables encode their cracker columns. So the pieces of hub
+// vertices end up compacted, and those of the long tail stay plain.
+
+use column::Column;
+use column::IntCol;
+use column::Values;
+use column::half_open;
+use column::in_half_open;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
+use cracked_table::owned_column_names;
+use cracker_index::AVLCrackerIndex;
+use cracker_index::CrackerIndex;
+use encoding::ForBlock;
+
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem;
+use std::ops::Range;
+use std::slice::Iter;
+
+// The compression schemes a piece can be held in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scheme {
+    Plain,
+    RLE,
+    Compacted,
+}
+
+#[derive(Clone)]
+pub enum Encoding {
+    // Cracked in place, element by element, as by DeCrackedTable.
+    Plain { crk: Vec<i64>, base_idx: Vec<usize> },
+    // Runs of equal values, each held once along with its length. The base indices of each run are
+    // contiguous in base_idx, in the order of the runs. Cracking moves whole runs.
+    Runs { values: Vec<i64>, lengths: Vec<usize>, base_idx: Vec<usize> },
+    // Every row of the piece holds VALUE, so only the base indices are kept.
+    Compacted { value: i64, base_idx: ForBlock },
+}
+
+// A piece's low and high bounds, encoding and query count, as stored by persist.
+pub type PieceParts = (i64, Option<i64>, Encoding, usize);
+
+#[derive(Clone)]
+struct Piece {
+    // Every element of the piece lies in [low, high), where a high of None leaves it open above.
+    low: i64,
+    high: Option<i64>,
+    encoding: Encoding,
+    // The number of queries which have read the piece, or the piece it was cracked from.
+    queries: usize,
+}
+
+impl Piece {
+    fn len(&self) -> usize {
+        match self.encoding {
+            Encoding::Plain { ref base_idx, .. } | Encoding::Runs { ref base_idx, .. } => base_idx.len(),
+            Encoding::Compacted { ref base_idx, .. } => base_idx.len(),
+        }
+    }
+
+    fn scheme(&self) -> Scheme {
+        match self.encoding {
+            Encoding::Plain { .. } => Scheme::Plain,
+            Encoding::Runs { .. } => Scheme::RLE,
+            Encoding::Compacted { .. } => Scheme::Compacted,
+        }
+    }
+
+    // Returns the number of runs of equal values in the piece, and whether it holds a single value.
+    fn runs(&self) -> (usize, bool) {
+        match self.encoding {
+            Encoding::Plain { ref crk, .. } => {
+                let runs = if crk.is_empty() { 0 } else { 1 + crk.windows(2).filter(|w| w[0] != w[1]).count() };
+                (runs, runs > 0 && crk.iter().all(|&c| c == crk[0]))
+            },
+            Encoding::Runs { ref values, .. } => (values.len(), !values.is_empty() && values.iter().all(|&x| x == values[0])),
+            Encoding::Compacted { .. } => (1, true),
+        }
+    }
+
+    // Appends the elements of COLUMN at the piece's base indices to OUT.
+    fn values(&self, column: &[i64], out: &mut Vec<i64>) {
+        match self.encoding {
+            Encoding::Plain { ref base_idx, .. } | Encoding::Runs { ref base_idx, .. } => {
+                out.extend(base_idx.iter().map(|&i| column[i]));
+            },
+            Encoding::Compacted { ref base_idx, .. } => out.extend(base_idx.iter().map(|i| column[i])),
+        }
+    }
+
+    // Appends the value and base index of each of the piece's tuples to OUT, in the order they are
+    // held in.
+    fn tuples(&self, out: &mut Vec<(i64, usize)>) {
+        match self.encoding {
+            Encoding::Plain { ref crk, ref base_idx } => out.extend(crk.iter().cloned().zip(base_idx.iter().cloned())),
+            Encoding::Runs { ref values, ref lengths, ref base_idx } => {
+                let run_values = values.iter().zip(lengths.iter()).flat_map(|(&x, &n)| (0..n).map(move |_| x));
+                out.extend(run_values.zip(base_idx.iter().cloned()));
+            },
+            Encoding::Compacted { value, ref base_idx } => out.extend(base_idx.iter().map(|i| (value, i))),
+        }
+    }
+
+    // Takes the row at base index I out of the piece, if the piece holds it.
+    fn remove(&mut self, i: usize) {
+        match self.encoding {
+            Encoding::Plain { ref mut crk, ref mut base_idx } => {
+                if let Some(p) = base_idx.iter().position(|&j| j == i) {
+                    crk.remove(p);
+                    base_idx.remove(p);
+                }
+            },
+            Encoding::Runs { ref mut values, ref mut lengths, ref mut base_idx } => {
+                if let Some(p) = base_idx.iter().position(|&j| j == i) {
+                    base_idx.remove(p);
+                    // Find the run holding position P and shorten it, dropping it once it is empty.
+                    let mut end = 0;
+                    let run = lengths.iter().position(|&n| { end += n; p < end }).unwrap();
+                    lengths[run] -= 1;
+                    if lengths[run] == 0 {
+                        values.remove(run);
+                        lengths.remove(run);
+                    }
+                }
+            },
+            Encoding::Compacted { ref mut base_idx, .. } => {
+                if let Some(block) = base_idx.without(i) {
+                    *base_idx = block;
+                }
+            },
+        }
+    }
+
+    // Adds the row at base index I, whose value is X, to the piece. A compacted piece's bounds can be
+    // wider than its one value, so a row of any other value turns it back into a plain piece.
+    fn push(&mut self, x: i64, i: usize) {
+        if let Encoding::Compacted { value, ref base_idx } = self.encoding {
+            if x != value {
+                let base_idx = base_idx.decode();
+                self.encoding = Encoding::Plain { crk: vec![value; base_idx.len()], base_idx };
+            }
+        }
+        match self.encoding {
+            Encoding::Plain { ref mut crk, ref mut base_idx } => {
+                crk.push(x);
+                base_idx.push(i);
+            },
+            Encoding::Runs { ref mut values, ref mut lengths, ref mut base_idx } => {
+                if values.last() == Some(&x) {
+                    *lengths.last_mut().unwrap() += 1;
+                } else {
+                    values.push(x);
+                    lengths.push(1);
+                }
+                base_idx.push(i);
+            },
+            Encoding::Compacted { ref mut base_idx, .. } => *base_idx = base_idx.push(i),
+        }
+    }
+
+    // Cracks the piece in two on V, which must lie in (low, high), keeping the elements less than V
+    // and returning a piece of those at least V, held in the same scheme.
+    fn split(&mut self, v: i64) -> Piece {
+        let upper = match self.encoding {
+            Encoding::Plain { ref mut crk, ref mut base_idx } => {
+                let mut p_low = 0;
+                let mut p_high = crk.len();
+                while p_low < p_high {
+                    if crk[p_low] < v {
+                        p_low += 1;
+                    } else if crk[p_high - 1] >= v {
+                        p_high -= 1;
+                    } else {
+                        crk.swap(p_low, p_high - 1);
+                        base_idx.swap(p_low, p_high - 1);
+                        p_low += 1;
+                        p_high -= 1;
+                    }
+                }
+                Encoding::Plain { crk: crk.split_off(p_low), base_idx: base_idx.split_off(p_low) }
+            },
+            Encoding::Runs { ref mut values, ref mut lengths, ref mut base_idx } => {
+                // The runs are laid out again, lower ones first, in the order they were in.
+                let mut lower = (Vec::new(), Vec::new(), Vec::with_capacity(base_idx.len()));
+                let mut upper = (Vec::new(), Vec::new(), Vec::new());
+                let mut start = 0;
+                for (&x, &n) in values.iter().zip(lengths.iter()) {
+                    let side = if x < v { &mut lower } else { &mut upper };
+                    side.0.push(x);
+                    side.1.push(n);
+                    side.2.extend_from_slice(&base_idx[start..(start + n)]);
+                    start += n;
+                }
+                *values = lower.0;
+                *lengths = lower.1;
+                *base_idx = lower.2;
+                Encoding::Runs { values: upper.0, lengths: upper.1, base_idx: upper.2 }
+            },
+            Encoding::Compacted { value, .. } => {
+                let empty = Encoding::Plain { crk: Vec::new(), base_idx: Vec::new() };
+                if value < v { empty } else { mem::replace(&mut self.encoding, empty) }
+            },
+        };
+        let high = self.high.replace(v);
+        Piece { low: v, high, encoding: upper, queries: self.queries }
+    }
+
+    // Counts a query against the piece and, once it has been read HOT_QUERIES times, moves it to the
+    // scheme which pays off for it: compaction for a piece of a single value in at least
+    // COMPACTION_THRESHOLD rows, RLE for a piece whose runs average at least RLE_THRESHOLD rows, and
+    // plain otherwise. A threshold of 0 turns its scheme off. Pieces are never decompressed.
+    fn adapt(&mut self, hot_queries: usize, compaction_threshold: usize, rle_threshold: usize) {
+        self.queries += 1;
+        if self.queries < hot_queries || self.scheme() == Scheme::Compacted {
+            return;
+        }
+        let len = self.len();
+        let (runs, single_value) = self.runs();
+        if compaction_threshold > 0 && single_value && len >= compaction_threshold {
+            let encoding = mem::replace(&mut self.encoding, Encoding::Plain { crk: Vec::new(), base_idx: Vec::new() });
+            self.encoding = match encoding {
+                Encoding::Plain { crk, base_idx } => Encoding::Compacted { value: crk[0], base_idx: ForBlock::encode(&base_idx) },
+                Encoding::Runs { values, base_idx, .. } => Encoding::Compacted { value: values[0], base_idx: ForBlock::encode(&base_idx) },
+                compacted => compacted,
+            };
+            return;
+        }
+        if rle_threshold > 0 && self.scheme() == Scheme::Plain && runs > 0 && len >= rle_threshold * runs {
+            let encoding = mem::replace(&mut self.encoding, Encoding::Plain { crk: Vec::new(), base_idx: Vec::new() });
+            if let Encoding::Plain { crk, base_idx } = encoding {
+                let mut values: Vec<i64> = Vec::with_capacity(runs);
+                let mut lengths: Vec<usize> = Vec::with_capacity(runs);
+                for c in crk {
+                    if values.last() == Some(&c) {
+                        *lengths.last_mut().unwrap() += 1;
+                    } else {
+                        values.push(c);
+                        lengths.push(1);
+                    }
+                }
+                self.encoding = Encoding::Runs { values, lengths, base_idx };
+            }
+        }
+    }
+
+    // The number of bytes the piece's cracker column and base indices take up.
+    fn size(&self) -> usize {
+        let word = mem::size_of::<usize>();
+        match self.encoding {
+            Encoding::Plain { ref crk, ref base_idx } => (crk.len() + base_idx.len()) * word,
+            Encoding::Runs { ref values, ref lengths, ref base_idx } => (values.len() + lengths.len() + base_idx.len()) * word,
+            Encoding::Compacted { ref base_idx, .. } => word + base_idx.size(),
+        }
+    }
+}
+
+pub struct AdaptiveTable {
+    pub count: usize,
+    pub crk_col_name: String,
+    pub columns: HashMap<String, IntCol>,
+    // The table cracks on the queried values alone, and its index maps values to pieces rather than
+    // positions, so none of its settings apply as yet.
+    pub settings: CrackSettings,
+    pub deleted: HashSet<usize>,
+    // For the smallest value each piece may hold, the piece's position in pieces.
+    index: AVLCrackerIndex,
+    pieces: Vec<Piece>,
+    // A piece is only moved to another scheme once this many queries have read it, so that pieces
+    // read once are not compressed for nothing. 1 looks over a piece the first time it is read.
+    pub hot_queries: usize,
+    // Pieces holding a single value in at least this many rows are compacted. 0 compacts none.
+    pub compaction_threshold: usize,
+    // Pieces whose runs of equal values average at least this many rows are run-length encoded. 0
+    // encodes none.
+    pub rle_threshold: usize,
+}
+
+impl AdaptiveTable {
+    pub fn new() -> AdaptiveTable {
+        AdaptiveTable {
+            count: 0,
+            crk_col_name: "".to_string(),
+            columns: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
+            index: AVLCrackerIndex::new(),
+            pieces: Vec::new(),
+            hot_queries: 1,
+            compaction_threshold: 8,
+            rle_threshold: 4,
+        }
+    }
+
+    pub fn new_columns(&mut self, col_names: Vec<String>) {
+        for col in col_names {
+            self.columns.insert(col, IntCol::empty());
+        }
+    }
+
+    pub fn set_crk_col(&mut self, col_name: String) {
+        let crk = match self.columns.get(&col_name) {
+            Some(c) => c.v.clone(),
+            None => panic!("set_crk_col: no such col"),
+        };
+        // The whole column starts out as one plain piece, from the smallest value up.
+        let base_idx: Vec<usize> = (0..crk.len()).filter(|i| !self.deleted.contains(i)).collect();
+        let whole = Piece {
+            low: i64::MIN,
+            high: None,
+            encoding: Encoding::Plain { crk: base_idx.iter().map(|&i| crk[i]).collect(), base_idx },
+            queries: 0,
+        };
+        self.index = AVLCrackerIndex::new();
+        self.index.insert(i64::MIN, 0);
+        self.pieces = vec![whole];
+        self.crk_col_name = col_name;
+    }
+
+    // Appends the given values to every column. Each row inserted once the cracker column is set goes
+    // into the piece holding its value, in that piece's scheme.
+    pub fn insert(&mut self, new_values: &mut HashMap<String, Vec<i64>>) {
+        let old_count = self.count;
+        let mut n_new_tuples = 0;
+        for (key, val) in self.columns.iter_mut() {
+            let new_elements = new_values.get_mut(key).unwrap();
+            let n = new_elements.len();
+            if n_new_tuples == 0 || n_new_tuples == n {
+                val.v.append(new_elements);
+                n_new_tuples = n;
+            } else {
+                panic!("insert: new_values has vectors of differing lengths");
+            }
+        }
+        self.count += n_new_tuples;
+
+        if !self.crk_col_name.is_empty() {
+            for i in old_count..self.count {
+                let x = self.columns[&self.crk_col_name].v[i];
+                let p = self.piece_of(x);
+                self.pieces[p].push(x, i);
+            }
+        }
+    }
+
+    // Deletes the rows at the given base indices, taking each out of the piece holding its value.
+    pub fn delete_indices(&mut self, indices: Iter<usize>) {
+        for &i in indices {
+            if i >= self.count {
+                panic!("delete_indices: no row at base index {}", i);
+            }
+            if self.deleted.insert(i) && !self.crk_col_name.is_empty() {
+                let p = self.piece_of(self.columns[&self.crk_col_name].v[i]);
+                self.pieces[p].remove(i);
+            }
+        }
+    }
+
+    // Returns the position in pieces of the piece which holds the value X.
+    fn piece_of(&self, x: i64) -> usize {
+        self.index.lower_bound(&x).unwrap() // The first piece starts at i64::MIN.
+    }
+
+    // Cracks the piece holding V in two, so that V starts a piece of its own.
+    fn crack(&mut self, v: i64) {
+        let p = self.piece_of(v);
+        if self.pieces[p].low == v {
+            return;
+        }
+        let upper = self.pieces[p].split(v);
+        self.pieces.push(upper);
+        self.index.insert(v, self.pieces.len() - 1);
+    }
+
+    // Returns the elements of COL where the cracker columns's value equals X
+    pub fn cracker_select_specific(&mut self, x: i64, col: &str) -> Vec<i64> {
+        self.cracker_select_range(x, x, true, true, col)
+    }
+
+    // Returns the elements of COL where the cracker columns's value is between LOW and HIGH, with
+    // inclusivity given by INC_L and INC_H. Every piece read is looked over for a better scheme.
+    pub fn cracker_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str) -> Vec<i64> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return vec![],
+        };
+        self.crack(l);
+        if let Some(h) = h {
+            self.crack(h);
+        }
+
+        let column = &self.columns[col].v;
+        let mut values = Vec::new();
+        let mut at = Some(l);
+        while let Some(a) = at.filter(|&a| h.is_none_or(|h| a < h)) {
+            let p = self.index.lower_bound(&a).unwrap();
+            let piece = &mut self.pieces[p];
+            piece.values(column, &mut values);
+            piece.adapt(self.hot_queries, self.compaction_threshold, self.rle_threshold);
+            at = piece.high;
+        }
+        values
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are in pieces of their own, returning their positions. The pieces are
+    // numbered in value order, counting tuples, and each one read is looked over for a better scheme.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        self.crack(l);
+        if let Some(h) = h {
+            self.crack(h);
+        }
+        let (mut start, mut end) = (0, 0);
+        for (k, p) in self.index.pairs() {
+            let piece = &mut self.pieces[p];
+            if k < l {
+                start += piece.len();
+                end = start;
+            } else if in_half_open(k, l, h) {
+                end += piece.len();
+                piece.adapt(self.hot_queries, self.compaction_threshold, self.rle_threshold);
+            }
+        }
+        start..end
+    }
+
+    // Cracks the column so that the elements equal to X are in a piece of their own, returning their
+    // positions.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        self.crack_select_range(x, x, true, true)
+    }
+
+    // Returns the values and base indices of the tuples at the positions R, numbered as by
+    // crack_select_range.
+    fn tuples(&self, r: Range<usize>) -> Vec<(i64, usize)> {
+        let mut tuples = Vec::new();
+        // The position of the first tuple in TUPLES, and of the next piece.
+        let (mut first, mut at) = (0, 0);
+        for (_, p) in self.index.pairs() {
+            let piece = &self.pieces[p];
+            if at >= r.end {
+                break;
+            }
+            if at + piece.len() <= r.start {
+                first = at + piece.len();
+            } else {
+                piece.tuples(&mut tuples);
+            }
+            at += piece.len();
+        }
+        tuples.drain(..cmp::min(r.start - first, tuples.len()));
+        tuples.truncate(r.len());
+        tuples
+    }
+
+    // Returns the elements of COL at the positions R, numbered as by crack_select_range.
+    pub fn values(&self, r: Range<usize>, col: &str) -> Vec<i64> {
+        let column = &self.columns[col].v;
+        self.tuples(r).into_iter().map(|(_, i)| column[i]).collect()
+    }
+
+    // Returns the cracks made in the cracker column, as the smallest value each piece may hold with
+    // the position of its first tuple.
+    pub fn cracks(&self) -> Vec<(i64, usize)> {
+        let mut start = 0;
+        self.index.pairs().into_iter().map(|(k, p)| {
+            let crack = (k, start);
+            start += self.pieces[p].len();
+            crack
+        }).collect()
+    }
+
+    // Returns the parts of each piece, in value order.
+    pub fn piece_parts(&self) -> Vec<PieceParts> {
+        self.index.pairs().into_iter().map(|(_, p)| {
+            let piece = &self.pieces[p];
+            (piece.low, piece.high, piece.encoding.clone(), piece.queries)
+        }).collect()
+    }
+
+    // Replaces the pieces with those given by PARTS, in value order, the first of which starts at
+    // i64::MIN and each of which ends where the next starts.
+    pub fn set_piece_parts(&mut self, parts: Vec<PieceParts>) {
+        self.index = AVLCrackerIndex::new();
+        self.pieces = Vec::with_capacity(parts.len());
+        for (low, high, encoding, queries) in parts {
+            self.index.insert(low, self.pieces.len());
+            self.pieces.push(Piece { low, high, encoding, queries });
+        }
+    }
+
+    // Returns the scheme the piece holding X is held in.
+    pub fn scheme_of(&self, x: i64) -> Scheme {
+        self.pieces[self.piece_of(x)].scheme()
+    }
+
+    // Returns statistics on how far the cracker column has been cracked, and how far its RLE and
+    // compacted pieces compress it.
+    pub fn stats(&self) -> CrackStats {
+        let runs: Vec<(usize, bool)> = self.pieces.iter().map(|p| p.runs()).collect();
+        let mut stats = CrackStats::new(self.pieces.iter().zip(runs.iter()).map(|(p, &(_, single_value))| (p.len(), single_value)), self.index.depth());
+        let (mut run_tuples, mut run_count, mut entries) = (0, 0, 0);
+        for (p, &(n, _)) in self.pieces.iter().zip(runs.iter()) {
+            match p.scheme() {
+                Scheme::Plain => entries += p.len(),
+                Scheme::RLE => {
+                    run_tuples += p.len();
+                    run_count += n;
+                    entries += n;
+                },
+                Scheme::Compacted => entries += 1,
+            }
+        }
+        if run_count > 0 {
+            stats.avg_run_length = Some(run_tuples as f64 / run_count as f64);
+        }
+        if stats.tuples > 0 {
+            stats.compaction_ratio = Some(entries as f64 / stats.tuples as f64);
+        }
+        stats
+    }
+
+    // Returns the number of pieces held in each scheme, as plain, RLE and compacted.
+    pub fn schemes(&self) -> (usize, usize, usize) {
+        let count = |s| self.pieces.iter().filter(|p| p.len() > 0 && p.scheme() == s).count();
+        (count(Scheme::Plain), count(Scheme::RLE), count(Scheme::Compacted))
+    }
+
+    // Returns the number of bytes taken up by the cracker column and base indices of the pieces.
+    pub fn size(&self) -> usize {
+        self.pieces.iter().map(|p| p.size()).sum()
+    }
+
+    // Returns a new, uncracked table containing the rows at the given base indices.
+    pub fn get_indices(&self, indices: Iter<usize>) -> AdaptiveTable {
+        let mut t = AdaptiveTable::new();
+        t.settings = self.settings.clone();
+        t.hot_queries = self.hot_queries;
+        t.compaction_threshold = self.compaction_threshold;
+        t.rle_threshold = self.rle_threshold;
+        t.count = indices.len();
+        for (name, col) in &self.columns {
+            let mut c = IntCol::empty();
+            c.v = indices.clone().map(|&i| col.v[i]).collect();
+            t.columns.insert(name.clone(), c);
+        }
+        t
+    }
+
+    // Lays the rows out in the order of the given base indices, dropping the pieces, which are built
+    // afresh by set_crk_col.
+    pub fn rearrange(&mut self, indices: Iter<usize>) {
+        for col in self.columns.values_mut() {
+            col.rearrange(indices.clone());
+        }
+        self.index = AVLCrackerIndex::new();
+        self.pieces = Vec::new();
+        self.crk_col_name = "".to_string();
+    }
+}
+
+impl Default for AdaptiveTable {
+    fn default() -> AdaptiveTable {
+        AdaptiveTable::new()
+    }
+}
+
+impl CrackedTable for AdaptiveTable {
+    fn new() -> AdaptiveTable {
+        AdaptiveTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        AdaptiveTable::new_columns(self, col_names.iter().map(|c| c.to_string()).collect())
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        AdaptiveTable::insert(self, &mut owned_column_names(new_values))
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        AdaptiveTable::set_crk_col(self, col_name.to_string())
+    }
+
+    // The table keeps a single cracker column, so moving to another builds it afresh.
+    fn use_crk_col(&mut self, col_name: &str) {
+        if self.crk_col_name != col_name {
+            AdaptiveTable::set_crk_col(self, col_name.to_string())
+        }
+    }
+
+    fn delete_indices(&mut self, indices: Iter<usize>) {
+        AdaptiveTable::delete_indices(self, indices)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    // The pieces are held apart rather than in an IntCol, so deletes are made by the table's own
+    // delete_indices.
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        Vec::new()
+    }
+
+    fn stats(&self) -> CrackStats {
+        AdaptiveTable::stats(self)
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        AdaptiveTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        AdaptiveTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        Values::Gathered(AdaptiveTable::values(self, r, col).into_iter())
+    }
+
+    fn scan_piece(&mut self, _l: i64, _h: Option<i64>) -> Option<Range<usize>> {
+        None
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        let column = &self.columns[col].v;
+        let values: Vec<i64> = self.tuples(r).into_iter()
+            .filter(|&(x, _)| in_half_open(x, l, h))
+            .map(|(_, i)| column[i])
+            .collect();
+        Values::Gathered(values.into_iter())
+    }
+
+    // Selections gather the pieces they read straight from the piece list, rather than numbering
+    // every piece before them as values must.
+    fn cracker_iter_specific<'a>(&'a mut self, x: i64, col: &str) -> Values<'a, i64> {
+        Values::Gathered(AdaptiveTable::cracker_select_specific(self, x, col).into_iter())
+    }
+
+    fn cracker_iter_range<'a>(&'a mut self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str) -> Values<'a, i64> {
+        Values::Gathered(AdaptiveTable::cracker_select_range(self, low, high, inc_l, inc_h, col).into_iter())
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(self.columns[col].v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> AdaptiveTable {
+        AdaptiveTable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        AdaptiveTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
+    }
+
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        AdaptiveTable::cracks(self)
+    }
+}
+
+// Returns an adjacency list built from the two vectors of adjacent nodes.
+pub fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str) -> AdaptiveTable {
+    let mut adjacency_list = AdaptiveTable::new();
+    adjacency_list.new_columns(vec!["src".to_string(), "dst".to_string()]);
+    adjacency_list.insert(&mut map!{"src".to_string() => src_node, "dst".to_string() => dst_node});
+    adjacency_list.set_crk_col(crk.to_string());
+    adjacency_list
+}
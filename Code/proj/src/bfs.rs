@@ -5,14 +5,19 @@ use rand::Rng; // HMM
 use rand;
 
 use datagen;
-use decomposed_cracking;
-use recognitive_compression;
-use compactive_compression;
-use underswap_rle_compression;
-use overswap_rle_compression;
+use cracked_table::CrackedTable;
+use decomposed_cracking::DeCrackedTable;
+use nary_cracking;
+use recognitive_compression::ReCoTable;
+use compactive_compression::CoCoTable;
+use underswap_rle_compression::UnderswapRLETable;
+use overswap_rle_compression::OverswapRLETable;
+use concurrent_cracking::ConcurrentTable;
 use time::Duration;
 use quicksort::*;
 
+use std::thread;
+
 /* BFS:
     Given an adjacency list of two i64 vectors, SRC_NODE and DST_NODE, this function visits every
     node in the graph from START_NODE.
@@ -24,14 +29,14 @@ pub fn run() {
     let n = 1000;
     let (src, dst) = datagen::randomly_connected_tree(n);
         let start_node = rand::thread_rng().gen_range(1, n);
-    let _visited = underswap_rle_bfs(src, dst, start_node);
+    let _visited = cracked_bfs::<UnderswapRLETable>(src, dst, start_node);
 }
 
 // Benchmark for single BFS runs
 
 // Prints to stdout valid csv lines containing the results of bfs benchmarks..
 pub fn benchmark_sparse_bfs_csv(graph_sizes: Vec<i64>) {
-    println!("nodes,edges,density,unoptimised,preclustered,preclusteredRLE,decomposed,recognitive,compactive,underswapRLE,overswapRLE");
+    println!("nodes,edges,density,unoptimised,preclustered,preclusteredRLE,decomposed,nary,recognitive,compactive,underswapRLE,overswapRLE");
     for n in graph_sizes {
         benchmark_sparse_bfs(n);
     }
@@ -48,11 +53,12 @@ fn benchmark_sparse_bfs(n: i64) {
     time_bfs(unoptimised_bfs,      src.clone(), dst.clone(), start_node);
     time_bfs(preclustered_bfs,     src.clone(), dst.clone(), start_node);
     time_bfs(preclustered_rle_bfs, src.clone(), dst.clone(), start_node);
-    time_bfs(decracked_bfs,        src.clone(), dst.clone(), start_node);
-    time_bfs(reco_bfs,             src.clone(), dst.clone(), start_node);
-    time_bfs(coco_bfs,             src.clone(), dst.clone(), start_node);
-    time_bfs(underswap_rle_bfs,    src.clone(), dst.clone(), start_node);
-    time_bfs(overswap_rle_bfs,     src.clone(), dst.clone(), start_node);
+    time_bfs(cracked_bfs::<DeCrackedTable>,        src.clone(), dst.clone(), start_node);
+    time_bfs(nary_bfs,             src.clone(), dst.clone(), start_node);
+    time_bfs(cracked_bfs::<ReCoTable>,             src.clone(), dst.clone(), start_node);
+    time_bfs(cracked_bfs::<CoCoTable>,             src.clone(), dst.clone(), start_node);
+    time_bfs(cracked_bfs::<UnderswapRLETable>,    src.clone(), dst.clone(), start_node);
+    time_bfs(cracked_bfs::<OverswapRLETable>,     src.clone(), dst.clone(), start_node);
     println!();
 }
 
@@ -67,7 +73,7 @@ fn time_bfs<F>(mut bfs: F, src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i
 // Benchmark for a number BFS runs
 
 pub fn benchmark_sparse_bfs_csv_n_runs(runs: usize, graph_sizes: Vec<i64>) {
-    println!("nodes,edges,density,unoptimised,preclustered,preclusteredRLE,decomposed,recognitive,compactive,underswapRLE,overswapRLE");
+    println!("nodes,edges,density,unoptimised,preclustered,preclusteredRLE,decomposed,nary,recognitive,compactive,underswapRLE,overswapRLE");
     for n in graph_sizes {
         benchmark_sparse_bfs_i_runs(runs, n);
     }
@@ -85,17 +91,18 @@ fn benchmark_sparse_bfs_i_runs(i: usize, n: i64) {
     time_bfs_n_runs(unoptimised_bfs_n,      i, src.clone(), dst.clone(), start_nodes.clone());
     time_bfs_n_runs(preclustered_bfs_n,     i, src.clone(), dst.clone(), start_nodes.clone());
     time_bfs_n_runs(preclustered_rle_bfs_n, i, src.clone(), dst.clone(), start_nodes.clone());
-    time_bfs_n_runs(decracked_bfs_n,        i, src.clone(), dst.clone(), start_nodes.clone());
-    time_bfs_n_runs(reco_bfs_n,             i, src.clone(), dst.clone(), start_nodes.clone());
-    time_bfs_n_runs(coco_bfs_n,             i, src.clone(), dst.clone(), start_nodes.clone());
-    time_bfs_n_runs(underswap_rle_bfs_n,    i, src.clone(), dst.clone(), start_nodes.clone());
-    time_bfs_n_runs(overswap_rle_bfs_n,     i, src.clone(), dst.clone(), start_nodes.clone());
+    time_bfs_n_runs(cracked_bfs_n::<DeCrackedTable>,        i, src.clone(), dst.clone(), start_nodes.clone());
+    time_bfs_n_runs(nary_bfs_n,             i, src.clone(), dst.clone(), start_nodes.clone());
+    time_bfs_n_runs(cracked_bfs_n::<ReCoTable>,             i, src.clone(), dst.clone(), start_nodes.clone());
+    time_bfs_n_runs(cracked_bfs_n::<CoCoTable>,             i, src.clone(), dst.clone(), start_nodes.clone());
+    time_bfs_n_runs(cracked_bfs_n::<UnderswapRLETable>,    i, src.clone(), dst.clone(), start_nodes.clone());
+    time_bfs_n_runs(cracked_bfs_n::<OverswapRLETable>,     i, src.clone(), dst.clone(), start_nodes.clone());
     println!();
 }
 
 fn time_bfs_n_runs<F>(mut bfs_n: F, n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) where F: FnMut(usize, Vec<i64>, Vec<i64>, Vec<i64>) {
     let start = PreciseTime::now();
-    let _visited = bfs_n(n, src_node, dst_node, start_nodes);
+    bfs_n(n, src_node, dst_node, start_nodes);
     let end = PreciseTime::now();
     print!(",{}", start.to(end));
 }
@@ -109,19 +116,21 @@ pub fn example_test_bfs_methods() {
     println!("Preclustered RLE");
     bfs_example_test(preclustered_rle_bfs);
     println!("Decracked");
-    bfs_example_test(decracked_bfs);
+    bfs_example_test(cracked_bfs::<DeCrackedTable>);
+    println!("N-ary");
+    bfs_example_test(nary_bfs);
     println!("Reco");
-    bfs_example_test(reco_bfs);
+    bfs_example_test(cracked_bfs::<ReCoTable>);
     println!("Coco");
-    bfs_example_test(coco_bfs);
+    bfs_example_test(cracked_bfs::<CoCoTable>);
     println!("Underswap RLE");
-    bfs_example_test(underswap_rle_bfs);
+    bfs_example_test(cracked_bfs::<UnderswapRLETable>);
     println!("Overswap RLE");
-    bfs_example_test(overswap_rle_bfs);
+    bfs_example_test(cracked_bfs::<OverswapRLETable>);
 }
 
 pub fn bfs_example_test<F>(mut bfs: F) where F: FnMut(Vec<i64>, Vec<i64>, i64) -> Vec<i64> {
-    let n = 30 as i64;
+    let n: i64 = 30;
     let src = vec![4, 16, 22, 8, 26, 13, 22, 4, 18, 12, 13, 22, 10, 14, 22, 8, 19, 29, 8, 8, 17, 18, 22, 5, 2, 28, 8, 12, 24, 13, 15, 21, 30, 6, 18, 25, 7, 9, 19, 19, 4, 3, 11, 17, 28, 10, 8, 28, 22, 11, 29, 1, 20, 30, 8, 23, 4, 27];
     let dst = vec![20, 18, 19, 6, 12, 22, 2, 11, 16, 26, 27, 25, 9, 4, 13, 30, 18, 30, 28, 13, 24, 19, 7, 19, 22, 8, 17, 29, 17, 8, 18, 4, 29, 8, 15, 22, 22, 10, 5, 22, 14, 8, 28, 8, 11, 22, 23, 1, 10, 4, 12, 28, 4, 8, 3, 8, 21, 13];
     let start_node = 7;
@@ -150,7 +159,7 @@ pub fn bfs_example_test<F>(mut bfs: F) where F: FnMut(Vec<i64>, Vec<i64>, i64) -
 }
 
 pub fn bait() {
-    let n = 30 as i64;
+    let n: i64 = 30;
 
     let (src, dst) = datagen::randomly_connected_tree(n);
     let start_node = rand::thread_rng().gen_range(1, n);
@@ -158,7 +167,7 @@ pub fn bait() {
 //    println!("let dst = vec!{:?};", dst);
 //    println!("let start_node = {};", start_node);
 
-    let visited = coco_bfs(src, dst, start_node);
+    let visited = cracked_bfs::<CoCoTable>(src, dst, start_node);
     let mut failed = false;
     if visited.len() != n as usize {
         println!("Incorrect visitations: {:?}", visited);
@@ -176,7 +185,7 @@ pub fn bait() {
 }
 
 pub fn random_test_bfs_methods() {
-    let n = 100 as i64;
+    let n: i64 = 100;
     let (src, dst) = datagen::randomly_connected_tree(n);
     let start_node = rand::thread_rng().gen_range(1, n);
     println!("src: {:?}", src);
@@ -189,15 +198,17 @@ pub fn random_test_bfs_methods() {
     println!("Preclustered RLE");
     bfs_random_test(preclustered_rle_bfs, n, src.clone(), dst.clone(), start_node);
     println!("Decracked");
-    bfs_random_test(decracked_bfs, n, src.clone(), dst.clone(), start_node);
+    bfs_random_test(cracked_bfs::<DeCrackedTable>, n, src.clone(), dst.clone(), start_node);
+    println!("N-ary");
+    bfs_random_test(nary_bfs, n, src.clone(), dst.clone(), start_node);
     println!("Reco");
-    bfs_random_test(reco_bfs, n, src.clone(), dst.clone(), start_node);
+    bfs_random_test(cracked_bfs::<ReCoTable>, n, src.clone(), dst.clone(), start_node);
     println!("Coco");
-    bfs_random_test(coco_bfs, n, src.clone(), dst.clone(), start_node);
+    bfs_random_test(cracked_bfs::<CoCoTable>, n, src.clone(), dst.clone(), start_node);
     println!("Underswap RLE");
-    bfs_random_test(underswap_rle_bfs, n, src.clone(), dst.clone(), start_node);
+    bfs_random_test(cracked_bfs::<UnderswapRLETable>, n, src.clone(), dst.clone(), start_node);
     println!("Overswap RLE");
-    bfs_random_test(overswap_rle_bfs, n, src.clone(), dst.clone(), start_node);
+    bfs_random_test(cracked_bfs::<OverswapRLETable>, n, src.clone(), dst.clone(), start_node);
 }
 
 pub fn bfs_random_test<F>(mut bfs: F, n: i64, src: Vec<i64>, dst: Vec<i64>, start_node: i64) where F: FnMut(Vec<i64>, Vec<i64>, i64) -> Vec<i64> {
@@ -252,7 +263,7 @@ fn indicise(v: Vec<i64>) -> Vec<i64> {
 }
 
 // Naive/Unoptimised
-fn unoptimised_bfs_refs(src_node: &Vec<i64>, dst_node: &Vec<i64>, start_node: i64) -> Vec<i64> {
+fn unoptimised_bfs_refs(src_node: &[i64], dst_node: &[i64], start_node: i64) -> Vec<i64> {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -275,16 +286,16 @@ fn unoptimised_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> V
     unoptimised_bfs_refs(&src_node, &dst_node, start_node)
 }
 fn unoptimised_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
-    for i in 0..n {
-        unoptimised_bfs_refs(&src_node, &dst_node, start_nodes[i]);
+    for &start_node in start_nodes.iter().take(n) {
+        unoptimised_bfs_refs(&src_node, &dst_node, start_node);
     }
 }
 
 // Preclustered
-pub fn precluster(src_node: &Vec<i64>, dst_node: &Vec<i64>) -> (Vec<i64>, Vec<i64>) {
+pub fn precluster(src_node: &[i64], dst_node: &[i64]) -> (Vec<i64>, Vec<i64>) {
     let e = src_node.len();
-    let mut src_col = src_node.clone();
-    let mut dst_col = dst_node.clone();
+    let mut src_col = src_node.to_vec();
+    let mut dst_col = dst_node.to_vec();
 
     let mut row_store = Vec::with_capacity(e);
     for i in 0..e {
@@ -299,7 +310,7 @@ pub fn precluster(src_node: &Vec<i64>, dst_node: &Vec<i64>) -> (Vec<i64>, Vec<i6
 
     (src_col, dst_col)
 }
-fn preclustered_bfs_from_ready(src_col: &Vec<i64>, dst_col: &Vec<i64>, start_node: i64) -> Vec<i64> {
+fn preclustered_bfs_from_ready(src_col: &[i64], dst_col: &[i64], start_node: i64) -> Vec<i64> {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -314,14 +325,12 @@ fn preclustered_bfs_from_ready(src_col: &Vec<i64>, dst_col: &Vec<i64>, start_nod
                 continue;
             }
             let i = binary_search_result.unwrap();
-            let mut inc_idx = i.clone();
-            let mut dec_idx = i.clone();
+            let mut inc_idx = i;
+            let mut dec_idx = i;
             loop {
                 discover(dst_col[inc_idx], &mut visited, &mut frontier);
                 inc_idx += 1;
-                if inc_idx >= src_col.len() {
-                    break;
-                } else if src_col[inc_idx] != src {
+                if inc_idx >= src_col.len() || src_col[inc_idx] != src {
                     break;
                 }
             }
@@ -343,8 +352,8 @@ fn preclustered_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) ->
 }
 fn preclustered_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
     let (src_col, dst_col) = precluster(&src_node, &dst_node);
-    for i in 0..n {
-        preclustered_bfs_from_ready(&src_col, &dst_col, start_nodes[i]);
+    for &start_node in start_nodes.iter().take(n) {
+        preclustered_bfs_from_ready(&src_col, &dst_col, start_node);
     }
 }
 
@@ -368,7 +377,7 @@ fn precluster_and_rle(src_node: Vec<i64>, dst_node: Vec<i64>) -> Vec<Vec<i64>> {
     }
     encoded_col
 }
-fn preclustered_rle_bfs_from_ready(encoded_col: &Vec<Vec<i64>>, start_node: i64) -> Vec<i64> {
+fn preclustered_rle_bfs_from_ready(encoded_col: &[Vec<i64>], start_node: i64) -> Vec<i64> {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -391,17 +400,17 @@ fn preclustered_rle_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64)
 }
 fn preclustered_rle_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
     let encoded_col = precluster_and_rle(src_node, dst_node);
-    for i in 0..n {
-        preclustered_rle_bfs_from_ready(&encoded_col, start_nodes[i]);
+    for &start_node in start_nodes.iter().take(n) {
+        preclustered_rle_bfs_from_ready(&encoded_col, start_node);
     }
 }
 
-// Decomposed cracking
-fn decracked_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
-    let mut adjacency_list = decomposed_cracking::from_adjacency_vectors(src_node, dst_node, "src");
-    decracked_bfs_adjl(&mut adjacency_list, start_node)
+// Decomposed cracking, with any of the compression schemes
+fn cracked_bfs<T: CrackedTable>(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
+    let mut adjacency_list = T::from_adjacency_vectors(src_node, dst_node, "src");
+    cracked_bfs_adjl(&mut adjacency_list, start_node)
 }
-pub fn decracked_bfs_adjl(adjacency_list: &mut decomposed_cracking::DeCrackedTable, start_node: i64) -> Vec<i64> {
+pub fn cracked_bfs_adjl<T: CrackedTable>(adjacency_list: &mut T, start_node: i64) -> Vec<i64> {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -414,27 +423,22 @@ pub fn decracked_bfs_adjl(adjacency_list: &mut decomposed_cracking::DeCrackedTab
         // For each src in the previous frontier, find the dsts which haven't been visited yet,
         // and add them to a new, empty frontier.
         for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
+            for dst in adjacency_list.cracker_iter_specific(src, "dst") {
+                discover(dst, &mut visited, &mut frontier);
             }
         }
     }
     bv_where(visited)
 }
-fn decracked_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
-    let mut adjacency_list = decomposed_cracking::from_adjacency_vectors(src_node, dst_node, "src");
-    for i in 0..n {
-        decracked_bfs_adjl(&mut adjacency_list, start_nodes[i]);
+fn cracked_bfs_n<T: CrackedTable>(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
+    let mut adjacency_list = T::from_adjacency_vectors(src_node, dst_node, "src");
+    for &start_node in start_nodes.iter().take(n) {
+        cracked_bfs_adjl(&mut adjacency_list, start_node);
     }
 }
 
-// Recognitive compression
-fn reco_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
-    let mut adjacency_list = recognitive_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    reco_bfs_adjl(&mut adjacency_list, start_node)
-}
-fn reco_bfs_adjl(adjacency_list: &mut recognitive_compression::ReCoTable, start_node: i64) -> Vec<i64> {
+// Concurrent cracking, with each frontier shared out between THREADS threads
+pub fn concurrent_bfs_adjl(adjacency_list: &ConcurrentTable, start_node: i64, threads: usize) -> Vec<i64> {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -444,30 +448,28 @@ fn reco_bfs_adjl(adjacency_list: &mut recognitive_compression::ReCoTable, start_
 
         let prev_frontier = frontier.clone();
         frontier.clear();
-        // For each src in the previous frontier, find the dsts which haven't been visited yet,
-        // and add them to a new, empty frontier.
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
-            }
+        // Each thread finds the dsts of its share of the previous frontier, then the dsts which
+        // haven't been visited yet are added to a new, empty frontier.
+        let share = prev_frontier.len().div_ceil(threads);
+        let neighbours: Vec<Vec<i64>> = thread::scope(|s| {
+            let handles: Vec<_> = prev_frontier.chunks(share).map(|srcs| s.spawn(move || {
+                srcs.iter().flat_map(|&src| adjacency_list.cracker_select_specific(src, "dst")).collect()
+            })).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for dst in neighbours.iter().flatten() {
+            discover(*dst, &mut visited, &mut frontier);
         }
     }
     bv_where(visited)
 }
-fn reco_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
-    let mut adjacency_list = recognitive_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    for i in 0..n {
-        reco_bfs_adjl(&mut adjacency_list, start_nodes[i]);
-    }
-}
 
-// Compactive compression
-fn coco_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
-    let mut adjacency_list = compactive_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    coco_bfs_adjl(&mut adjacency_list, start_node)
+// N-ary cracking
+fn nary_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
+    let mut adjacency_list = nary_cracking::from_adjacency_vectors(src_node, dst_node, "src");
+    nary_bfs_adjl(&mut adjacency_list, start_node)
 }
-fn coco_bfs_adjl(adjacency_list: &mut compactive_compression::CoCoTable, start_node: i64) -> Vec<i64> {
+pub fn nary_bfs_adjl(adjacency_list: &mut nary_cracking::NaryCrackedTable, start_node: i64) -> Vec<i64> {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -477,10 +479,9 @@ fn coco_bfs_adjl(adjacency_list: &mut compactive_compression::CoCoTable, start_n
 
         let prev_frontier = frontier.clone();
         frontier.clear();
-        // For each src in the previous frontier, find the dsts which haven't been visited yet,
-        // and add them to a new, empty frontier.
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
+        // Crack on the whole previous frontier at once, then find the dsts which haven't been
+        // visited yet and add them to a new, empty frontier.
+        for neighbours in adjacency_list.cracker_select_batch(&prev_frontier, "dst") {
             for dst in &neighbours {
                 discover(*dst, &mut visited, &mut frontier);
             }
@@ -488,84 +489,17 @@ fn coco_bfs_adjl(adjacency_list: &mut compactive_compression::CoCoTable, start_n
     }
     bv_where(visited)
 }
-fn coco_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
-    let mut adjacency_list = compactive_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    for i in 0..n {
-        coco_bfs_adjl(&mut adjacency_list, start_nodes[i]);
-    }
-}
-
-
-// Underswap-RLE compression
-fn underswap_rle_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
-    let mut adjacency_list = underswap_rle_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    underswap_rle_bfs_adjl(&mut adjacency_list, start_node)
-}
-pub fn underswap_rle_bfs_adjl(adjacency_list: &mut underswap_rle_compression::UnderswapRLETable, start_node: i64) -> Vec<i64> {
-    let mut frontier = vec![start_node];
-    let mut visited = BitVec::from_elem(start_node as usize, false);
-
-    while !frontier.is_empty() {
-        // Add visited nodes
-        set_indices(&mut visited, indicise(frontier.clone()));
-
-        let prev_frontier = frontier.clone();
-        frontier.clear();
-        // For each src in the previous frontier, find the dsts which haven't been visited yet,
-        // and add them to a new, empty frontier.
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
-            }
-        }
-    }
-    bv_where(visited)
-}
-fn underswap_rle_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
-    let mut adjacency_list = underswap_rle_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    for i in 0..n {
-        underswap_rle_bfs_adjl(&mut adjacency_list, start_nodes[i]);
-    }
-}
-
-// Overswap-RLE compression
-fn overswap_rle_bfs(src_node: Vec<i64>, dst_node: Vec<i64>, start_node: i64) -> Vec<i64> {
-    let mut adjacency_list = overswap_rle_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    overswap_rle_bfs_adjl(&mut adjacency_list, start_node)
-}
-pub fn overswap_rle_bfs_adjl(adjacency_list: &mut overswap_rle_compression::OverswapRLETable, start_node: i64) -> Vec<i64> {
-    let mut frontier = vec![start_node];
-    let mut visited = BitVec::from_elem(start_node as usize, false);
-
-    while !frontier.is_empty() {
-        // Add visited nodes
-        set_indices(&mut visited, indicise(frontier.clone()));
-
-        let prev_frontier = frontier.clone();
-        frontier.clear();
-        // For each src in the previous frontier, find the dsts which haven't been visited yet,
-        // and add them to a new, empty frontier.
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
-            }
-        }
-    }
-    bv_where(visited)
-}
-fn overswap_rle_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
-    let mut adjacency_list = overswap_rle_compression::from_adjacency_vectors(src_node, dst_node, "src");
-    for i in 0..n {
-        overswap_rle_bfs_adjl(&mut adjacency_list, start_nodes[i]);
+fn nary_bfs_n(n: usize, src_node: Vec<i64>, dst_node: Vec<i64>, start_nodes: Vec<i64>) {
+    let mut adjacency_list = nary_cracking::from_adjacency_vectors(src_node, dst_node, "src");
+    for &start_node in start_nodes.iter().take(n) {
+        nary_bfs_adjl(&mut adjacency_list, start_node);
     }
 }
 
 // == BREAK-EVEN POINT ==
 // Returns number of queries completed before (d) elapses.
 
-pub fn decracked_bfs_adjl_until(mut adjacency_list: decomposed_cracking::DeCrackedTable, start_node: i64, d: Duration) -> usize {
+pub fn cracked_bfs_adjl_until<T: CrackedTable>(mut adjacency_list: T, start_node: i64, d: Duration) -> usize {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -577,9 +511,8 @@ pub fn decracked_bfs_adjl_until(mut adjacency_list: decomposed_cracking::DeCrack
         let prev_frontier = frontier.clone();
         frontier.clear();
         for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
+            for dst in adjacency_list.cracker_iter_specific(src, "dst") {
+                discover(dst, &mut visited, &mut frontier);
             }
             queries += 1;
             if start.to(PreciseTime::now()) > d {
@@ -590,7 +523,8 @@ pub fn decracked_bfs_adjl_until(mut adjacency_list: decomposed_cracking::DeCrack
     queries
 }
 
-pub fn reco_bfs_adjl_until(mut adjacency_list: recognitive_compression::ReCoTable, start_node: i64, d: Duration) -> usize {
+// Counts each frontier as a single query, since it is cracked on in one sweep.
+pub fn nary_bfs_adjl_until(mut adjacency_list: nary_cracking::NaryCrackedTable, start_node: i64, d: Duration) -> usize {
     let mut frontier = vec![start_node];
     let mut visited = BitVec::from_elem(start_node as usize, false);
 
@@ -601,91 +535,15 @@ pub fn reco_bfs_adjl_until(mut adjacency_list: recognitive_compression::ReCoTabl
 
         let prev_frontier = frontier.clone();
         frontier.clear();
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
-            }
-            queries += 1;
-            if start.to(PreciseTime::now()) > d {
-                return queries;
-            }
-        }
-    }
-    queries
-}
-
-pub fn coco_bfs_adjl_until(mut adjacency_list: compactive_compression::CoCoTable, start_node: i64, d: Duration) -> usize {
-    let mut frontier = vec![start_node];
-    let mut visited = BitVec::from_elem(start_node as usize, false);
-
-    let start = PreciseTime::now();
-    let mut queries = 0;
-    while !frontier.is_empty() {
-        set_indices(&mut visited, indicise(frontier.clone()));
-
-        let prev_frontier = frontier.clone();
-        frontier.clear();
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
+        for neighbours in adjacency_list.cracker_select_batch(&prev_frontier, "dst") {
             for dst in &neighbours {
                 discover(*dst, &mut visited, &mut frontier);
             }
-            queries += 1;
-            if start.to(PreciseTime::now()) > d {
-                return queries;
-            }
         }
-    }
-    queries
-}
-
-pub fn underswap_bfs_adjl_until(mut adjacency_list: underswap_rle_compression::UnderswapRLETable, start_node: i64, d: Duration) -> usize {
-    let mut frontier = vec![start_node];
-    let mut visited = BitVec::from_elem(start_node as usize, false);
-
-    let start = PreciseTime::now();
-    let mut queries = 0;
-    while !frontier.is_empty() {
-        set_indices(&mut visited, indicise(frontier.clone()));
-
-        let prev_frontier = frontier.clone();
-        frontier.clear();
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
-            }
-            queries += 1;
-            if start.to(PreciseTime::now()) > d {
-                return queries;
-            }
+        queries += 1;
+        if start.to(PreciseTime::now()) > d {
+            return queries;
         }
     }
     queries
 }
-
-pub fn overswap_bfs_adjl_until(mut adjacency_list: overswap_rle_compression::OverswapRLETable, start_node: i64, d: Duration) -> usize {
-    let mut frontier = vec![start_node];
-    let mut visited = BitVec::from_elem(start_node as usize, false);
-
-    let start = PreciseTime::now();
-    let mut queries = 0;
-    while !frontier.is_empty() {
-        set_indices(&mut visited, indicise(frontier.clone()));
-
-        let prev_frontier = frontier.clone();
-        frontier.clear();
-        for src in prev_frontier {
-            let neighbours = adjacency_list.cracker_select_specific(src, "dst");
-            for dst in &neighbours {
-                discover(*dst, &mut visited, &mut frontier);
-            }
-            queries += 1;
-            if start.to(PreciseTime::now()) > d {
-                return queries;
-            }
-        }
-    }
-    queries
-}
\ No newline at end of file
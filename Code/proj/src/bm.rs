@@ -9,12 +9,20 @@ extern crate lib;
 pub mod utils;
 
 pub mod cracker_index;
+pub mod encoding;
 pub mod column;
+pub mod cracked_table;
 pub mod decomposed_cracking;
+pub mod nary_cracking;
 pub mod recognitive_compression;
 pub mod compactive_compression;
 pub mod underswap_rle_compression;
 pub mod overswap_rle_compression;
+pub mod hybrid_cracking;
+pub mod concurrent_cracking;
+pub mod adaptive_compression;
+pub mod packed_cracking;
+pub mod persist;
 
 pub mod datagen;
 pub mod bfs;
@@ -27,82 +35,280 @@ pub mod quicksort;
 use time::PreciseTime;
 use time::Duration;
 use rand::Rng;
+use hybrid_cracking::HybridMode;
+use cracker_index::IndexKind;
+use cracked_table::CrackedTable;
 
+use std::env;
+
+// Runs the bench named by the first argument, or the traversal times without one.
 fn main() {
-    traversal_time(2000, 10);
+    match env::args().nth(1).as_deref() {
+        None => traversal_time(2000, 10),
+        Some("index_backends") => index_backends(2000, 10),
+        Some("convergence") => convergence(2000, 2000, 200),
+        Some("min_piece_sizes") => min_piece_sizes(2000, 10),
+        Some("adaptive_schemes") => adaptive_schemes(2000, 8.0, 10),
+        Some("break_even_points") => break_even_points(2000, 10),
+        Some("graphviz") => {
+            let (src, dst) = datagen::randomly_connected_tree(30);
+            prep_graphviz(src, dst)
+        },
+        Some("personrank") => speed_test(1, 10, 5),
+        Some("tighten") => speed_test_tighten(0.01, 10),
+        Some(bench) => panic!("main: no bench named {}", bench),
+    }
 }
 
 fn traversal_time(n: i64, i: usize) {
     let mut decracked_times = Vec::new();
+    let mut sideways_times = Vec::new();
+    let mut nary_times = Vec::new();
     let mut underswap_times = Vec::new();
     let mut overswap_times = Vec::new();
+    let mut packed_times = Vec::new();
 
     for j in 0..i {
         let mut start = PreciseTime::now();
         let (src, dst) = datagen::randomly_connected_tree(n);
         let start_node = rand::thread_rng().gen_range(1, n);
-         shellsprintln!("Created tree {} after {} seconds", j, start.to(PreciseTime::now()).to_string());
+        println!("Created tree {} after {} seconds", j, start.to(PreciseTime::now()));
 
         start = PreciseTime::now();
-        bfs::decracked_bfs_adjl(&mut decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
+        bfs::cracked_bfs_adjl(&mut decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
         decracked_times.push(start.to(PreciseTime::now()));
 
         start = PreciseTime::now();
-        bfs::underswap_rle_bfs_adjl(&mut underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
+        let mut sideways = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+        sideways.add_crk_map("dst");
+        bfs::cracked_bfs_adjl(&mut sideways, start_node);
+        sideways_times.push(start.to(PreciseTime::now()));
+
+        start = PreciseTime::now();
+        bfs::nary_bfs_adjl(&mut nary_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
+        nary_times.push(start.to(PreciseTime::now()));
+
+        start = PreciseTime::now();
+        bfs::cracked_bfs_adjl(&mut underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
         underswap_times.push(start.to(PreciseTime::now()));
 
         start = PreciseTime::now();
-        bfs::overswap_rle_bfs_adjl(&mut overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
+        bfs::cracked_bfs_adjl(&mut overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
         overswap_times.push(start.to(PreciseTime::now()));
+
+        start = PreciseTime::now();
+        bfs::cracked_bfs_adjl(&mut packed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node);
+        packed_times.push(start.to(PreciseTime::now()));
     }
 
     let decracked_avg: Duration = decracked_times.iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
+    let sideways_avg: Duration = sideways_times.iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
+    let nary_avg: Duration = nary_times.iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
     let underswap_avg: Duration = underswap_times.iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
     let overswap_avg: Duration = overswap_times.iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
+    let packed_avg: Duration = packed_times.iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
 
     println!("Decracked: {}", decracked_avg);
+    println!("Sideways:  {}", sideways_avg);
+    println!("N-ary:     {}", nary_avg);
     println!("Underswap: {}", underswap_avg);
     println!("Overswap:  {}", overswap_avg);
+    println!("Packed:    {}", packed_avg);
 }
 
 // Gets for each method the average over (i) runs of the break-even point on a random tree of size (n).
 fn break_even_points(n: i64, i: usize) {
     let mut decracked_queries = Vec::new();
+    let mut nary_queries = Vec::new();
     let mut reco_queries = Vec::new();
     let mut coco_queries = Vec::new();
     let mut underswap_queries = Vec::new();
     let mut overswap_queries = Vec::new();
+    let mut crack_crack_queries = Vec::new();
+    let mut crack_sort_queries = Vec::new();
+    let mut radix_sort_queries = Vec::new();
 
     for j in 0..i {
         let start = PreciseTime::now();
         let (src, dst) = datagen::randomly_connected_tree(n);
         let start_node = rand::thread_rng().gen_range(1, n);
-        println!("Created tree {} after {} seconds", j, start.to(PreciseTime::now()).to_string());
+        println!("Created tree {} after {} seconds", j, start.to(PreciseTime::now()));
 
         let start = PreciseTime::now();
         bfs::precluster(&src, &dst);
         let d = start.to(PreciseTime::now());
 
-        decracked_queries.push(bfs::decracked_bfs_adjl_until(decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
-        reco_queries.push(bfs::reco_bfs_adjl_until(recognitive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
-        coco_queries.push(bfs::coco_bfs_adjl_until(compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
-        underswap_queries.push(bfs::underswap_bfs_adjl_until(underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
-        overswap_queries.push(bfs::overswap_bfs_adjl_until(overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        decracked_queries.push(bfs::cracked_bfs_adjl_until(decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        nary_queries.push(bfs::nary_bfs_adjl_until(nary_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        reco_queries.push(bfs::cracked_bfs_adjl_until(recognitive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        coco_queries.push(bfs::cracked_bfs_adjl_until(compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        underswap_queries.push(bfs::cracked_bfs_adjl_until(underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        overswap_queries.push(bfs::cracked_bfs_adjl_until(overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src"), start_node, d));
+        crack_crack_queries.push(bfs::cracked_bfs_adjl_until(hybrid_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src", HybridMode::CrackCrack), start_node, d));
+        crack_sort_queries.push(bfs::cracked_bfs_adjl_until(hybrid_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src", HybridMode::CrackSort), start_node, d));
+        radix_sort_queries.push(bfs::cracked_bfs_adjl_until(hybrid_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src", HybridMode::RadixSort), start_node, d));
     }
 
     println!("Decracked: {}", decracked_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
+    println!("N-ary:     {}", nary_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
     println!("Reco:      {}", reco_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
     println!("Coco:      {}", coco_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
     println!("Underswap: {}", underswap_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
     println!("Overswap:  {}", overswap_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
+    println!("HCC:       {}", crack_crack_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
+    println!("HCS:       {}", crack_sort_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
+    println!("HRS:       {}", radix_sort_queries.iter().fold(0 as f64, |sum, val| sum + (*val  as f64)) / (i as f64));
+}
+
+// Compares the cracker index backends, first on their own over (n) random keys, then by the average
+// over (i) runs of a traversal of a random tree of size (n) with each backend under a decomposed table.
+fn index_backends(n: i64, i: usize) {
+    let kinds = [IndexKind::AVL, IndexKind::BPlusTree, IndexKind::Sorted, IndexKind::Array];
+    let mut rng = rand::thread_rng();
+    let keys: Vec<i64> = (0..n).map(|_| rng.gen_range(0, n)).collect();
+
+    for kind in kinds.iter() {
+        let mut idx = kind.new_index::<i64>();
+        let start = PreciseTime::now();
+        for (p, &k) in keys.iter().enumerate() {
+            idx.insert(k, p);
+        }
+        let inserted = PreciseTime::now();
+        let mut found = 0;
+        for &k in &keys {
+            found += idx.lower_bound(&k).unwrap_or(0) + idx.upper_bound(&(k + 1)).unwrap_or(0);
+        }
+        let end = PreciseTime::now();
+        println!("{:?}: {} inserts in {}, {} lookups in {} ({})", kind, n, start.to(inserted), 2 * n, inserted.to(end), found);
+    }
+
+    let mut times = vec![Vec::new(); kinds.len()];
+    for j in 0..i {
+        let start = PreciseTime::now();
+        let (src, dst) = datagen::randomly_connected_tree(n);
+        let start_node = rand::thread_rng().gen_range(1, n);
+        println!("Created tree {} after {} seconds", j, start.to(PreciseTime::now()));
+
+        for (k, kind) in kinds.iter().enumerate() {
+            let mut adjacency_list = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+            adjacency_list.set_cracker_index(*kind);
+            let start = PreciseTime::now();
+            bfs::cracked_bfs_adjl(&mut adjacency_list, start_node);
+            times[k].push(start.to(PreciseTime::now()));
+        }
+    }
+
+    for (k, kind) in kinds.iter().enumerate() {
+        let avg: Duration = times[k].iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
+        println!("{:?} traversal: {}", kind, avg);
+    }
+}
+
+// Compares minimum piece sizes by the average over (i) runs of a traversal of a random tree of size
+// (n) under a decomposed table, along with the number of pieces, and so of cracker index entries, and
+// the index depth each leaves behind.
+fn min_piece_sizes(n: i64, i: usize) {
+    let sizes = [0, 8, 32, 128, 512];
+    let mut times = vec![Vec::new(); sizes.len()];
+    let mut stats = Vec::new();
+    for j in 0..i {
+        let start = PreciseTime::now();
+        let (src, dst) = datagen::randomly_connected_tree(n);
+        let start_node = rand::thread_rng().gen_range(1, n);
+        println!("Created tree {} after {} seconds", j, start.to(PreciseTime::now()));
+
+        stats.clear();
+        for (k, &size) in sizes.iter().enumerate() {
+            let mut adjacency_list = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+            adjacency_list.set_min_piece_size(size);
+            let start = PreciseTime::now();
+            bfs::cracked_bfs_adjl(&mut adjacency_list, start_node);
+            times[k].push(start.to(PreciseTime::now()));
+            stats.push(adjacency_list.stats());
+        }
+    }
+
+    for (k, size) in sizes.iter().enumerate() {
+        let avg: Duration = times[k].iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
+        println!("Min piece size {}: traversal {}, {} pieces, index depth {}", size, avg, stats[k].pieces, stats[k].index_depth);
+    }
+}
+
+// Compares the whole-table compression schemes with per-piece adaptive compression by the average
+// over (i) runs of two traversals of a random graph of (n) nodes and density (d), whose hubs have many
+// edges. The second traversal reads pieces the first has already compressed.
+fn adaptive_schemes(n: i64, d: f64, i: usize) {
+    let names = ["Decracked", "Coco", "Underswap", "Adaptive"];
+    let mut times = vec![Vec::new(); names.len()];
+    let mut adaptive = adaptive_compression::AdaptiveTable::new();
+    for j in 0..i {
+        let start = PreciseTime::now();
+        // The traversals number nodes from 1.
+        let (src, dst) = datagen::randomly_connected_graph(n, d);
+        let (src, dst): (Vec<i64>, Vec<i64>) = (src.iter().map(|x| x + 1).collect(), dst.iter().map(|x| x + 1).collect());
+        let start_node = src[rand::thread_rng().gen_range(0, src.len())];
+        println!("Created graph {} after {} seconds", j, start.to(PreciseTime::now()));
+
+        let mut decracked = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+        let mut coco = compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+        let mut underswap = underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+        adaptive = adaptive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+        let start = PreciseTime::now();
+        for _ in 0..2 {
+            bfs::cracked_bfs_adjl(&mut decracked, start_node);
+        }
+        times[0].push(start.to(PreciseTime::now()));
+        let start = PreciseTime::now();
+        for _ in 0..2 {
+            bfs::cracked_bfs_adjl(&mut coco, start_node);
+        }
+        times[1].push(start.to(PreciseTime::now()));
+        let start = PreciseTime::now();
+        for _ in 0..2 {
+            bfs::cracked_bfs_adjl(&mut underswap, start_node);
+        }
+        times[2].push(start.to(PreciseTime::now()));
+        let start = PreciseTime::now();
+        for _ in 0..2 {
+            bfs::cracked_bfs_adjl(&mut adaptive, start_node);
+        }
+        times[3].push(start.to(PreciseTime::now()));
+    }
+
+    for (k, name) in names.iter().enumerate() {
+        let avg: Duration = times[k].iter().fold(Duration::hours(0), |sum, val| sum + *val) / (i as i32);
+        println!("{:<10} {}", name, avg);
+    }
+    let (plain, rle, compacted) = adaptive.schemes();
+    println!("Adaptive pieces: {} plain, {} RLE, {} compacted, {} bytes", plain, rle, compacted, adaptive.size());
+}
+
+// Prints how far each table has converged after every (step) of (queries) random vertex lookups on a
+// random tree of size (n).
+fn convergence(n: i64, queries: usize, step: usize) {
+    let (src, dst) = datagen::randomly_connected_tree(n);
+    let lookups: Vec<i64> = (0..queries).map(|_| rand::thread_rng().gen_range(1, n)).collect();
+    convergence_of::<decomposed_cracking::DeCrackedTable>("Decracked", &src, &dst, &lookups, step);
+    convergence_of::<compactive_compression::CoCoTable>("Coco", &src, &dst, &lookups, step);
+    convergence_of::<underswap_rle_compression::UnderswapRLETable>("Underswap", &src, &dst, &lookups, step);
+}
+
+fn convergence_of<T: CrackedTable>(name: &str, src: &[i64], dst: &[i64], lookups: &[i64], step: usize) {
+    let mut adjacency_list = T::from_adjacency_vectors(src.to_vec(), dst.to_vec(), "src");
+    for (q, chunk) in lookups.chunks(step).enumerate() {
+        for &v in chunk {
+            adjacency_list.cracker_select_specific(v, "dst");
+        }
+        println!("{} after {} queries:\n{}", name, (q + 1) * step, adjacency_list.stats());
+    }
 }
 
 fn prep_graphviz(src: Vec<i64>, dst: Vec<i64>) {
-    print!("digraph {} ", "{");
+    print!("digraph {{ ");
     for i in 0..src.len() {
         print!("{} -> {}; ", src[i], dst[i]);
     }
-    print!("{}\n", "}");
+    println!("}}");
 }
 
 fn speed_test(sf: i16, mi: i16, n: i8) {
@@ -119,8 +325,8 @@ fn speed_test(sf: i16, mi: i16, n: i8) {
 
     for _ in 0..n {
         let (preclustered_ranks, _) = personrank::preclustered_personrank(vertices.clone(), src.clone(), dst.clone(), mi);
-        let (decracked_ranks, dt) = personrank::decracked_personrank(vertices.clone(), src.clone(), dst.clone(), mi);
-        let (reco_ranks, ut) = personrank::reco_personrank(vertices.clone(), src.clone(), dst.clone(), mi);
+        let (decracked_ranks, dt) = personrank::cracked_personrank::<decomposed_cracking::DeCrackedTable>("decracked", vertices.clone(), src.clone(), dst.clone(), mi);
+        let (reco_ranks, ut) = personrank::cracked_personrank::<recognitive_compression::ReCoTable>("reco", vertices.clone(), src.clone(), dst.clone(), mi);
 
         let diff = dt - ut;
         diffs.push(diff);
@@ -168,7 +374,7 @@ fn speed_test_tighten(selectivity: f64, n: usize) {
 
     let sum: Duration = ts.iter().fold(Duration::hours(0), |sum, val| sum + *val);
     let avg = sum / (ts.len() as i32);
-    println!("Average time {} to tighten over {} values at {} selectivity on the {} side", avg, count, selectivity, "low");
+    println!("Average time {} to tighten over {} values at {} selectivity on the low side", avg, count, selectivity);
 }
 
 fn time_low_tighten_1(crk: Vec<i64>, mut run_lengths: Vec<usize>, count: usize, mut p_low: usize, p_high: usize, x: i64) -> Duration {
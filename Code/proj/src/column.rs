@@ -1,6 +1,72 @@
 use cracker_index::AVLCrackerIndex;
+use cracker_index::CrackerIndex;
+use cracker_index::IndexKind;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use encoding::ForBlock;
+use encoding::ForIter;
+use encoding::RunIter;
+use encoding::RunLengthColumn;
 
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::mem;
 use std::slice::Iter;
+use std::ops::Range;
+use std::thread;
+use std::vec;
+
+// Pieces larger than this are cracked on several threads unless a table is told otherwise.
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 1 << 20;
+
+// The types of value a column can hold. Cracking needs only an order on the values and, for the
+// exclusive end of a range, the next value up.
+pub trait Element: Copy + PartialOrd + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    // Returns the smallest value greater than this one, or None for the greatest value, above which a
+    // range is left open.
+    fn successor(self) -> Option<Self>;
+}
+
+macro_rules! integer_element {
+    ($($t:ty),*) => {
+        $(impl Element for $t {
+            #[inline]
+            fn successor(self) -> Option<$t> {
+                self.checked_add(1)
+            }
+        })*
+    }
+}
+
+integer_element!(i32, u32, u64, i64);
+
+// Floating point columns must not hold NaN, which has no place in the order.
+impl Element for f64 {
+    #[inline]
+    fn successor(self) -> Option<f64> {
+        if self == f64::INFINITY { None } else { Some(self.next_up()) }
+    }
+}
+
+// Returns the half-open interval [L, H) of the values between LOW and HIGH, with inclusivity given by
+// INC_L and INC_H. H is None when the interval is open above, as it is when HIGH is the greatest
+// value and included. Returns None when no value lies between LOW and HIGH.
+pub fn half_open<T: Element>(low: T, high: T, inc_l: bool, inc_h: bool) -> Option<(T, Option<T>)> {
+    let l = if inc_l { low } else { low.successor()? };
+    let h = if inc_h { high.successor() } else { Some(high) };
+    match h {
+        Some(h) if h <= l => None,
+        _ => Some((l, h)),
+    }
+}
+
+// Returns whether X lies in the half-open interval [L, H), where an H of None leaves it open above.
+#[inline]
+pub fn in_half_open<T: Element>(x: T, l: T, h: Option<T>) -> bool {
+    l <= x && h.is_none_or(|h| x < h)
+}
 
 pub trait Column {
     type Item;
@@ -11,17 +77,17 @@ pub trait Column {
 }
 
 #[derive(Clone)]
-pub struct IntCol {
+pub struct Col<T: Element> {
     // Original
-    pub v: Vec<i64>,
+    pub v: Vec<T>,
 
     // Cracked
-    pub crk: Vec<i64>,
+    pub crk: Vec<T>,
 
     // Cracker index - for a value v, stores the index p such that
     // for all i < p: c[i] < v. That is - Every value before p in the column
     // is less than v.
-    pub crk_idx: AVLCrackerIndex,
+    pub crk_idx: Box<dyn CrackerIndex<T>>,
 
     // Base index - maintains an index into the base columns of the table for alignment
     // during tuple reconstruction.
@@ -30,34 +96,830 @@ pub struct IntCol {
     // Offset - compressed base_idx for reducing scan time in compactive compression.
     pub ofs: Vec<usize>,
 
+    // Frames - the base indices of the compacted entries of a compactive compression column, by entry,
+    // frame-of-reference encoded. An entry's base indices are its block of base_idx followed by its
+    // frame.
+    pub frames: Vec<ForBlock>,
+
     // Run lengths - stores the run lengths gathered during intra-fragment compression.
     pub run_lengths: Vec<usize>,
+
+    // Cracker maps - copies of payload columns, by name, which are reorganised in lock-step with crk
+    // so that tuple reconstruction needs no base index.
+    pub crk_maps: HashMap<String, Vec<T>>,
+
+    // RLE maps - cracker maps held run-length encoded, for payload columns which fall into long runs
+    // once crk is cracked, such as the destinations of a hub's duplicate edges.
+    pub rle_maps: HashMap<String, RunLengthColumn<T>>,
+
+    // Pending inserts - base indices of the rows added since crk was built. Each is merged into crk by
+    // the first query whose range covers its value.
+    pub pending: Vec<usize>,
+
+    // Pending deletes - base indices of the deleted rows which are still in crk. Each is removed from
+    // crk by the first query whose range covers its value.
+    pub pending_deletes: Vec<usize>,
+
+    // Sorted - whether crk has converged to a sorted column, in which case the cracker index is
+    // empty and ranges are found by binary search.
+    pub sorted: bool,
+
+    // Queries left before converge next looks over the pieces of crk.
+    pub convergence_countdown: usize,
 }
 
-use std::ptr;
+// The column of 64 bit integers which every table but DeCrackedTable is built from.
+pub type IntCol = Col<i64>;
 
-impl IntCol {
+// Removes the base indices in ROWS whose values in V lie in [L, H), returning them sorted by value.
+fn take_rows_in_range<T: Element>(rows: &mut Vec<usize>, v: &[T], l: T, h: Option<T>) -> Vec<usize> {
+    let (mut taken, rest): (Vec<usize>, Vec<usize>) = rows.iter().partition(|&&i| in_half_open(v[i], l, h));
+    *rows = rest;
+    taken.sort_by(|&i, &j| v[i].partial_cmp(&v[j]).unwrap());
+    taken
+}
+
+impl<T: Element> Col<T> {
+    // Swaps the elements at A and B, along with their base indices and cracker map entries. Once a
+    // compactive column has offsets, its elements are compressed entries, each of which must hold a
+    // single base index at the start of its block.
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.crk.swap(a, b);
+        if self.ofs.is_empty() {
+            self.base_idx.swap(a, b);
+        } else {
+            self.base_idx.swap(self.ofs[a], self.ofs[b]);
+        }
+        for map in self.crk_maps.values_mut() {
+            map.swap(a, b);
+        }
+        for map in self.rle_maps.values_mut() {
+            map.swap(a, b);
+        }
+    }
+
+    // Returns the positions of the pieces crk is cracked into, in order. Sorted crk is cracked into a
+    // piece per value.
+    pub fn pieces(&self) -> Vec<Range<usize>> {
+        if self.sorted {
+            let mut pieces = Vec::new();
+            let mut start = 0;
+            while start < self.crk.len() {
+                let end = start + self.crk[start..].partition_point(|&c| c <= self.crk[start]);
+                pieces.push(start..end);
+                start = end;
+            }
+            return pieces;
+        }
+        let mut bounds: Vec<usize> = self.crk_idx.pairs().into_iter().map(|(_, p)| p).filter(|&p| p < self.crk.len()).collect();
+        bounds.push(0);
+        bounds.push(self.crk.len());
+        bounds.sort();
+        bounds.dedup();
+        bounds.windows(2).map(|w| w[0]..w[1]).collect()
+    }
+
+    // Returns statistics on the pieces of crk, each element of which is one tuple.
+    pub fn stats(&self) -> CrackStats {
+        let pieces = self.pieces().into_iter().map(|r| {
+            let piece = &self.crk[r];
+            (piece.len(), piece.iter().all(|&c| c == piece[0]))
+        });
+        CrackStats::new(pieces, self.crk_idx.depth())
+    }
+
+    // Returns the number of runs recorded in run_lengths, each of which holds its length at its first
+    // element.
+    pub fn recognised_runs(&self) -> usize {
+        let mut runs = 0;
+        let mut p = 0;
+        while p < self.run_lengths.len() {
+            runs += 1;
+            p += cmp::max(self.run_lengths[p], 1);
+        }
+        runs
+    }
+
+    // Moves the cracker index into IDX, which holds it from then on.
+    pub fn set_index(&mut self, mut idx: Box<dyn CrackerIndex<T>>) {
+        for (k, p) in self.crk_idx.pairs() {
+            idx.insert(k, p);
+        }
+        self.crk_idx = idx;
+    }
+
+    // Returns the elements of the column NAME, whose values are COLUMN, at the positions R of crk. They
+    // are read from the cracker map of NAME where there is one, decoded from its RLE map where there is
+    // one of those, and through the base index otherwise.
+    pub fn values<'a>(&'a self, r: Range<usize>, name: &str, column: &'a [T]) -> Values<'a, T> {
+        match (self.crk_maps.get(name), self.rle_maps.get(name)) {
+            (Some(map), _) => Values::Mapped(map[r].iter()),
+            (None, Some(map)) => Values::Runs(map.iter(r)),
+            (None, None) => Values::Indexed(self.base_idx[r].iter(), column),
+        }
+    }
+
+    // Returns the positions in base_idx of the block of compressed entry E.
+    pub fn block(&self, e: usize) -> Range<usize> {
+        let end = if e + 1 < self.ofs.len() { self.ofs[e + 1] } else { self.base_idx.len() };
+        self.ofs[e]..end
+    }
+
+    // Returns the number of base indices held by the compressed entries R.
+    pub fn entries_len(&self, r: Range<usize>) -> usize {
+        if r.start >= r.end {
+            return 0;
+        }
+        let plain = self.block(r.end - 1).end - self.ofs[r.start];
+        plain + self.frames[r].iter().map(|f| f.len()).sum::<usize>()
+    }
+
+    // Returns the base indices held by the compressed entries R, skipping the entries whose values lie
+    // outside [L, H) if a FILTER is given.
+    pub fn entry_base_indices(&self, r: Range<usize>, filter: Option<(T, Option<T>)>) -> EntryBaseIndices<'_, T> {
+        EntryBaseIndices { col: self, entries: r, filter, plain: [].iter(), frame: None }
+    }
+
+    // Returns the elements of the column NAME, whose values are COLUMN, at the positions R of crk
+    // whose values lie in [L, H).
+    pub fn filtered_values<'a>(&'a self, r: Range<usize>, l: T, h: Option<T>, column: &'a [T]) -> Values<'a, T> {
+        Values::Filtered { base_idx: self.base_idx[r].iter(), keys: &self.v, column, l, h }
+    }
+
+    // Returns the positions of the pieces holding the elements in [L, H) if, between them, they hold
+    // no more than MIN_PIECE_SIZE elements, in which case they are better scanned than cracked. Pieces
+    // cracked out for [L, H) already, and sorted crk, are never scanned, and nothing is with a
+    // MIN_PIECE_SIZE of 0.
+    pub fn small_piece(&self, l: T, h: Option<T>, min_piece_size: usize) -> Option<Range<usize>> {
+        let cracked = self.crk_idx.contains(l) && h.is_none_or(|h| self.crk_idx.contains(h));
+        if min_piece_size == 0 || self.sorted || h.is_some_and(|h| h <= l) || cracked {
+            return None;
+        }
+        let start = self.crk_idx.lower_bound(&l).unwrap_or(0);
+        let end = self.upper_end(h);
+        if end - start <= min_piece_size { Some(start..end) } else { None }
+    }
+
+    // Queues the rows at base indices ROWS for merging into crk, taking their values from COLUMN.
+    pub fn add_pending(&mut self, column: &[T], rows: Range<usize>) {
+        for i in rows {
+            self.v.push(column[i]);
+            self.pending.push(i);
+        }
+    }
+
+    // Removes the pending inserts whose values lie in [L, H), returning their base indices sorted by value.
+    pub fn take_pending(&mut self, l: T, h: Option<T>) -> Vec<usize> {
+        take_rows_in_range(&mut self.pending, &self.v, l, h)
+    }
+
+    // Removes the pending deletes whose values lie in [L, H), returning their base indices sorted by value.
+    pub fn take_pending_deletes(&mut self, l: T, h: Option<T>) -> Vec<usize> {
+        take_rows_in_range(&mut self.pending_deletes, &self.v, l, h)
+    }
+
+    // Returns the positions of the elements of sorted crk which lie in [L, H).
+    pub fn sorted_range(&self, l: T, h: Option<T>) -> Range<usize> {
+        let start = self.crk.partition_point(|&c| c < l);
+        let end = match h {
+            Some(h) => start + self.crk[start..].partition_point(|&c| c < h),
+            None => self.crk.len(),
+        };
+        start..end
+    }
+
+    // Returns the positions of the piece of crk which holds VALUE, or of the elements equal to VALUE
+    // once crk is sorted.
+    pub fn piece_of(&self, value: T) -> Range<usize> {
+        if self.sorted {
+            return self.sorted_range(value, value.successor());
+        }
+        let start = self.crk_idx.lower_bound(&value).unwrap_or(0);
+        start..self.upper_end(value.successor())
+    }
+
+    // Returns the end of the piece of crk holding the values just below H, which for an H of None is
+    // the end of crk.
+    fn upper_end(&self, h: Option<T>) -> usize {
+        h.and_then(|h| self.crk_idx.upper_bound(&h)).unwrap_or(self.crk.len())
+    }
+
+    // Sorts crk and drops the cracker index once no piece holds more than THRESHOLD elements, or
+    // every piece is already in order. The pieces are only looked over once every so many queries,
+    // as many as there are pieces, so that the check costs nothing much per query. A THRESHOLD of 0
+    // leaves crk cracked for good.
+    pub fn converge(&mut self, threshold: usize) {
+        if self.sorted || threshold == 0 {
+            return;
+        }
+        if self.convergence_countdown > 0 {
+            self.convergence_countdown -= 1;
+            return;
+        }
+        let pieces = self.pieces();
+        self.convergence_countdown = pieces.len();
+        let small = pieces.iter().all(|r| r.len() <= threshold);
+        if !small && !pieces.iter().all(|r| self.crk[r.clone()].windows(2).all(|w| w[0] <= w[1])) {
+            return;
+        }
+
+        for r in pieces {
+            let mut order: Vec<usize> = r.clone().collect();
+            order.sort_by(|&i, &j| self.crk[i].partial_cmp(&self.crk[j]).unwrap());
+            self.reorder(r.start, r.end, &order);
+        }
+        self.crk_idx.clear();
+        self.sorted = true;
+        if !self.run_lengths.is_empty() {
+            // Every run of equal values is now one run, with its length at both ends.
+            self.run_lengths = vec![1; self.crk.len()];
+            for r in self.pieces() {
+                self.run_lengths[r.start] = r.len();
+                self.run_lengths[r.end - 1] = r.len();
+            }
+        }
+    }
+
+    // Returns a new cracker column over the base column values V, holding every row not in DELETED in
+    // base index order, with an empty cracker index of the given KIND.
+    pub fn cracking(v: &[T], deleted: &HashSet<usize>, kind: IndexKind) -> Col<T> {
+        let mut c = Col::empty();
+        c.v = v.to_vec();
+        c.base_idx = (0..v.len()).filter(|i| !deleted.contains(i)).collect();
+        c.crk = c.base_idx.iter().map(|&i| v[i]).collect();
+        c.crk_idx = kind.new_index();
+        c
+    }
+
+    // Moves this cracker column, on NAME, into PARKED, leaving an empty column in its place. A table
+    // has a column on no name before its first cracker column is set, and that one is dropped.
+    pub fn park(&mut self, name: &str, parked: &mut HashMap<String, Col<T>>) {
+        let current = mem::replace(self, Col::empty());
+        if !name.is_empty() {
+            parked.insert(name.to_string(), current);
+        }
+    }
+
+    // Swaps this cracker column, on NAME, for the one in PARKED on COL_NAME, parking this one and
+    // setting NAME to COL_NAME. Returns false, changing nothing, if there is no such column to swap in.
+    pub fn unpark(&mut self, name: &mut String, col_name: &str, parked: &mut HashMap<String, Col<T>>) -> bool {
+        if name == col_name {
+            return true;
+        }
+        match parked.remove(col_name) {
+            Some(c) => {
+                let current = mem::replace(self, c);
+                parked.insert(mem::replace(name, col_name.to_string()), current);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Deletes the row at base index I from crk: straight away if it has yet to be merged, and
+    // otherwise by the first query whose range covers its value.
+    pub fn delete(&mut self, i: usize) {
+        match self.pending.iter().position(|&j| j == i) {
+            Some(p) => { self.pending.remove(p); },
+            None => self.pending_deletes.push(i),
+        }
+    }
+
+    // Merges the pending deletes and inserts whose values lie in [L, H) into crk. A deleted row is
+    // taken out of the piece holding its value and an inserted row goes at the end of it. Rather than
+    // shifting everything after the piece along, the hole left behind, or the slot needed, is rippled
+    // through the later pieces by moving one element across each of their boundaries, and each
+    // boundary key moves by one. Sorted crk, and crk with run lengths whose runs must stay whole,
+    // shift their elements instead, with the elements equal to the row's value standing in for its
+    // piece once crk is sorted. COLUMNS supplies the inserted rows' values for the cracker maps.
+    pub fn merge_pending(&mut self, l: T, h: Option<T>, columns: &HashMap<String, Col<T>>) {
+        let ripple = !self.sorted && self.run_lengths.is_empty();
+        for i in self.take_pending_deletes(l, h) {
+            let value = self.v[i];
+            let Range { start, end } = self.piece_of(value);
+            let p = start + self.base_idx[start..end].iter().position(|&j| j == i).unwrap();
+            if ripple {
+                // Move the row to the end of crk, one piece at a time, and drop it from there.
+                let mut hole = p;
+                for b in self.starts_after(value).into_iter().chain(Some(self.crk.len())) {
+                    if b > hole {
+                        self.swap(hole, b - 1);
+                        hole = b - 1;
+                    }
+                }
+                self.crk.pop();
+                self.base_idx.pop();
+                for map in self.crk_maps.values_mut() {
+                    map.pop();
+                }
+                for map in self.rle_maps.values_mut() {
+                    map.remove(hole);
+                }
+                self.crk_idx.subtract_where_greater_than(value, 1);
+                continue;
+            }
+            self.crk.remove(p);
+            self.base_idx.remove(p);
+            if !self.run_lengths.is_empty() {
+                // The run which held the row is now shorter than its markers say, so forget the runs
+                // of the piece; the next query on them finds them again.
+                self.run_lengths.remove(p);
+                for rl in self.run_lengths[start..(end - 1)].iter_mut() {
+                    *rl = 1;
+                }
+            }
+            for map in self.crk_maps.values_mut() {
+                map.remove(p);
+            }
+            for map in self.rle_maps.values_mut() {
+                map.remove(p);
+            }
+            self.crk_idx.subtract_where_greater_than(value, 1);
+        }
+
+        for i in self.take_pending(l, h) {
+            let value = self.v[i];
+            if ripple {
+                // Add the row at the end of crk and move it down to its piece, one piece at a time.
+                let mut hole = self.crk.len();
+                self.crk.push(value);
+                self.base_idx.push(i);
+                for (name, map) in self.crk_maps.iter_mut() {
+                    map.push(columns[name].v[i]);
+                }
+                for (name, map) in self.rle_maps.iter_mut() {
+                    map.insert(hole, columns[name].v[i]);
+                }
+                for s in self.starts_after(value).into_iter().rev() {
+                    if s < hole {
+                        self.swap(s, hole);
+                        hole = s;
+                    }
+                }
+                self.crk_idx.add_where_greater_than(value, 1);
+                continue;
+            }
+            let p = self.piece_of(value).end;
+            self.crk.insert(p, value);
+            self.base_idx.insert(p, i);
+            if !self.run_lengths.is_empty() {
+                self.run_lengths.insert(p, 1);
+            }
+            for (name, map) in self.crk_maps.iter_mut() {
+                map.insert(p, columns[name].v[i]);
+            }
+            for (name, map) in self.rle_maps.iter_mut() {
+                map.insert(p, columns[name].v[i]);
+            }
+            self.crk_idx.add_where_greater_than(value, 1);
+        }
+    }
+
+    // Returns the start of each piece after the one holding VALUE, in order, leaving out empty pieces.
+    fn starts_after(&self, value: T) -> Vec<usize> {
+        let mut starts: Vec<usize> = self.crk_idx.pairs().into_iter().filter(|&(k, _)| k > value).map(|(_, p)| p).collect();
+        starts.dedup();
+        starts
+    }
+
+    // Cracks the piece containing V in two, so that every element before the returned position is
+    // less than V and every element from it onwards is at least V. The position is stored against V
+    // in the cracker index. A column with run lengths is partitioned stably, so that its runs stay
+    // whole, and a piece of more than PARALLEL_THRESHOLD elements is partitioned on several threads.
+    pub fn crack_in_two(&mut self, v: T, parallel_threshold: usize) -> usize {
+        if let Some(p) = self.crk_idx.get(v) {
+            return p;
+        }
+        let start = self.crk_idx.lower_bound(&v).unwrap_or(0);
+        let end = self.crk_idx.upper_bound(&v).unwrap_or(self.crk.len());
+        let p = match (self.run_lengths.is_empty(), self.parallel(end - start, parallel_threshold)) {
+            (true, false) => self.partition_in_two(start, end, v),
+            (true, true) => self.partition_parallel(start, end, v, worker_threads()),
+            (false, false) => self.partition_stable(start, end, v, v).0,
+            (false, true) => self.partition_stable_parallel(start, end, v, v, worker_threads()).0,
+        };
+        self.crk_idx.insert(v, p);
+        p
+    }
+
+    // Cracks a single piece into three around the half-open interval [L, H). Both L and H must fall
+    // within the same piece. Returns the positions stored against L and H.
+    pub fn crack_in_three(&mut self, l: T, h: T, parallel_threshold: usize) -> (usize, usize) {
+        let start = self.crk_idx.lower_bound(&l).unwrap_or(0);
+        let end = self.crk_idx.upper_bound(&h).unwrap_or(self.crk.len());
+        let (p_low, p_high) = match (self.run_lengths.is_empty(), self.parallel(end - start, parallel_threshold)) {
+            (true, false) => self.partition_in_three(start, end, l, h),
+            // The parallel kernel splits in two, so a large piece is cracked twice instead.
+            (true, true) => return (self.crack_in_two(l, parallel_threshold), self.crack_in_two(h, parallel_threshold)),
+            (false, false) => self.partition_stable(start, end, l, h),
+            (false, true) => self.partition_stable_parallel(start, end, l, h, worker_threads()),
+        };
+        self.crk_idx.insert(l, p_low);
+        self.crk_idx.insert(h, p_high);
+        (p_low, p_high)
+    }
+
+    // Cracks crk so that the values in [L, H) are contiguous, returning their positions. Bounds which
+    // fall within the same piece are cracked on in one pass, and an H of None needs no crack.
+    pub fn crack_range(&mut self, l: T, h: Option<T>, parallel_threshold: usize) -> (usize, usize) {
+        let h = match h {
+            Some(h) => h,
+            None => return (self.crack_in_two(l, parallel_threshold), self.crk.len()),
+        };
+        let same_piece = !self.crk_idx.contains(l) && !self.crk_idx.contains(h)
+            && self.crk_idx.lower_bound(&l) == self.crk_idx.lower_bound(&h)
+            && self.crk_idx.upper_bound(&l) == self.crk_idx.upper_bound(&h);
+        if same_piece {
+            self.crack_in_three(l, h, parallel_threshold)
+        } else {
+            (self.crack_in_two(l, parallel_threshold), self.crack_in_two(h, parallel_threshold))
+        }
+    }
+
+    // Makes the extra cracks called for by the stochastic mode of SETTINGS in the piece containing X,
+    // drawing any random pivots from its generator.
+    pub fn stochastic_crack(&mut self, x: T, settings: &mut CrackSettings) {
+        let mut cracks_made = 0;
+        let mut piece = 0..0;
+        loop {
+            let p = self.piece_of(x);
+            if p == piece {
+                return; // Nothing left to split, or the last crack failed to split the piece.
+            }
+            piece = p.clone();
+            match settings.stochastic.pivot(&self.crk[p], cracks_made, &mut settings.rng) {
+                Some(pivot) => self.crack_in_two(pivot, settings.parallel_threshold),
+                None => return,
+            };
+            cracks_made += 1;
+        }
+    }
+
+    // Returns whether a piece of LEN elements is partitioned on several threads. The parallel kernels
+    // move base indices by position, so a compactive column's entries are partitioned on one.
+    fn parallel(&self, len: usize, parallel_threshold: usize) -> bool {
+        len > parallel_threshold && self.ofs.is_empty()
+    }
+
+    // Partitions the elements from START up to (but excluding) END into those less than V and those
+    // at least V, returning the position at which the second partition begins.
+    fn partition_in_two(&mut self, start: usize, end: usize, v: T) -> usize {
+        let mut p_low = start;
+        let mut p_high = end;
+        while p_low < p_high {
+            if self.crk[p_low] < v {
+                p_low += 1;
+            } else if self.crk[p_high - 1] >= v {
+                p_high -= 1;
+            } else {
+                self.swap(p_low, p_high - 1);
+                p_low += 1;
+                p_high -= 1;
+            }
+        }
+        p_low
+    }
+
+    // Partitions the elements from START up to (but excluding) END into those less than L, those in
+    // [L, H) and those at least H in one pass, returning the positions at which the second and third
+    // partitions begin.
+    fn partition_in_three(&mut self, start: usize, end: usize, l: T, h: T) -> (usize, usize) {
+        let mut p_low = start;
+        let mut p_high = end;
+        let mut p_itr = start;
+        while p_itr < p_high {
+            if self.crk[p_itr] < l {
+                self.swap(p_low, p_itr);
+                p_low += 1;
+                p_itr += 1;
+            } else if self.crk[p_itr] >= h {
+                p_high -= 1;
+                self.swap(p_itr, p_high);
+            } else {
+                p_itr += 1;
+            }
+        }
+        (p_low, p_high)
+    }
+
+    // Swaps the L elements starting at A with the L elements starting at B. The ranges may overlap,
+    // in which case the swap is performed element by element from the front.
     #[inline]
     pub fn swap_range(&mut self, l: usize, a: usize, b: usize) {
-        unsafe {
-            ptr::swap_nonoverlapping(&mut self.crk[a], &mut self.crk[b], l);
-            ptr::swap_nonoverlapping(&mut self.base_idx[a], &mut self.base_idx[b], l);
-            ptr::swap_nonoverlapping(&mut self.run_lengths[a], &mut self.run_lengths[b], l);
+        for i in 0..l {
+            self.crk.swap(a + i, b + i);
+            self.base_idx.swap(a + i, b + i);
+            self.run_lengths.swap(a + i, b + i);
+            for map in self.crk_maps.values_mut() {
+                map.swap(a + i, b + i);
+            }
+        }
+        // RLE maps swap the ranges whole, so that each run is split and merged once.
+        for map in self.rle_maps.values_mut() {
+            map.swap_range(l, a, b);
+        }
+    }
+
+    // Stably partitions the elements from START up to (but excluding) END into those less than L,
+    // those in [L, H) and those at least H, returning the positions at which the second and third
+    // partitions begin. Runs stay contiguous, so their run length markers remain valid.
+    pub fn partition_stable(&mut self, start: usize, end: usize, l: T, h: T) -> (usize, usize) {
+        let mut order = Vec::with_capacity(end - start);
+        let mut bounds = [start; 3];
+
+        for (partition, bound) in bounds.iter_mut().enumerate() {
+            for i in start..end {
+                let c = self.crk[i];
+                let p = if c < l { 0 } else if c >= h { 2 } else { 1 };
+                if p == partition {
+                    order.push(i);
+                }
+            }
+            *bound = start + order.len();
+        }
+
+        self.reorder(start, end, &order);
+        (bounds[0], bounds[1])
+    }
+
+    // As partition_stable, but with the elements classified on THREADS threads. Each thread splits
+    // a chunk of the piece into its three partitions, and the chunks' partitions are then laid out
+    // in chunk order, which is the order partition_stable gives.
+    pub fn partition_stable_parallel(&mut self, start: usize, end: usize, l: T, h: T, threads: usize) -> (usize, usize) {
+        if end <= start {
+            return (start, start);
+        }
+        let size = (end - start).div_ceil(threads.max(1));
+        let crk = &self.crk;
+        let chunks: Vec<[Vec<usize>; 3]> = thread::scope(|s| {
+            let handles: Vec<_> = (start..end).step_by(size).map(|from| s.spawn(move || {
+                let mut partitions = [Vec::new(), Vec::new(), Vec::new()];
+                let to = (from + size).min(end);
+                for (i, &c) in crk[from..to].iter().enumerate() {
+                    partitions[if c < l { 0 } else if c >= h { 2 } else { 1 }].push(from + i);
+                }
+                partitions
+            })).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        let mut order = Vec::with_capacity(end - start);
+        let mut bounds = [start; 2];
+        for partition in 0..3 {
+            for chunk in chunks.iter() {
+                order.extend_from_slice(&chunk[partition]);
+            }
+            if partition < 2 {
+                bounds[partition] = start + order.len();
+            }
+        }
+
+        self.reorder(start, end, &order);
+        (bounds[0], bounds[1])
+    }
+
+    // Partitions the elements from START up to (but excluding) END into those less than V and those
+    // at least V, returning the position at which the second partition begins. The piece is split
+    // into a chunk per thread, each chunk is partitioned on a thread of its own, and the elements
+    // left on the wrong side of the split are then swapped across it. Run lengths are not moved.
+    pub fn partition_parallel(&mut self, start: usize, end: usize, v: T, threads: usize) -> usize {
+        if end <= start {
+            return start;
+        }
+        let size = (end - start).div_ceil(threads.max(1));
+        // RLE maps are decoded for the threads to partition and encoded again afterwards.
+        let mut decoded: Vec<(String, Vec<T>)> = self.rle_maps.iter()
+            .map(|(name, map)| (name.clone(), map.decode(start..end)))
+            .collect();
+        let mut maps: Vec<_> = self.crk_maps.values_mut().map(|map| map[start..end].chunks_mut(size))
+            .chain(decoded.iter_mut().map(|&mut (_, ref mut map)| map[..].chunks_mut(size)))
+            .collect();
+        let chunks: Vec<_> = self.crk[start..end].chunks_mut(size)
+            .zip(self.base_idx[start..end].chunks_mut(size))
+            .map(|(crk, base_idx)| (crk, base_idx, maps.iter_mut().map(|m| m.next().unwrap()).collect::<Vec<_>>()))
+            .collect();
+        let splits: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = chunks.into_iter().map(|(crk, base_idx, mut maps)| s.spawn(move || {
+                partition_chunk(crk, base_idx, &mut maps, v)
+            })).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        for (name, map) in decoded {
+            self.rle_maps.get_mut(&name).unwrap().write(start, &map);
+        }
+
+        // Every element at least V before P belongs after it, and every element less than V after P
+        // belongs before it; there are as many of one as of the other.
+        let p = start + splits.iter().sum::<usize>();
+        let mut upper_before = Vec::new();
+        let mut lower_after = Vec::new();
+        for (k, &split) in splits.iter().enumerate() {
+            let from = start + k * size;
+            let to = (from + size).min(end);
+            upper_before.push((from + split)..to.min(p));
+            lower_after.push(p.max(from)..(from + split));
+        }
+        let misplaced: Vec<(usize, usize)> = upper_before.into_iter().flatten()
+            .zip(lower_after.into_iter().flatten())
+            .collect();
+        for (a, b) in misplaced {
+            self.swap(a, b);
         }
+        p
     }
+
+    // Lays out the elements from START up to (but excluding) END in the order of the positions in
+    // ORDER, moving their base indices, run lengths and cracker map entries along with them.
+    fn reorder(&mut self, start: usize, end: usize, order: &[usize]) {
+        let crk: Vec<T> = order.iter().map(|&i| self.crk[i]).collect();
+        let base_idx: Vec<usize> = order.iter().map(|&i| self.base_idx[i]).collect();
+        self.crk[start..end].copy_from_slice(&crk);
+        self.base_idx[start..end].copy_from_slice(&base_idx);
+        if !self.run_lengths.is_empty() {
+            let run_lengths: Vec<usize> = order.iter().map(|&i| self.run_lengths[i]).collect();
+            self.run_lengths[start..end].copy_from_slice(&run_lengths);
+        }
+        for map in self.crk_maps.values_mut() {
+            let reordered: Vec<T> = order.iter().map(|&i| map[i]).collect();
+            map[start..end].copy_from_slice(&reordered);
+        }
+        for map in self.rle_maps.values_mut() {
+            let reordered: Vec<T> = order.iter().map(|&i| map.get(i)).collect();
+            map.write(start, &reordered);
+        }
+    }
+}
+
+// Partitions a chunk of a piece into the elements less than V and those at least V, moving their
+// base indices and cracker map entries with them, and returns the size of the first partition.
+fn partition_chunk<T: Element>(crk: &mut [T], base_idx: &mut [usize], maps: &mut [&mut [T]], v: T) -> usize {
+    let mut p_low = 0;
+    let mut p_high = crk.len();
+    while p_low < p_high {
+        if crk[p_low] < v {
+            p_low += 1;
+        } else if crk[p_high - 1] >= v {
+            p_high -= 1;
+        } else {
+            crk.swap(p_low, p_high - 1);
+            base_idx.swap(p_low, p_high - 1);
+            for map in maps.iter_mut() {
+                map.swap(p_low, p_high - 1);
+            }
+            p_low += 1;
+            p_high -= 1;
+        }
+    }
+    p_low
+}
+
+// The base indices of a range of the compressed entries of a compactive compression column.
+pub struct EntryBaseIndices<'a, T: Element> {
+    col: &'a Col<T>,
+    entries: Range<usize>,
+    filter: Option<(T, Option<T>)>,
+    plain: Iter<'a, usize>,
+    frame: Option<ForIter<'a>>,
+}
+
+impl<'a, T: Element> Iterator for EntryBaseIndices<'a, T> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(&i) = self.plain.next() {
+                return Some(i);
+            }
+            if let Some(i) = self.frame.as_mut().and_then(|f| f.next()) {
+                return Some(i);
+            }
+            let e = self.entries.next()?;
+            if let Some((l, h)) = self.filter {
+                if !in_half_open(self.col.crk[e], l, h) {
+                    continue;
+                }
+            }
+            self.plain = self.col.base_idx[self.col.block(e)].iter();
+            self.frame = self.col.frames.get(e).map(|f| f.iter());
+        }
+    }
+}
+
+// The elements of a column at a range of positions of the cracker column, borrowed from the table
+// rather than gathered into a vector. Elements behind the base index are looked up as they are
+// iterated. The elements of a piece scanned rather than cracked are filtered as they are iterated,
+// keeping those whose cracker column value lies in [L, H). The elements of a range of compressed
+// entries are looked up through each entry's block and frame in turn. Those of an RLE map are
+// decoded run by run. Tables whose columns cannot be borrowed element by element, such as bit-packed
+// ones, hand over elements gathered into a vector.
+pub enum Values<'a, T: Element + 'a> {
+    Indexed(Iter<'a, usize>, &'a [T]),
+    Mapped(Iter<'a, T>),
+    Runs(RunIter<'a, T>),
+    Filtered { base_idx: Iter<'a, usize>, keys: &'a [T], column: &'a [T], l: T, h: Option<T> },
+    Entries(EntryBaseIndices<'a, T>, &'a [T]),
+    Gathered(vec::IntoIter<T>),
+}
+
+impl<'a, T: Element> Iterator for Values<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        match *self {
+            Values::Indexed(ref mut base_idx, column) => base_idx.next().map(|&i| column[i]),
+            Values::Mapped(ref mut map) => map.next().cloned(),
+            Values::Runs(ref mut map) => map.next(),
+            Values::Filtered { ref mut base_idx, keys, column, l, h } => {
+                base_idx.find(|&&i| in_half_open(keys[i], l, h)).map(|&i| column[i])
+            },
+            Values::Entries(ref mut base_idx, column) => base_idx.next().map(|i| column[i]),
+            Values::Gathered(ref mut values) => values.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match *self {
+            Values::Indexed(ref base_idx, _) => base_idx.size_hint(),
+            Values::Mapped(ref map) => map.size_hint(),
+            Values::Runs(ref map) => map.size_hint(),
+            Values::Filtered { ref base_idx, .. } => (0, base_idx.size_hint().1),
+            Values::Entries(..) => (0, None),
+            Values::Gathered(ref values) => values.size_hint(),
+        }
+    }
+}
+
+// The dictionary of a string column, which a table stores as an integer column of codes so that it
+// can be cracked like any other. Codes are handed out in order of first appearance, so equality on
+// codes is equality on strings, but a range of codes is not a range of strings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dictionary {
+    // The string of each code.
+    pub strings: Vec<String>,
+    codes: HashMap<String, i64>,
+}
+
+impl Dictionary {
+    pub fn new() -> Dictionary {
+        Dictionary { strings: Vec::new(), codes: HashMap::new() }
+    }
+
+    // Rebuilds a dictionary from the string of each code.
+    pub fn from_strings(strings: Vec<String>) -> Dictionary {
+        let codes = strings.iter().enumerate().map(|(c, s)| (s.clone(), c as i64)).collect();
+        Dictionary { strings, codes }
+    }
+
+    // Returns the code of S, giving it the next code if it has none yet.
+    pub fn encode(&mut self, s: &str) -> i64 {
+        if let Some(&c) = self.codes.get(s) {
+            return c;
+        }
+        let c = self.strings.len() as i64;
+        self.strings.push(s.to_string());
+        self.codes.insert(s.to_string(), c);
+        c
+    }
+
+    // Returns the code of S, or None if no row has ever held it.
+    pub fn code(&self, s: &str) -> Option<i64> {
+        self.codes.get(s).cloned()
+    }
+
+    pub fn decode(&self, code: i64) -> &str {
+        match self.strings.get(code as usize) {
+            Some(s) => s,
+            None => panic!("decode: no string has code {}", code),
+        }
+    }
+}
+
+// Returns the number of threads a parallel crack is split across.
+pub fn worker_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
 }
 
-impl Column for IntCol {
-    type Item = i64;
+impl<T: Element> Column for Col<T> {
+    type Item = T;
 
-    fn empty() -> IntCol {
-        IntCol {
+    fn empty() -> Col<T> {
+        Col {
             v: Vec::new(),
             crk:Vec::new(),
-            crk_idx: AVLCrackerIndex::new(),
+            crk_idx: Box::new(AVLCrackerIndex::new()),
             base_idx: Vec::new(),
             ofs: Vec::new(),
+            frames: Vec::new(),
             run_lengths: Vec::new(),
+            crk_maps: HashMap::new(),
+            rle_maps: HashMap::new(),
+            pending: Vec::new(),
+            pending_deletes: Vec::new(),
+            sorted: false,
+            convergence_countdown: 0,
         }
     }
 
@@ -70,17 +932,24 @@ impl Column for IntCol {
 
         // Could be optimised for nested queries
         self.crk = Vec::new();
-        self.crk_idx = AVLCrackerIndex::new();
+        self.crk_idx.clear();
         self.base_idx = Vec::new();
         self.ofs = Vec::new();
+        self.frames = Vec::new();
         self.run_lengths = Vec::new();
+        self.crk_maps = HashMap::new();
+        self.rle_maps = HashMap::new();
+        self.pending = Vec::new();
+        self.pending_deletes = Vec::new();
+        self.sorted = false;
+        self.convergence_countdown = 0;
     }
 
-    fn at(self, idx: usize) -> i64 {
+    fn at(self, idx: usize) -> T {
         self.v[idx]
     }
 
-    fn append(&mut self, values: &mut Vec<i64>) {
+    fn append(&mut self, values: &mut Vec<T>) {
         self.v.append(values);
     }
 }
\ No newline at end of file
@@ -3,11 +3,19 @@
 
 // uses map! macro.
 
-use cracker_index::AVLCrackerIndex;
 use column::Column;
+use column::Element;
 use column::IntCol;
+use column::Values;
+use column::half_open;
+use encoding::ForBlock;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem;
 use std::slice::Iter;
 use std::ops::Range;
 
@@ -16,6 +24,13 @@ pub struct CoCoTable {
     pub count: usize,
     pub crk_col_name: String,
     pub crk_col: IntCol,
+    // Cracker columns on other columns, parked by the name of the column they crack until use_crk_col
+    // switches to them.
+    pub crk_cols: HashMap<String, IntCol>,
+    pub settings: CrackSettings,
+    // Base indices of the deleted rows. Their values stay in the base columns, so that base indices
+    // remain valid.
+    pub deleted: HashSet<usize>,
     pub int_columns: HashMap<String, IntCol>,
 }
 
@@ -25,6 +40,9 @@ impl CoCoTable {
             count: 0,
             crk_col_name: "".to_string(),
             crk_col: IntCol::empty(),
+            crk_cols: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
             int_columns: HashMap::new(),
         }
     }
@@ -37,16 +55,24 @@ impl CoCoTable {
     }
 
     pub fn set_crk_col(&mut self, col_name: &str) {
-        self.crk_col_name = col_name.to_string();
-
-        match self.int_columns.get_mut(&(col_name.to_string())) {
-            Some(ref mut c) => {
-                self.crk_col.v = c.v.clone();
-                self.crk_col.crk = c.v.clone();
-                self.crk_col.base_idx = (0..self.count).collect();
-            },
+        // The current cracker column is parked, so that use_crk_col can switch back to it.
+        if self.crk_col_name != col_name {
+            self.crk_col.park(&self.crk_col_name, &mut self.crk_cols);
+        }
+        self.crk_cols.remove(col_name);
+        self.crk_col = match self.int_columns.get(col_name) {
+            Some(c) => IntCol::cracking(&c.v, &self.deleted, self.settings.index_kind),
             None => panic!("set_crk_col: no such col"),
         };
+        self.crk_col_name = col_name.to_string();
+    }
+
+    // Makes the cracker column on COL_NAME the one which queries crack, parking the current one. The
+    // cracker column is built if the table has none on COL_NAME yet.
+    pub fn use_crk_col(&mut self, col_name: &str) {
+        if !self.crk_col.unpark(&mut self.crk_col_name, col_name, &mut self.crk_cols) {
+            self.set_crk_col(col_name);
+        }
     }
 
     pub fn new_columns(&mut self, cols: Vec<&str>) {
@@ -73,7 +99,7 @@ impl CoCoTable {
         let check_inserted_column_lengths = |v: &Vec<i64>, l: usize| if v.len() != l { panic!("insert: (i64) Columns to be inserted do not have the same length") };
 
         for (k, v) in new_values.iter() {
-            if self.int_columns.contains_key(&(k.to_string())) {
+            if self.int_columns.contains_key(*k) {
                 match l_old {
                     Some(l) => check_inserted_column_lengths(v, l),
                     None => l_old = Some(v.len()),
@@ -104,21 +130,22 @@ impl CoCoTable {
         // For every old-column entry, append the values to the current column
         // For every new-column entry, create the column and add the values
         for (k, v) in new_values.iter_mut() {
-            if self.int_columns.contains_key(&(k.to_string())) {
-                let c = self.int_columns.get_mut(&(k.to_string())).unwrap();
-                c.append(v);
-            } else {
-                let mut new = IntCol::empty();
-                new.append(v);
-                self.int_columns.insert(k.to_string(), new);
-            }
+            self.int_columns.entry(k.to_string()).or_insert_with(IntCol::empty).append(v);
         }
 
         // Mark the increased size of the table
-        if l_new.is_some() {
-            self.count = l_new.unwrap();
-        } else {
-            self.count += l_old.unwrap();
+        let old_count = self.count;
+        match l_new {
+            Some(l) => self.count = l,
+            None    => self.count += l_old.unwrap(),
+        }
+
+        // Rows inserted after the cracker column is set are merged into it by the queries which need them
+        if !self.crk_col_name.is_empty() && self.count > old_count {
+            self.crk_col.add_pending(&self.int_columns[&self.crk_col_name].v, old_count..self.count);
+        }
+        for (name, c) in self.crk_cols.iter_mut() {
+            c.add_pending(&self.int_columns[name].v, old_count..self.count);
         }
     }
 
@@ -127,10 +154,13 @@ impl CoCoTable {
             col.rearrange(indices.clone());
         }
         self.crk_col.rearrange(indices.clone());
+        for c in self.crk_cols.values_mut() {
+            c.rearrange(indices.clone());
+        }
     }
 
     pub fn get_col(&self, col: &str) -> &IntCol {
-        self.int_columns.get(&(col.to_string())).expect(&*("get_col: No column called ".to_string() + col))
+        self.int_columns.get(col).unwrap_or_else(|| panic!("get_col: No column called {}", col))
     }
 
     pub fn get_indices(&self, base_indices: Iter<usize>, compressed_indices: Range<usize>) -> CoCoTable {
@@ -148,9 +178,10 @@ impl CoCoTable {
         let mut t = CoCoTable::new();
         t.int_columns = int_selection;
         t.count = base_indices.len();
+        t.settings = self.settings.clone();
 
         let mut indexed_crk_v = Vec::with_capacity(base_indices.len());
-        if self.crk_col.crk.len() > 0 {
+        if !self.crk_col.crk.is_empty() {
             let mut indexed_crk_col = Vec::with_capacity(base_indices.len());
             for i in compressed_indices.clone() {
                 indexed_crk_col.push(self.crk_col.crk[i]);
@@ -159,7 +190,7 @@ impl CoCoTable {
                 indexed_crk_v.push(self.crk_col.v[i]);
             }
             t.crk_col.crk = indexed_crk_col;
-            t.crk_col.crk_idx = AVLCrackerIndex::new();
+            t.crk_col.crk_idx = self.settings.index_kind.new_index();
         } else {
             for &i in base_indices.clone() {
                 indexed_crk_v.push(self.crk_col.v[i]);
@@ -177,63 +208,98 @@ impl CoCoTable {
         buf
     }
 
-    pub fn decompress_values(&self, compressed_index: usize, col: &str) -> Vec<i64> {
-        let offset = self.crk_col.ofs[compressed_index];
+    // Gives every entry of crk a block of base_idx, and an empty frame, if the column has yet to be
+    // compressed. Until then each entry of crk is the element of base_idx at the same position.
+    fn init_ofs(&mut self) {
+        if self.crk_col.ofs.is_empty() {
+            self.crk_col.ofs = (0..self.crk_col.crk.len()).collect();
+            self.crk_col.frames = vec![ForBlock::default(); self.crk_col.crk.len()];
+        }
+    }
 
-        let next_offset = if compressed_index >= self.crk_col.ofs.len() - 1 {
-            self.crk_col.base_idx.len()
-        } else {
-            self.crk_col.ofs[compressed_index + 1]
-        };
+    // Returns the number of tuples held by the compressed entries R.
+    pub fn tuple_count(&self, r: Range<usize>) -> usize {
+        if self.crk_col.ofs.is_empty() { r.len() } else { self.crk_col.entries_len(r) }
+    }
 
-        let index_range = offset..next_offset;
-        let mut base_indices = Vec::with_capacity(next_offset - offset);
-        for i in index_range {
-            base_indices.push(self.crk_col.base_idx[i]);
+    // Returns the elements of COL held by the compressed entries R, borrowing them from the table.
+    pub fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        if self.crk_col.ofs.is_empty() {
+            return self.crk_col.values(r, col, &self.int_columns[col].v);
         }
+        Values::Entries(self.crk_col.entry_base_indices(r, None), &self.int_columns[col].v)
+    }
 
-        let mut buf = Vec::new();
-        for i in base_indices {
-            buf.push(self.int_columns[&col.to_string()].v[i]);
-        }
-        buf
+    // Returns the compressed entries of the pieces holding the elements in [L, H) if, between them,
+    // they hold few enough tuples to be scanned rather than cracked.
+    pub fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        self.merge_pending(l, h);
+        let piece = self.crk_col.small_piece(l, h, usize::MAX)?;
+        if self.tuple_count(piece.clone()) <= self.settings.min_piece_size { Some(piece) } else { None }
+    }
+
+    // Returns the elements of COL held by those of the compressed entries R whose values lie in [L, H).
+    pub fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        Values::Entries(self.crk_col.entry_base_indices(r, Some((l, h))), &self.int_columns[col].v)
+    }
+
+    pub fn decompress_values(&self, compressed_index: usize, col: &str) -> Vec<i64> {
+        self.values(compressed_index..(compressed_index + 1), col).collect()
     }
 
-    // Compact the cracker column if there is an opportunity to do so, given the recent crk_idx addition of V->I
+    // Compact the cracker column if there is an opportunity to do so, given the recent crk_idx addition
+    // of V->I. A piece is only compacted if it holds more than one entry; a range crack can leave I at
+    // the first entry, with nothing below it.
     pub fn compact(&mut self, v: i64, i: usize) {
-        if self.crk_col.ofs.is_empty() {
-            self.crk_col.ofs = (0..self.count).collect();
+        self.init_ofs();
+        if let Some(j) = v.checked_add(1).and_then(|k| self.crk_col.crk_idx.get(k)) {
+            if i + 1 < j {
+                // Compress v
+                self.merge_entries(i, j);
+                self.crk_col.crk_idx.subtract_where_greater_than(v, j - i - 1);
+            }
         }
-        if let Some(j) = self.crk_col.crk_idx.get(v + 1) {
-            if i >= j - 1 { return; }
-            // Compress v
-            self.crk_col.crk.drain((i + 1)..j);
-            self.crk_col.ofs.drain((i + 1)..j);
-            self.crk_col.crk_idx.subtract_where_greater_than(v, j - i - 1);
+        if let Some(j) = v.checked_sub(1).and_then(|k| self.crk_col.crk_idx.get(k)) {
+            if j + 1 < i {
+                // Compress v - 1
+                self.merge_entries(j, i);
+                self.crk_col.crk_idx.subtract_where_greater_than(v - 1, i - j - 1);
+            }
         }
-        if let Some(j) = self.crk_col.crk_idx.get(v - 1) {
-            if j >= i - 1 { return; }
-            // Compress v - 1
-            self.crk_col.crk.drain((j + 1)..i);
-            self.crk_col.ofs.drain((j + 1)..i);
-            self.crk_col.crk_idx.subtract_where_greater_than(v - 1, i - j - 1);
+    }
+
+    // Merges the compressed entries from START up to (but excluding) END, which must share a value,
+    // into the entry at START. Their base indices are taken out of base_idx and encoded in the frame
+    // of the merged entry.
+    fn merge_entries(&mut self, start: usize, end: usize) {
+        let base_indices: Vec<usize> = self.crk_col.entry_base_indices(start..end, None).collect();
+        let plain = self.crk_col.ofs[start]..self.crk_col.block(end - 1).end;
+        let removed = plain.len();
+        self.crk_col.base_idx.drain(plain);
+        for o in self.crk_col.ofs[end..].iter_mut() {
+            *o -= removed;
         }
+        self.crk_col.frames[start] = ForBlock::encode(&base_indices);
+        self.crk_col.crk.drain((start + 1)..end);
+        self.crk_col.ofs.drain((start + 1)..end);
+        self.crk_col.frames.drain((start + 1)..end);
     }
 
-    pub fn decompress_index(&self, compressed_index: usize) -> CoCoTable {
-        let offset = self.crk_col.ofs[compressed_index];
+    // Returns the number of bytes taken up by the base indices of the cracker column, plain or
+    // encoded, and the offsets of their blocks.
+    pub fn base_idx_size(&self) -> usize {
+        let usizes = self.crk_col.base_idx.len() + self.crk_col.ofs.len();
+        usizes * mem::size_of::<usize>() + self.crk_col.frames.iter().map(|f| f.size()).sum::<usize>()
+    }
 
-        let next_offset = if compressed_index >= self.crk_col.ofs.len() - 1 {
-            self.crk_col.base_idx.len()
-        } else {
-            self.crk_col.ofs[compressed_index + 1]
-        };
+    // Returns the cracks made in the cracker column, with the compressed entry each key of the cracker
+    // index points to translated to the tuple it starts at.
+    pub fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_col.crk_idx.pairs().into_iter().map(|(k, e)| (k, self.tuple_count(0..e))).collect()
+    }
 
-        let index_range = offset..next_offset;
-        let mut base_indices = Vec::with_capacity(next_offset - offset);
-        for i in index_range {
-            base_indices.push(self.crk_col.base_idx[i]);
-        }
+    pub fn decompress_index(&self, compressed_index: usize) -> CoCoTable {
+        let base_indices: Vec<usize> = self.crk_col.entry_base_indices(compressed_index..(compressed_index + 1), None).collect();
 
         let mut int_selection: HashMap<String, IntCol> = HashMap::new();
         for (name, col) in &self.int_columns {
@@ -251,20 +317,31 @@ impl CoCoTable {
         t
     }
 
-    // Returns the elements of T where the cracker columns's value is between LOW and HIGH, with inclusivity given by INC_L and INC_H.
-    pub fn cracker_select_specific(&mut self, x: i64, col: &str) -> Vec<i64> {
+    // Cracks the column on X and X + 1, returning the compressed entries holding the elements equal to X.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        let x1 = match x.successor() {
+            Some(x1) => x1,
+            // Nothing lies above i64::MAX, so its elements are those from it onwards.
+            None => return self.crack_select_range(x, x, true, true),
+        };
         // PHASE 0: Setup
 
-        if self.crk_col.ofs.is_empty() {
-            self.crk_col.ofs = (0..self.count).collect();
+        self.merge_pending(x, Some(x1));
+        self.converge();
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(x, Some(x1));
         }
+        self.crk_col.stochastic_crack(x, &mut self.settings);
 
         let compressed_count = self.crk_col.crk.len();
+        if compressed_count == 0 {
+            return 0..0;
+        }
 
         // Start with a pointer at both ends of the array: p_low, p_high
 
         let mut p_low = self.crk_col.crk_idx.lower_bound(&x).unwrap_or(0);
-        let mut p_high = self.crk_col.crk_idx.upper_bound(&(x + 1)).unwrap_or((compressed_count - 1) as usize);
+        let mut p_high = self.crk_col.crk_idx.upper_bound(&x1).unwrap_or(compressed_count - 1);
         if p_high >= compressed_count { p_high = compressed_count - 1; }
         if p_low  >= compressed_count { p_low  = compressed_count - 1; }
 
@@ -273,39 +350,27 @@ impl CoCoTable {
         // while p_low is pointing at an element satisfying c_low,  move it forwards
         while self.crk_col.crk[p_low] < x {
             p_low += 1;
-            if p_low == compressed_count as usize {
-                return vec![];
+            if p_low == compressed_count {
+                return 0..0;
             }
         }
 
         // while p_high is pointing at an element satisfying c_high, move it backwards
         while self.crk_col.crk[p_high] > x {
             if p_high == 0 {
-                return vec![];
+                return 0..0;
             }
             p_high -= 1;
         }
 
         // If the vertex is compressed/contains a single entry, return.
         if p_low == p_high {
-            if p_low == (compressed_count - 1) {
-                if self.crk_col.crk_idx.contains(self.crk_col.crk[p_low]) && self.crk_col.crk_idx.contains(self.crk_col.crk[p_low] + 1) {
-                    return self.decompress_values(p_low, col);
-                } else {
-                    return self.get_values(vec![self.crk_col.base_idx[self.crk_col.ofs[p_low]]].iter(), col);
-                }
-            } else {
-                if self.crk_col.ofs[p_low + 1] - self.crk_col.ofs[p_low] > 1 {
-                    return self.decompress_values(p_low, col);
-                } else {
-                    return self.get_values(vec![self.crk_col.base_idx[self.crk_col.ofs[p_low]]].iter(), col);
-                }
-            }
+            return p_low..(p_low + 1);
         }
 
         // PHASE 2: Main loop
 
-        let mut p_itr = p_low.clone();
+        let mut p_itr = p_low;
 
         while p_itr <= p_high {
             if self.crk_col.crk[p_itr] < x {
@@ -315,7 +380,7 @@ impl CoCoTable {
                     p_low += 1;
                 }
                 if p_itr < p_low {
-                    p_itr = p_low.clone();
+                    p_itr = p_low;
                 }
             } else if self.crk_col.crk[p_itr] > x {
                 self.crk_col.crk.swap(p_itr, p_high);
@@ -332,8 +397,8 @@ impl CoCoTable {
         // PHASE 3: Compression
 
         if p_itr <= self.crk_col.crk.len() {
-            self.crk_col.crk_idx.insert(x + 1, p_itr);
-            self.compact(x + 1, p_itr);
+            self.crk_col.crk_idx.insert(x1, p_itr);
+            self.compact(x1, p_itr);
         }
 
         self.crk_col.crk_idx.insert(x, p_low);
@@ -342,15 +407,275 @@ impl CoCoTable {
         // PHASE 4: Decompression
 
         if p_low <= p_high {
-            self.decompress_values(p_low, col)
+            // Compacting the piece below X moves X's entry down, so look it up again.
+            let p_x = self.crk_col.crk_idx.get(x).unwrap();
+            p_x..(p_x + 1)
         } else {
-            vec![]
+            0..0
+        }
+    }
+
+    // Decompresses the values of COL for every compressed entry from P_LOW up to (but excluding) P_HIGH.
+    pub fn decompress_range(&self, p_low: usize, p_high: usize, col: &str) -> Vec<i64> {
+        self.values(p_low..p_high, col).collect()
+    }
+
+    // Merges the rows inserted since the cracker column was set whose values lie in [L, H) into it. A
+    // row whose value already has an isolated entry joins that entry's base indices, and any other row
+    // gets an entry of its own at the end of the piece holding its value. Once the column is sorted
+    // every value has an isolated entry.
+    fn merge_pending(&mut self, l: i64, h: Option<i64>) {
+        self.init_ofs();
+        for i in self.crk_col.take_pending_deletes(l, h) {
+            let value = self.crk_col.v[i];
+            let piece = self.crk_col.piece_of(value);
+            let e = match piece.clone().find(|&e| self.crk_col.entry_base_indices(e..(e + 1), None).any(|b| b == i)) {
+                Some(e) => e,
+                None => panic!("merge_pending: deleted row {} is not in its piece", i),
+            };
+            let block = self.crk_col.block(e);
+            match self.crk_col.base_idx[block.clone()].iter().position(|&b| b == i) {
+                Some(o) => {
+                    self.crk_col.base_idx.remove(block.start + o);
+                    for x in self.crk_col.ofs[e + 1..].iter_mut() {
+                        *x -= 1;
+                    }
+                },
+                None => self.crk_col.frames[e] = self.crk_col.frames[e].without(i).unwrap(),
+            }
+
+            if self.crk_col.entries_len(e..(e + 1)) == 0 {
+                self.crk_col.crk.remove(e);
+                self.crk_col.ofs.remove(e);
+                self.crk_col.frames.remove(e);
+                self.crk_col.crk_idx.subtract_where_greater_than(value, 1);
+            }
+        }
+        for i in self.crk_col.take_pending(l, h) {
+            let value = self.crk_col.v[i];
+            let p = self.crk_col.piece_of(value).end;
+            let isolated = p > 0 && if self.crk_col.sorted {
+                self.crk_col.crk[p - 1] == value
+            } else {
+                self.crk_col.crk_idx.get(value) == Some(p - 1)
+                    && value.successor().map_or(p == self.crk_col.crk.len(), |v1| self.crk_col.crk_idx.get(v1) == Some(p))
+            };
+            if isolated && !self.crk_col.frames[p - 1].is_empty() {
+                self.crk_col.frames[p - 1] = self.crk_col.frames[p - 1].push(i);
+                continue;
+            }
+
+            let offset = if p < self.crk_col.ofs.len() { self.crk_col.ofs[p] } else { self.crk_col.base_idx.len() };
+            self.crk_col.base_idx.insert(offset, i);
+            for o in self.crk_col.ofs[p..].iter_mut() {
+                *o += 1;
+            }
+            if !isolated {
+                self.crk_col.crk.insert(p, value);
+                self.crk_col.ofs.insert(p, offset);
+                self.crk_col.frames.insert(p, ForBlock::default());
+                self.crk_col.crk_idx.add_where_greater_than(value, 1);
+            }
+        }
+    }
+
+    // Rebuilds the cracker column as a sorted column once no piece holds more than
+    // convergence_threshold tuples, or every piece is already in order, and drops the cracker index.
+    // The sorted column holds each value once, with the base indices of its rows in its frame, or in
+    // base_idx for a value of a single row, so that a value's rows are found by a binary search of
+    // crk. As for Col::converge, the pieces are only looked over once every so many queries.
+    fn converge(&mut self) {
+        if self.crk_col.sorted || self.settings.convergence_threshold == 0 {
+            return;
         }
+        if self.crk_col.convergence_countdown > 0 {
+            self.crk_col.convergence_countdown -= 1;
+            return;
+        }
+        self.init_ofs();
+        let pieces = self.crk_col.pieces();
+        self.crk_col.convergence_countdown = pieces.len();
+        let small = pieces.iter().all(|r| self.tuple_count(r.clone()) <= self.settings.convergence_threshold);
+        if !small && !pieces.iter().all(|r| self.crk_col.crk[r.clone()].windows(2).all(|w| w[0] <= w[1])) {
+            return;
+        }
+
+        let mut entries: Vec<usize> = (0..self.crk_col.crk.len()).collect();
+        entries.sort_by_key(|&e| self.crk_col.crk[e]);
+        let mut values: Vec<(i64, Vec<usize>)> = Vec::new();
+        for e in entries {
+            let value = self.crk_col.crk[e];
+            if values.last().map(|&(v, _)| v) != Some(value) {
+                values.push((value, Vec::new()));
+            }
+            values.last_mut().unwrap().1.extend(self.crk_col.entry_base_indices(e..(e + 1), None));
+        }
+
+        let mut base_idx = Vec::new();
+        self.crk_col.crk = Vec::with_capacity(values.len());
+        self.crk_col.ofs = Vec::with_capacity(values.len());
+        self.crk_col.frames = Vec::with_capacity(values.len());
+        for (value, rows) in values {
+            self.crk_col.crk.push(value);
+            self.crk_col.ofs.push(base_idx.len());
+            if rows.len() == 1 {
+                base_idx.push(rows[0]);
+                self.crk_col.frames.push(ForBlock::default());
+            } else {
+                self.crk_col.frames.push(ForBlock::encode(&rows));
+            }
+        }
+        self.crk_col.base_idx = base_idx;
+        self.crk_col.crk_idx.clear();
+        self.crk_col.sorted = true;
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning the compressed entries holding them.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        self.merge_pending(l, h);
+        if self.crk_col.crk.is_empty() {
+            return 0..0;
+        }
+        self.converge();
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(l, h);
+        }
+        self.crk_col.stochastic_crack(l, &mut self.settings);
+        if let Some(h) = h {
+            self.crk_col.stochastic_crack(h, &mut self.settings);
+        }
+        let (p_low, p_high) = self.crk_col.crack_range(l, h, self.settings.parallel_threshold);
+        p_low..p_high
+    }
+
+    // Returns statistics on how far the cracker column has been cracked. Pieces are counted in tuples,
+    // not in the compressed entries which hold them.
+    pub fn stats(&self) -> CrackStats {
+        let crk = &self.crk_col.crk;
+        let pieces = self.crk_col.pieces().into_iter().map(|r| {
+            let tuples = self.tuple_count(r.clone());
+            (tuples, crk[r.clone()].iter().all(|&c| c == crk[r.start]))
+        });
+        let mut stats = CrackStats::new(pieces, self.crk_col.crk_idx.depth());
+        if stats.tuples > 0 {
+            stats.compaction_ratio = Some(crk.len() as f64 / stats.tuples as f64);
+        }
+        stats
+    }
+
+}
+
+impl Default for CoCoTable {
+    fn default() -> CoCoTable {
+        CoCoTable::new()
+    }
+}
+
+impl CrackedTable for CoCoTable {
+    fn new() -> CoCoTable {
+        CoCoTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        CoCoTable::new_columns(self, col_names)
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        CoCoTable::insert(self, new_values)
+    }
+
+    fn use_crk_col(&mut self, col_name: &str) {
+        CoCoTable::use_crk_col(self, col_name)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        let mut cols: Vec<&mut IntCol> = self.crk_cols.values_mut().collect();
+        cols.push(&mut self.crk_col);
+        cols
+    }
+
+    fn stats(&self) -> CrackStats {
+        CoCoTable::stats(self)
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        CoCoTable::set_crk_col(self, col_name)
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        CoCoTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        CoCoTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        CoCoTable::values(self, r, col)
+    }
+
+    fn tuple_count(&self, r: Range<usize>) -> usize {
+        CoCoTable::tuple_count(self, r)
+    }
+
+    fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        CoCoTable::scan_piece(self, l, h)
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        CoCoTable::filtered_values(self, r, l, h, col)
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(CoCoTable::get_col(self, col).v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> CoCoTable {
+        // Only the base columns are selected, so no compressed entries are carried over.
+        CoCoTable::get_indices(self, indices, 0..0)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        CoCoTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.int_columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
     }
 
-    // Counts the places where a given column equals a given value
-    pub fn count_col_eq(&self, col: &str, eq: i64) -> i64 {
-        self.get_col(col).v.iter().map(|&x|(x==eq)as i64).fold(0, |sum, x|sum+x) as i64
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        CoCoTable::cracks(self)
     }
 }
 
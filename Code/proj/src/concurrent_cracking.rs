@@ -0,0 +1,233 @@
+// Concurrent cracking
+// No compression
+
+// uses map! macro.
+
+// A decomposed cracking table which serves queries from many threads at once. The cracker column is
+// held as separate pieces, each behind a latch of its own, with an AVLCrackerIndex from the smallest
+// value a piece may hold to the piece. A query latches only the piece it cracks, so that queries on
+// different pieces crack in parallel, and a piece which has converged to a single value is read
+// under a shared latch without being cracked at all.
+
+use cracker_index::AVLCrackerIndex;
+use column::Column;
+use column::Element;
+use column::IntCol;
+use column::half_open;
+use column::in_half_open;
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+// A piece's low and high bounds, cracker column and base indices, as stored by persist.
+pub type PieceParts = (i64, Option<i64>, Vec<i64>, Vec<usize>);
+
+struct Piece {
+    // Every element of the piece lies in [low, high), where a high of None leaves it open above.
+    low: i64,
+    high: Option<i64>,
+    crk: Vec<i64>,
+    base_idx: Vec<usize>,
+}
+
+impl Piece {
+    // A piece holding only one value is never cracked again.
+    fn converged(&self) -> bool {
+        self.high == self.low.successor()
+    }
+}
+
+struct Directory {
+    // For the smallest value each piece may hold, the piece's position in pieces.
+    index: AVLCrackerIndex,
+    pieces: Vec<Arc<RwLock<Piece>>>,
+}
+
+pub struct ConcurrentTable {
+    pub count: usize,
+    pub crk_col_name: String,
+    pub columns: HashMap<String, IntCol>,
+    directory: RwLock<Directory>,
+}
+
+impl ConcurrentTable {
+    // Builds a table from equal length COLUMNS, cracking on CRK_COL_NAME. The table is read-only:
+    // queries reorganise the cracker column, but rows cannot be inserted or deleted.
+    pub fn new(columns: HashMap<String, Vec<i64>>, crk_col_name: &str) -> ConcurrentTable {
+        let mut count = None;
+        for v in columns.values() {
+            if count.is_some() && count != Some(v.len()) {
+                panic!("new: columns have differing lengths");
+            }
+            count = Some(v.len());
+        }
+        let crk = match columns.get(crk_col_name) {
+            Some(v) => v.clone(),
+            None => panic!("new: no such col"),
+        };
+
+        // The whole column starts out as one piece, from the smallest value up.
+        let whole = Piece { low: i64::MIN, high: None, base_idx: (0..crk.len()).collect(), crk };
+        let mut index = AVLCrackerIndex::new();
+        index.insert(i64::MIN, 0);
+
+        ConcurrentTable {
+            count: count.unwrap_or(0),
+            crk_col_name: crk_col_name.to_string(),
+            columns: columns.into_iter().map(|(name, v)| {
+                let mut c = IntCol::empty();
+                c.v = v;
+                (name, c)
+            }).collect(),
+            directory: RwLock::new(Directory { index, pieces: vec![Arc::new(RwLock::new(whole))] }),
+        }
+    }
+
+    // Builds a table from its COLUMNS and the parts of its pieces, in value order, the first of which
+    // starts at i64::MIN and each of which ends where the next starts.
+    pub fn from_parts(count: usize, crk_col_name: String, columns: HashMap<String, IntCol>, parts: Vec<PieceParts>) -> ConcurrentTable {
+        let mut index = AVLCrackerIndex::new();
+        let mut pieces = Vec::with_capacity(parts.len());
+        for (low, high, crk, base_idx) in parts {
+            index.insert(low, pieces.len());
+            pieces.push(Arc::new(RwLock::new(Piece { low, high, crk, base_idx })));
+        }
+        ConcurrentTable { count, crk_col_name, columns, directory: RwLock::new(Directory { index, pieces }) }
+    }
+
+    // Returns the parts of each piece, in value order. The pieces are latched one at a time, after the
+    // directory is released as crack requires, so they are read again if another thread cracked one
+    // of them in between and left a gap in their bounds.
+    pub fn piece_parts(&self) -> Vec<PieceParts> {
+        loop {
+            let pieces: Vec<Arc<RwLock<Piece>>> = {
+                let directory = self.directory.read().unwrap();
+                directory.index.pairs().into_iter().map(|(_, p)| directory.pieces[p].clone()).collect()
+            };
+            let parts: Vec<PieceParts> = pieces.iter().map(|piece| {
+                let piece = piece.read().unwrap();
+                (piece.low, piece.high, piece.crk.clone(), piece.base_idx.clone())
+            }).collect();
+            let highs = parts.iter().map(|p| p.1);
+            let next_lows = parts.iter().skip(1).map(|p| Some(p.0)).chain(Some(None));
+            if highs.zip(next_lows).all(|(h, l)| h == l) {
+                return parts;
+            }
+        }
+    }
+
+    // Returns the number of pieces the cracker column has been cracked into.
+    pub fn piece_count(&self) -> usize {
+        self.directory.read().unwrap().pieces.len()
+    }
+
+    // Returns the piece which holds the value X. The piece may be cracked by another thread before
+    // the caller latches it, so callers check its bounds again once they hold the latch.
+    fn piece_of(&self, x: i64) -> Arc<RwLock<Piece>> {
+        let directory = self.directory.read().unwrap();
+        let p = directory.index.lower_bound(&x).unwrap(); // The first piece starts at i64::MIN.
+        directory.pieces[p].clone()
+    }
+
+    // Cracks the piece holding V in two, so that V starts a piece of its own.
+    fn crack(&self, v: i64) {
+        loop {
+            let piece = self.piece_of(v);
+            {
+                let p = piece.read().unwrap();
+                if p.low == v {
+                    return;
+                }
+            }
+
+            let mut p = piece.write().unwrap();
+            if !in_half_open(v, p.low, p.high) {
+                continue; // Cracked by another thread since it was looked up.
+            }
+            if p.low == v {
+                return;
+            }
+
+            let mut p_low = 0;
+            let mut p_high = p.crk.len();
+            while p_low < p_high {
+                if p.crk[p_low] < v {
+                    p_low += 1;
+                } else if p.crk[p_high - 1] >= v {
+                    p_high -= 1;
+                } else {
+                    p.crk.swap(p_low, p_high - 1);
+                    p.base_idx.swap(p_low, p_high - 1);
+                    p_low += 1;
+                    p_high -= 1;
+                }
+            }
+            let upper = Piece { low: v, high: p.high, crk: p.crk.split_off(p_low), base_idx: p.base_idx.split_off(p_low) };
+            p.high = Some(v);
+
+            // The new piece is published before the latch is released, so that a thread which
+            // finds V has left this piece can find the piece it went to.
+            let mut directory = self.directory.write().unwrap();
+            directory.pieces.push(Arc::new(RwLock::new(upper)));
+            let p_upper = directory.pieces.len() - 1;
+            directory.index.insert(v, p_upper);
+            return;
+        }
+    }
+
+    fn get_values(&self, base_idx: &[usize], col: &str) -> Vec<i64> {
+        let column = &self.columns[col].v;
+        base_idx.iter().map(|&i| column[i]).collect()
+    }
+
+    // Returns the elements of T where the cracker columns's value equals X
+    pub fn cracker_select_specific(&self, x: i64, col: &str) -> Vec<i64> {
+        // A piece which has already converged on X is read without cracking.
+        {
+            let piece = self.piece_of(x);
+            let p = piece.read().unwrap();
+            if p.low == x && p.converged() {
+                return self.get_values(&p.base_idx, col);
+            }
+        }
+        self.cracker_select_range(x, x, true, true, col)
+    }
+
+    // Returns the elements of T where the cracker columns's value is between LOW and HIGH, with
+    // inclusivity given by INC_L and INC_H.
+    pub fn cracker_select_range(&self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str) -> Vec<i64> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return vec![],
+        };
+        self.crack(l);
+        if let Some(h) = h {
+            self.crack(h);
+        }
+
+        // L and H start pieces from now on, so the pieces from L up to H hold exactly the elements
+        // asked for, however other threads crack them in the meantime.
+        let mut values = Vec::new();
+        let mut at = Some(l);
+        while let Some(a) = at.filter(|&a| h.is_none_or(|h| a < h)) {
+            let piece = self.piece_of(a);
+            let p = piece.read().unwrap();
+            if !in_half_open(a, p.low, p.high) {
+                continue;
+            }
+            values.append(&mut self.get_values(&p.base_idx, col));
+            at = p.high;
+        }
+        values
+    }
+
+    // Counts the places where a given column equals a given value
+    pub fn count_col_eq(&self, col: &str, eq: i64) -> i64 {
+        self.columns[col].v.iter().filter(|&&x| x == eq).count() as i64
+    }
+}
+
+// Returns an adjacency list built from the two vectors of adjacent nodes.
+pub fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str) -> ConcurrentTable {
+    ConcurrentTable::new(map!{"src".to_string() => src_node, "dst".to_string() => dst_node}, crk)
+}
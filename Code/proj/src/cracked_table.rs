@@ -0,0 +1,467 @@
+// The operations shared by every decomposed cracking table, so that algorithms and benchmarks can
+// be written once over any of the compression schemes.
+
+// uses map! macro.
+
+use column::Element;
+use column::half_open;
+use column::DEFAULT_PARALLEL_THRESHOLD;
+use column::IntCol;
+use column::Values;
+use cracker_index::IndexKind;
+use decomposed_cracking::DeCrackedTable;
+
+use rand;
+use rand::Rng;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::slice::Iter;
+
+// Cracking only on the queried values degenerates to quadratic work when the queries arrive in
+// order, e.g. when every vertex is visited in turn. A stochastic mode makes extra cracks on values
+// taken from the data itself, ahead of the cracks on the queried value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StochasticMode {
+    // Crack only on the queried values.
+    Off,
+    // One crack on a random element of the piece containing the queried value (DD1R).
+    DD1R,
+    // Crack on random elements of the piece containing the queried value until it holds no more
+    // than the given number of elements (DDR).
+    DDR(usize),
+    // As DDR, but cracking on the median of the piece each time (DDC).
+    DDC(usize),
+}
+
+impl StochasticMode {
+    // Returns the value to crack PIECE on next, given that CRACKS_MADE cracks have already been made
+    // for this query, or None if no more cracks are called for. Random pivots are drawn from RNG.
+    pub fn pivot<T: Element, R: Rng>(&self, piece: &[T], cracks_made: usize, rng: &mut R) -> Option<T> {
+        if piece.len() < 2 {
+            return None;
+        }
+        let v = match *self {
+            StochasticMode::Off => return None,
+            StochasticMode::DD1R if cracks_made > 0 => return None,
+            StochasticMode::DDR(max_piece_size) | StochasticMode::DDC(max_piece_size) if piece.len() <= max_piece_size => return None,
+            StochasticMode::DD1R | StochasticMode::DDR(_) => piece[rng.gen_range(0, piece.len())],
+            StochasticMode::DDC(_) => {
+                let mut values = piece.to_vec();
+                let mid = values.len() / 2;
+                *values.select_nth_unstable_by(mid, |a, b| a.partial_cmp(b).unwrap()).1
+            },
+        };
+        // Cracking on the smallest value would leave the piece whole, so crack just above it instead.
+        if piece.iter().all(|&c| c >= v) {
+            v.successor()
+        } else {
+            Some(v)
+        }
+    }
+}
+
+// The generator the random pivots of the stochastic modes are drawn from, a 64-bit xorshift. Its
+// state is a single word, so that a table can be seeded for a reproducible run and saved part way
+// through one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PivotRng(pub u64);
+
+impl PivotRng {
+    // Returns a generator seeded with SEED. Xorshift never leaves a zero state, so a zero seed is
+    // replaced.
+    pub fn new(seed: u64) -> PivotRng {
+        PivotRng(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed })
+    }
+}
+
+impl Rng for PivotRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// The knobs which tune how a table cracks. Every table holds them in one of these, so that they are
+// set, copied and saved in one place. A table ignores those which do not apply to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrackSettings {
+    pub stochastic: StochasticMode,
+    // The generator the stochastic mode draws its random pivots from. A new table seeds it at random.
+    pub rng: PivotRng,
+    // The structure the cracker indices are held in.
+    pub index_kind: IndexKind,
+    // Pieces of more than this many elements are cracked on several threads.
+    pub parallel_threshold: usize,
+    // Once no piece holds more than this many elements, the cracker column is sorted and its index
+    // dropped. 0 leaves it cracked.
+    pub convergence_threshold: usize,
+    // Pieces of no more than this many elements are scanned by queries rather than cracked. 0 cracks
+    // every piece.
+    pub min_piece_size: usize,
+}
+
+impl Default for CrackSettings {
+    fn default() -> CrackSettings {
+        CrackSettings {
+            stochastic: StochasticMode::Off,
+            rng: PivotRng::new(rand::thread_rng().next_u64()),
+            index_kind: IndexKind::AVL,
+            parallel_threshold: DEFAULT_PARALLEL_THRESHOLD,
+            convergence_threshold: 0,
+            min_piece_size: 0,
+        }
+    }
+}
+
+// The aggregates which a selection can compute over a payload column in place of returning its
+// elements.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    // Computes the aggregate of VALUES. The count and sum of no values are zero, and their minimum
+    // and maximum are None.
+    pub fn fold<I: Iterator<Item = i64>>(&self, values: I) -> Option<i64> {
+        match *self {
+            Aggregate::Count => Some(values.count() as i64),
+            Aggregate::Sum => Some(values.sum()),
+            Aggregate::Min => values.min(),
+            Aggregate::Max => values.max(),
+        }
+    }
+}
+
+// A summary of how far a cracker column has been cracked, for tracking convergence over a workload.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrackStats {
+    // The number of tuples in the cracker column.
+    pub tuples: usize,
+    pub pieces: usize,
+    // piece_sizes[i] counts the pieces holding from 2^i up to 2^(i + 1) - 1 tuples.
+    pub piece_sizes: Vec<usize>,
+    // The deepest any of the table's cracker indices is.
+    pub index_depth: usize,
+    // The fraction of the tuples in pieces holding a single value, which no query cracks further.
+    pub converged: f64,
+    // The average number of tuples in the runs of equal values an RLE table has recognised.
+    pub avg_run_length: Option<f64>,
+    // The number of compressed entries per tuple in a compactive table.
+    pub compaction_ratio: Option<f64>,
+}
+
+impl CrackStats {
+    // Summarises PIECES, each given as the number of tuples it holds and whether they all share one
+    // value.
+    pub fn new<I: IntoIterator<Item = (usize, bool)>>(pieces: I, index_depth: usize) -> CrackStats {
+        let mut stats = CrackStats {
+            tuples: 0,
+            pieces: 0,
+            piece_sizes: Vec::new(),
+            index_depth,
+            converged: 0.0,
+            avg_run_length: None,
+            compaction_ratio: None,
+        };
+        let mut converged = 0;
+        for (size, single_value) in pieces {
+            if size == 0 {
+                continue;
+            }
+            let bucket = (usize::BITS - 1 - size.leading_zeros()) as usize;
+            if stats.piece_sizes.len() <= bucket {
+                stats.piece_sizes.resize(bucket + 1, 0);
+            }
+            stats.piece_sizes[bucket] += 1;
+            stats.pieces += 1;
+            stats.tuples += size;
+            if single_value {
+                converged += size;
+            }
+        }
+        if stats.tuples > 0 {
+            stats.converged = converged as f64 / stats.tuples as f64;
+        }
+        stats
+    }
+}
+
+impl fmt::Display for CrackStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "tuples:      {}", self.tuples)?;
+        writeln!(f, "pieces:      {}", self.pieces)?;
+        for (i, n) in self.piece_sizes.iter().enumerate().filter(|&(_, &n)| n > 0) {
+            writeln!(f, "  {:>10}+: {}", 1usize << i, n)?;
+        }
+        writeln!(f, "index depth: {}", self.index_depth)?;
+        write!(f, "converged:   {:.1}%", 100.0 * self.converged)?;
+        if let Some(rl) = self.avg_run_length {
+            write!(f, "\navg run:     {:.2}", rl)?;
+        }
+        if let Some(ratio) = self.compaction_ratio {
+            write!(f, "\ncompaction:  {:.3}", ratio)?;
+        }
+        Ok(())
+    }
+}
+
+pub trait CrackedTable: Sized {
+    fn new() -> Self;
+
+    fn new_columns(&mut self, col_names: Vec<&str>);
+
+    // Appends the given values to every column of the table. Every column must be given the same
+    // number of values.
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>);
+
+    fn set_crk_col(&mut self, col_name: &str);
+
+    // Makes the cracker column on COL_NAME the one which queries crack, keeping the current one for
+    // later use. The cracker column is built if the table has none on COL_NAME yet.
+    fn use_crk_col(&mut self, col_name: &str);
+
+    // Deletes the rows at the given base indices. Rows deleted already are left alone.
+    fn delete_indices(&mut self, indices: Iter<usize>) {
+        for &i in indices {
+            if i >= self.row_count() {
+                panic!("delete_indices: no row at base index {}", i);
+            }
+            if self.deleted_mut().insert(i) {
+                for c in self.cracker_columns_mut() {
+                    c.delete(i);
+                }
+            }
+        }
+    }
+
+    // Deletes every row where COL equals VALUE.
+    fn delete_where(&mut self, col: &str, value: i64) {
+        let indices: Vec<usize> = self.column_values(col).enumerate()
+            .filter(|&(_, x)| x == value)
+            .map(|(i, _)| i)
+            .collect();
+        self.delete_indices(indices.iter());
+    }
+
+    // Returns the number of rows ever inserted into the table, deleted ones included.
+    fn row_count(&self) -> usize;
+
+    // Returns the base indices of the deleted rows.
+    fn deleted(&self) -> &HashSet<usize>;
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize>;
+
+    fn settings(&self) -> &CrackSettings;
+
+    fn settings_mut(&mut self) -> &mut CrackSettings;
+
+    // Returns every cracker column of the table, the one in use and those parked by use_crk_col.
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol>;
+
+    fn set_stochastic_mode(&mut self, mode: StochasticMode) {
+        self.settings_mut().stochastic = mode;
+    }
+
+    // Seeds the generator the stochastic mode draws its random pivots from, so that a run can be
+    // repeated.
+    fn set_seed(&mut self, seed: u64) {
+        self.settings_mut().rng = PivotRng::new(seed);
+    }
+
+    // Moves the cracker index of every cracker column into a structure of the given KIND, keeping the
+    // cracks made so far. Cracker columns built later use KIND too.
+    fn set_cracker_index(&mut self, kind: IndexKind) {
+        self.settings_mut().index_kind = kind;
+        for c in self.cracker_columns_mut() {
+            c.set_index(kind.new_index());
+        }
+    }
+
+    // Has the cracker column sorted, and its cracker index dropped, once no piece holds more than
+    // THRESHOLD elements or every piece is in order. Ranges are then found by binary search. A
+    // THRESHOLD of 0, the default, leaves the column cracked.
+    fn set_convergence_threshold(&mut self, threshold: usize) {
+        self.settings_mut().convergence_threshold = threshold;
+    }
+
+    // Has queries scan, rather than crack, pieces of no more than SIZE elements, so that the cracker
+    // index gains no entries for them. A SIZE of 0, the default, cracks every piece.
+    fn set_min_piece_size(&mut self, size: usize) {
+        self.settings_mut().min_piece_size = size;
+    }
+
+    // Returns statistics on how far the cracker column has been cracked.
+    fn stats(&self) -> CrackStats;
+
+    // Returns the elements of COL where the cracker column's value equals X
+    fn cracker_select_specific(&mut self, x: i64, col: &str) -> Vec<i64> {
+        self.cracker_iter_specific(x, col).collect()
+    }
+
+    // Returns the elements of COL where the cracker column's value is between LOW and HIGH, with
+    // inclusivity given by INC_L and INC_H.
+    fn cracker_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str) -> Vec<i64> {
+        self.cracker_iter_range(low, high, inc_l, inc_h, col).collect()
+    }
+
+    // Cracks the column on X and X + 1, returning the positions of the elements equal to X for values.
+    fn crack_specific(&mut self, x: i64) -> Range<usize>;
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions for values.
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize>;
+
+    // Returns the elements of COL at the positions R, as returned by crack_specific or crack_select_range
+    // with no change to the table since, borrowing them rather than copying them out.
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64>;
+
+    // Returns the number of tuples at the positions R. Each position holds one tuple unless the table
+    // compresses several into one.
+    fn tuple_count(&self, r: Range<usize>) -> usize {
+        r.len()
+    }
+
+    // Returns the positions of the pieces holding the elements in [L, H) if they are no larger than
+    // the minimum piece size, in which case they are scanned with filtered_values instead of cracked.
+    fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>>;
+
+    // Returns the elements of COL at the positions R, as returned by scan_piece, whose cracker column
+    // values lie in [L, H).
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64>;
+
+    // As cracker_select_specific, but borrowing the elements from the table.
+    fn cracker_iter_specific<'a>(&'a mut self, x: i64, col: &str) -> Values<'a, i64> {
+        if let Some(r) = self.scan_piece(x, x.successor()) {
+            return self.filtered_values(r, x, x.successor(), col);
+        }
+        let r = self.crack_specific(x);
+        self.values(r, col)
+    }
+
+    // As cracker_select_range, but borrowing the elements from the table.
+    fn cracker_iter_range<'a>(&'a mut self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str) -> Values<'a, i64> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return self.values(0..0, col),
+        };
+        if let Some(r) = self.scan_piece(l, h) {
+            return self.filtered_values(r, l, h, col);
+        }
+        let r = self.crack_select_range(low, high, inc_l, inc_h);
+        self.values(r, col)
+    }
+
+    // Returns AGG over the elements of COL where the cracker column's value equals X, computed over
+    // the cracked piece without gathering the elements. A count needs only the piece's positions.
+    fn cracker_aggregate_specific(&mut self, x: i64, col: &str, agg: Aggregate) -> Option<i64> {
+        if let Some(r) = self.scan_piece(x, x.successor()) {
+            return agg.fold(self.filtered_values(r, x, x.successor(), col));
+        }
+        let r = self.crack_specific(x);
+        if agg == Aggregate::Count {
+            return Some(self.tuple_count(r) as i64);
+        }
+        agg.fold(self.values(r, col))
+    }
+
+    // Returns AGG over the elements of COL where the cracker column's value is between LOW and HIGH,
+    // with inclusivity given by INC_L and INC_H.
+    fn cracker_aggregate_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str, agg: Aggregate) -> Option<i64> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return agg.fold(self.values(0..0, col)),
+        };
+        if let Some(r) = self.scan_piece(l, h) {
+            return agg.fold(self.filtered_values(r, l, h, col));
+        }
+        let r = self.crack_select_range(low, high, inc_l, inc_h);
+        if agg == Aggregate::Count {
+            return Some(self.tuple_count(r) as i64);
+        }
+        agg.fold(self.values(r, col))
+    }
+
+    // Returns the elements of COL in base index order, deleted rows included.
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64>;
+
+    // Counts the rows where a given column equals a given value, leaving out deleted rows.
+    fn count_col_eq(&self, col: &str, eq: i64) -> i64 {
+        let deleted = self.deleted();
+        self.column_values(col).enumerate().filter(|&(i, x)| x == eq && !deleted.contains(&i)).count() as i64
+    }
+
+    // Returns a new, uncracked table containing the rows at the given base indices.
+    fn get_indices(&self, indices: Iter<usize>) -> Self;
+
+    fn rearrange(&mut self, indices: Iter<usize>);
+
+    // Returns the names of the table's columns, in order.
+    fn column_names(&self) -> Vec<String>;
+
+    // Returns the name of the column the table cracks on.
+    fn crk_col_name(&self) -> &str;
+
+    // Returns the cracks made in the cracker column, as each key of its cracker index with the
+    // position of the first tuple at least the key, counting tuples rather than compressed entries.
+    fn cracks(&self) -> Vec<(i64, usize)>;
+
+    // Returns every column of the table with its rows laid out in the cracked order of the cracker
+    // column, one element per tuple, for code which knows nothing of offsets or run lengths. Pending
+    // inserts and deletes are merged first, and deleted rows are left out.
+    fn cracked_columns(&mut self) -> HashMap<String, Vec<i64>> {
+        // A range from the smallest value the cracker column has held, open above, takes in every tuple.
+        let bounds = {
+            let v: Vec<i64> = self.column_values(self.crk_col_name()).collect();
+            (v.iter().min().cloned(), v.iter().max().cloned())
+        };
+        let r = match bounds {
+            (Some(min), Some(max)) => self.crack_select_range(min, max, true, true),
+            _ => 0..0,
+        };
+        self.column_names().into_iter().map(|name| {
+            let values = self.values(r.clone(), &name).collect();
+            (name, values)
+        }).collect()
+    }
+
+    // Returns a fresh, uncompressed table holding the rows of cracked_columns, cracked on the same
+    // column with the same cracks, so that it answers queries without cracking them again.
+    fn decompress(&mut self) -> DeCrackedTable {
+        let mut columns = self.cracked_columns();
+        let mut t = DeCrackedTable::new();
+        t.new_columns(columns.keys().cloned().collect());
+        t.insert(&mut columns);
+        t.set_crk_col(self.crk_col_name().to_string());
+        for (k, p) in self.cracks() {
+            t.crk_col.crk_idx.insert(k, p);
+        }
+        t
+    }
+
+    // Returns an adjacency list built from the two vectors of adjacent nodes.
+    fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str) -> Self {
+        let mut adjacency_list = Self::new();
+        adjacency_list.new_columns(vec!["src", "dst"]);
+        adjacency_list.insert(&mut map!{"src" => src_node, "dst" => dst_node});
+        adjacency_list.set_crk_col(crk);
+        adjacency_list
+    }
+}
+
+// Converts the column names of NEW_VALUES to owned strings, for the tables which key their columns by String.
+pub fn owned_column_names(new_values: &mut HashMap<&str, Vec<i64>>) -> HashMap<String, Vec<i64>> {
+    new_values.drain().map(|(k, v)| (k.to_string(), v)).collect()
+}
@@ -1,40 +1,139 @@
+use std::any::Any;
 use std::cmp;
 use std::cmp::Ordering;
+use std::mem;
+
+use column::Element;
+
+// The operations a cracker index provides, whichever structure holds it. Maps K -> usize.
+pub trait CrackerIndex<K: Element = i64>: Send + Sync {
+    fn insert(&mut self, key: K, data: usize);
+    fn delete(&mut self, key: K);
+    fn get(&self, key: K) -> Option<usize>;
+
+    // Returns the smallest key >= key
+    fn upper_bound(&self, key: &K) -> Option<usize>;
+
+    // Returns the largest key <= key
+    fn lower_bound(&self, key: &K) -> Option<usize>;
+
+    // For all keys > THRESHOLD, subtract their value by AMOUNT.
+    fn subtract_where_greater_than(&mut self, threshold: K, amount: usize);
+
+    // For all keys > THRESHOLD, add AMOUNT to their value.
+    fn add_where_greater_than(&mut self, threshold: K, amount: usize);
+
+    // Returns every key value pair in the index, in key order.
+    fn pairs(&self) -> Vec<(K, usize)>;
+
+    // Removes every key from the index.
+    fn clear(&mut self);
+
+    // Returns the number of levels a lookup descends through, or 0 if the index is empty.
+    fn depth(&self) -> usize;
+
+    fn box_clone(&self) -> Box<dyn CrackerIndex<K>>;
+
+    fn contains(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Element> Clone for Box<dyn CrackerIndex<K>> {
+    fn clone(&self) -> Box<dyn CrackerIndex<K>> {
+        self.box_clone()
+    }
+}
+
+// The structures a table's cracker index can be held in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IndexKind {
+    AVL,
+    BPlusTree,
+    Sorted,
+    // Holds a slot for every key from the smallest up to the largest, so suits only dense keys such
+    // as vertex ids.
+    Array,
+}
+
+impl IndexKind {
+    // Returns an empty index of this kind.
+    pub fn new_index<K: Element>(&self) -> Box<dyn CrackerIndex<K>> {
+        match *self {
+            IndexKind::AVL => Box::new(AVLCrackerIndex::new()),
+            IndexKind::BPlusTree => Box::new(BPlusTreeCrackerIndex::new()),
+            IndexKind::Sorted => Box::new(SortedCrackerIndex::new()),
+            IndexKind::Array => {
+                // Keys are array positions, so only i64 keys can be held.
+                let idx: Box<dyn Any> = Box::new(Box::new(ArrayCrackerIndex::new()) as Box<dyn CrackerIndex>);
+                match idx.downcast::<Box<dyn CrackerIndex<K>>>() {
+                    Ok(idx) => *idx,
+                    Err(_) => panic!("new_index: the array index needs i64 keys"),
+                }
+            },
+        }
+    }
+}
 
 // Implements the functions required for the cracker index.
-// Maps i64 -> usize
+// Maps i64 -> usize, with a slot for every key from the smallest to the largest held.
 #[derive(Clone)]
 pub struct ArrayCrackerIndex {
-    pub index: Vec<Option<usize>>
+    pub index: Vec<Option<usize>>,
+    // The key held in index[0].
+    pub base: i64,
 }
 
 impl ArrayCrackerIndex {
     pub fn new() -> ArrayCrackerIndex {
-        ArrayCrackerIndex { index: Vec::new() }
+        ArrayCrackerIndex { index: Vec::new(), base: 0 }
+    }
+
+    // Returns how far KEY lies from the key held in index[0]. Keys at opposite ends of i64 lie further
+    // apart than an i64 holds, so the distance is an i128.
+    fn offset(&self, key: i64) -> i128 {
+        key as i128 - self.base as i128
+    }
+
+    // Returns the slot of KEY, or None if it lies outside the slots.
+    fn slot(&self, key: i64) -> Option<usize> {
+        let d = self.offset(key);
+        if d < 0 || d >= self.index.len() as i128 {
+            None
+        } else {
+            Some(d as usize)
+        }
+    }
+
+    // Returns the first slot of the keys greater than KEY, which is 0 for a KEY below the slots and
+    // the number of slots for one above them.
+    fn slot_after(&self, key: i64) -> usize {
+        cmp::min(cmp::max(self.offset(key) + 1, 0), self.index.len() as i128) as usize
     }
 
     pub fn insert(&mut self, key: i64, data: usize) {
-        if key < 0 {
-            return;
+        if self.index.is_empty() {
+            self.base = key;
+        } else if key < self.base {
+            let n = (-self.offset(key)) as usize;
+            self.index.splice(0..0, vec![None; n]);
+            self.base = key;
         }
-        while self.index.len() <= key as usize {
-            self.index.push(None);
+        let k = self.offset(key) as usize;
+        if self.index.len() <= k {
+            self.index.resize(k + 1, None);
         }
-        self.index[key as usize] = Some(data);
+        self.index[k] = Some(data);
     }
 
     pub fn delete(&mut self, key: i64) {
-        if self.index.len() > key as usize {
-            self.index.remove(key as usize);
+        if let Some(k) = self.slot(key) {
+            self.index[k] = None;
         }
     }
 
     pub fn get(&self, key: i64) -> Option<usize> {
-        if self.index.len() <= key as usize {
-            None
-        } else {
-            self.index[key as usize]
-        }
+        self.slot(key).and_then(|k| self.index[k])
     }
 
     pub fn get_or(self, key: i64, default: usize) -> usize {
@@ -45,95 +144,146 @@ impl ArrayCrackerIndex {
         self.get(key).is_some()
     }
 
-    pub fn empty(&self) -> bool { self.index.len() == 0 }
+    pub fn empty(&self) -> bool { self.index.iter().all(|d| d.is_none()) }
 
     // Returns the smallest key >= key
     pub fn upper_bound(&self, key: &i64) -> Option<usize> {
-        if *key < 0 {
-            return None;
-        }
-        let mut k = (*key) as usize;
-        if k >= self.index.len() {
-            None
-        } else {
-            while self.index[k].is_none() && k < self.index.len() - 1 {
-                k += 1;
-            }
-            self.index[k]
-        }
+        let k = cmp::min(cmp::max(self.offset(*key), 0), self.index.len() as i128) as usize;
+        self.index.iter().skip(k).filter_map(|&d| d).next()
     }
 
     // Returns the largest key <= key
     pub fn lower_bound(&self, key: &i64) -> Option<usize> {
-        if *key < 0 {
+        let d = self.offset(*key);
+        if d < 0 {
             return None;
         }
-        let mut k = (*key) as usize;
-        if k >= self.index.len() {
-            None
-        } else {
-            while self.index[k].is_none() && k > 0 {
-                k -= 1;
-            }
-            self.index[k]
-        }
+        let k = cmp::min(d, self.index.len().saturating_sub(1) as i128) as usize;
+        self.index.iter().take(k + 1).rev().filter_map(|&d| d).next()
     }
 
     // For all keys > THRESHOLD, subtract their value by AMOUNT.
     // Assumed that threshold is a key in the current index
     pub fn subtract_where_greater_than(&mut self, threshold: i64, amount: usize) {
-        let mut k = 1 + threshold as usize;
-        let l = self.index.len();
-        while k < l {
-            if let Some(d) = self.index[k].as_mut() {
-                *d -= amount;
-            }
-            k += 1;
+        let k = self.slot_after(threshold);
+        for d in self.index.iter_mut().skip(k).flatten() {
+            *d -= amount;
+        }
+    }
+
+    // For all keys > THRESHOLD, add AMOUNT to their value.
+    pub fn add_where_greater_than(&mut self, threshold: i64, amount: usize) {
+        let k = self.slot_after(threshold);
+        for d in self.index.iter_mut().skip(k).flatten() {
+            *d += amount;
         }
     }
+
+    // Returns every key value pair in the index, in key order.
+    pub fn pairs(&self) -> Vec<(i64, usize)> {
+        let base = self.base;
+        self.index.iter().enumerate().filter_map(|(k, d)| d.map(|d| (base + k as i64, d))).collect()
+    }
+}
+
+impl Default for ArrayCrackerIndex {
+    fn default() -> ArrayCrackerIndex {
+        ArrayCrackerIndex::new()
+    }
+}
+
+impl CrackerIndex for ArrayCrackerIndex {
+    fn insert(&mut self, key: i64, data: usize) {
+        ArrayCrackerIndex::insert(self, key, data)
+    }
+
+    fn delete(&mut self, key: i64) {
+        ArrayCrackerIndex::delete(self, key)
+    }
+
+    fn get(&self, key: i64) -> Option<usize> {
+        ArrayCrackerIndex::get(self, key)
+    }
+
+    fn upper_bound(&self, key: &i64) -> Option<usize> {
+        ArrayCrackerIndex::upper_bound(self, key)
+    }
+
+    fn lower_bound(&self, key: &i64) -> Option<usize> {
+        ArrayCrackerIndex::lower_bound(self, key)
+    }
+
+    fn subtract_where_greater_than(&mut self, threshold: i64, amount: usize) {
+        ArrayCrackerIndex::subtract_where_greater_than(self, threshold, amount)
+    }
+
+    fn add_where_greater_than(&mut self, threshold: i64, amount: usize) {
+        ArrayCrackerIndex::add_where_greater_than(self, threshold, amount)
+    }
+
+    fn pairs(&self) -> Vec<(i64, usize)> {
+        ArrayCrackerIndex::pairs(self)
+    }
+
+    fn clear(&mut self) {
+        self.index.clear();
+    }
+
+    fn depth(&self) -> usize {
+        if self.empty() { 0 } else { 1 }
+    }
+
+    fn box_clone(&self) -> Box<dyn CrackerIndex> {
+        Box::new(self.clone())
+    }
+}
+
+// Orders two keys. Floating point keys are only partially ordered, but a cracker index never holds NaN.
+fn compare<K: Element>(a: &K, b: &K) -> Ordering {
+    a.partial_cmp(b).expect("cracker index: unordered key")
 }
 
 #[derive(Clone)]
-pub struct AVLNode {
-    key: i64,
+pub struct AVLNode<K: Element> {
+    key: K,
     data: usize,
     height: u64,
-    left:  Option<Box<AVLNode>>,
-    right: Option<Box<AVLNode>>,
+    left:  Option<Box<AVLNode<K>>>,
+    right: Option<Box<AVLNode<K>>>,
 }
 
-impl AVLNode {
-    pub fn new(k: i64, d: usize) -> AVLNode {
+impl<K: Element> AVLNode<K> {
+    pub fn new(k: K, d: usize) -> AVLNode<K> {
         AVLNode {key: k, data: d, height: 1, left: None, right: None}
     }
 }
 
-fn height (node: &Option<Box<AVLNode>>) -> u64  {
-    return node.as_ref().map_or(0, |successor| successor.height)
+fn height<K: Element>(node: &Option<Box<AVLNode<K>>>) -> u64  {
+    node.as_ref().map_or(0, |successor| successor.height)
 }
 
 // Perform a single right rotation on this (sub) tree
-fn rotate_right(mut root: Box<AVLNode>) -> Box<AVLNode> {
+fn rotate_right<K: Element>(mut root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     let mut new_root_box = root.left.take().expect("AVL broken");
     root.left = new_root_box.right.take();
     update_height(&mut root);
     new_root_box.right = Some(root);
     update_height(&mut new_root_box);
-    return new_root_box
+    new_root_box
 }
 
 // Perform a single left rotation on this (sub) tree
-fn rotate_left(mut root: Box<AVLNode>) -> Box<AVLNode> {
+fn rotate_left<K: Element>(mut root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     let mut new_root_box = root.right.take().expect("AVL broken");
     root.right = new_root_box.left.take();
     update_height(&mut root);
     new_root_box.left = Some(root);
     update_height(&mut new_root_box);
-    return new_root_box
+    new_root_box
 }
 
 // Performs a rotation that counteracts the fact that the left successor is too high
-fn rotate_left_successor(mut root: Box<AVLNode>) -> Box<AVLNode> {
+fn rotate_left_successor<K: Element>(mut root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     let left = root.left.take().expect("AVL broken");
     if height(&left.left) < height(&left.right) {
         let rotated = rotate_left(left);
@@ -147,7 +297,7 @@ fn rotate_left_successor(mut root: Box<AVLNode>) -> Box<AVLNode> {
 }
 
 // Performs a rotation that counteracts the fact that the right successor is too high
-fn rotate_right_successor(mut root: Box<AVLNode>) -> Box<AVLNode> {
+fn rotate_right_successor<K: Element>(mut root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     let right = root.right.take().expect("AVL broken");
     if height(&right.left) > height(&right.right) {
         let rotated = rotate_right(right);
@@ -160,7 +310,7 @@ fn rotate_right_successor(mut root: Box<AVLNode>) -> Box<AVLNode> {
     rotate_left(root)
 }
 
-fn diff_of_successors_height(root: &Box<AVLNode>) -> i32 {
+fn diff_of_successors_height<K: Element>(root: &AVLNode<K>) -> i32 {
     let l = height(&root.left);
     let r = height(&root.right);
     (l as i32) - (r as i32)
@@ -168,9 +318,9 @@ fn diff_of_successors_height(root: &Box<AVLNode>) -> i32 {
 
 
 // Apply all necessary rotations on root.
-fn rotate_if_necessary(root: Box<AVLNode>) -> Box<AVLNode> {
+fn rotate_if_necessary<K: Element>(root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     let diff  = diff_of_successors_height(&root);
-    if -1 <= diff && diff <= 1 {return root}
+    if (-1..=1).contains(&diff) {return root}
     match diff {
         2 => rotate_left_successor(root),
         -2 => rotate_right_successor(root),
@@ -180,12 +330,12 @@ fn rotate_if_necessary(root: Box<AVLNode>) -> Box<AVLNode> {
 
 // Update the cached height of root. To call this function make sure that the cached values of
 // both children of root ar up to date.
-fn update_height(root: &mut AVLNode) {
+fn update_height<K: Element>(root: &mut AVLNode<K>) {
     root.height = cmp::max( height(&root.left), height(&root.right) )+1;
 }
 
 // Recursively insert the (key,data) pair into the given optional succesor and return its new value
-fn insert_in_successor(key: i64, data: usize, successor: Option<Box<AVLNode>>) -> Option<Box<AVLNode>> {
+fn insert_in_successor<K: Element>(key: K, data: usize, successor: Option<Box<AVLNode<K>>>) -> Option<Box<AVLNode<K>>> {
     Some(match successor {
         Some(s) => insert(key, data, s),
         None       => Box::new(AVLNode::new(key, data))
@@ -195,43 +345,43 @@ fn insert_in_successor(key: i64, data: usize, successor: Option<Box<AVLNode>>) -
 // Inserts the given data under the key in the tree root. It will replace old data stored
 // under this key if it was allready used in the tree. The resulting tree will be returned
 // (its root may now differ due to rotations, thus the old root is moved into the function)
-pub fn insert(key: i64, data: usize, mut root: Box<AVLNode>) -> Box<AVLNode> {
-    match root.key.cmp(&key) {
+pub fn insert<K: Element>(key: K, data: usize, mut root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
+    match compare(&root.key, &key) {
         Ordering::Equal   => { root.data  = data; return root },
         Ordering::Less    => root.right = insert_in_successor(key, data, root.right.take()),
         Ordering::Greater => root.left  = insert_in_successor(key,data, root.left.take())
     }
     update_height(&mut *root);
-    return rotate_if_necessary(root)
+    rotate_if_necessary(root)
 }
 
 // Returns a read only reference to the data stored under key in the tree given by root
-pub fn search<'a>(key: &i64, root: &'a Box<AVLNode>) -> Option<&'a usize> {
+pub fn search<'a, K: Element>(key: &K, root: &'a AVLNode<K>) -> Option<&'a usize> {
     search_pair(key,root).map(|(_,v)| v )
 }
 
 // Returns a read only reference pair to the data stored under key in the tree given by root
-pub fn search_pair<'a>(key: &i64, root: &'a Box<AVLNode>) -> Option<(&'a i64,&'a usize)> {
-    match root.key.cmp(key) {
+pub fn search_pair<'a, K: Element>(key: &K, root: &'a AVLNode<K>) -> Option<(&'a K,&'a usize)> {
+    match compare(&root.key, key) {
         Ordering::Equal   => Some((&root.key, &root.data)),
-        Ordering::Less    => root.right.as_ref().map_or(None, |succ| search_pair(key, succ)),
-        Ordering::Greater => root.left.as_ref().map_or(None, |succ| search_pair(key, succ))
+        Ordering::Less    => root.right.as_ref().and_then(|succ| search_pair(key, succ)),
+        Ordering::Greater => root.left.as_ref().and_then(|succ| search_pair(key, succ))
     }
 }
 
 // Returns the smallest key value pair (k, v) s.t. k >= given key.
-pub fn min_after<'a>(key: &i64, root: &'a Box<AVLNode>) -> Option<(&'a i64,&'a usize)> {
-    match root.key.cmp(key) {
+pub fn min_after<'a, K: Element>(key: &K, root: &'a AVLNode<K>) -> Option<(&'a K,&'a usize)> {
+    match compare(&root.key, key) {
         Ordering::Equal   => Some((&root.key, &root.data)),
         Ordering::Less    => {
             match root.right {
-                Some(ref succ) => min_after(key, &succ),
+                Some(ref succ) => min_after(key, succ),
                 None           => None
             }
         },
         Ordering::Greater => {
             match root.left {
-                Some(ref succ) => min_after(key, &succ).or(Some((&root.key,&root.data))),
+                Some(ref succ) => min_after(key, succ).or(Some((&root.key,&root.data))),
                 None           => Some((&root.key, &root.data))
             }
         }
@@ -239,18 +389,18 @@ pub fn min_after<'a>(key: &i64, root: &'a Box<AVLNode>) -> Option<(&'a i64,&'a u
 }
 
 // Returns the greatest key value pair (k, v) s.t. k  <= given key.
-pub fn max_before<'a>(key: &i64, root: &'a Box<AVLNode>) -> Option<(&'a i64,&'a usize)> {
-    match root.key.cmp(key) {
+pub fn max_before<'a, K: Element>(key: &K, root: &'a AVLNode<K>) -> Option<(&'a K,&'a usize)> {
+    match compare(&root.key, key) {
         Ordering::Equal   => Some((&root.key, &root.data)),
         Ordering::Less    => {
             match root.right {
-                Some(ref succ) => max_before(key, &succ).or(Some((&root.key,&root.data))),
+                Some(ref succ) => max_before(key, succ).or(Some((&root.key,&root.data))),
                 None           => Some((&root.key, &root.data))
             }
         }
         Ordering::Greater => {
             match root.left {
-                Some(ref succ) => max_before(key, &succ),
+                Some(ref succ) => max_before(key, succ),
                 None           => None
             }
         }
@@ -258,33 +408,33 @@ pub fn max_before<'a>(key: &i64, root: &'a Box<AVLNode>) -> Option<(&'a i64,&'a
 }
 
 // Returns the minimal key,value pair within this tree
-pub fn min_pair(root: &Box<AVLNode>) -> (&i64,&usize) {
-    root.left.as_ref().map_or((&root.key,&root.data), min_pair)
+pub fn min_pair<K: Element>(root: &AVLNode<K>) -> (&K,&usize) {
+    root.left.as_deref().map_or((&root.key,&root.data), min_pair)
 }
 
 // Returns the maximal key,value pair within this tree
-pub fn max_pair(root: &Box<AVLNode>) -> (&i64,&usize) {
-    root.right.as_ref().map_or((&root.key,&root.data), max_pair)
+pub fn max_pair<K: Element>(root: &AVLNode<K>) -> (&K,&usize) {
+    root.right.as_deref().map_or((&root.key,&root.data), max_pair)
 }
 
 // Returns the minimal value within this tree
-pub fn min(root: &Box<AVLNode>) -> &usize {
-    root.left.as_ref().map_or(&root.data, min)
+pub fn min<K: Element>(root: &AVLNode<K>) -> &usize {
+    root.left.as_deref().map_or(&root.data, min)
 }
 
 // Returns the minimal value within this tree
-pub fn max(root: &Box<AVLNode>) -> &usize {
-    root.right.as_ref().map_or(&root.data, max)
+pub fn max<K: Element>(root: &AVLNode<K>) -> &usize {
+    root.right.as_deref().map_or(&root.data, max)
 }
 
 // Will update_heights and rotate the node if necessary, returns the rotated node
-fn updated_node(mut root: Box<AVLNode>) -> Box<AVLNode> {
+fn updated_node<K: Element>(mut root: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     update_height(&mut root);
     rotate_if_necessary(root)
 }
 
 // Performs recursive `drop_and_get_min` if a left  since a successor is available
-fn drop_min_from_left(mut root : Box<AVLNode>, left: Box<AVLNode>) -> (Option<Box<AVLNode>>,Box<AVLNode>) {
+fn drop_min_from_left<K: Element>(mut root : Box<AVLNode<K>>, left: Box<AVLNode<K>>) -> (Option<Box<AVLNode<K>>>,Box<AVLNode<K>>) {
     let (new_left, min) = drop_min(left);
     root.left = new_left;
     (Some(updated_node(root)),min)
@@ -292,7 +442,7 @@ fn drop_min_from_left(mut root : Box<AVLNode>, left: Box<AVLNode>) -> (Option<Bo
 
 // Finds the minimal value below root and returns a new (optional) tree where the minimal value has been
 // removed and the (optional) minimal node as tuple (new_tree, min);
-fn drop_min(mut root: Box<AVLNode>) -> (Option<Box<AVLNode>>, Box<AVLNode>) {
+fn drop_min<K: Element>(mut root: Box<AVLNode<K>>) -> (Option<Box<AVLNode<K>>>, Box<AVLNode<K>>) {
     match root.left.take() {
         Some(left) => drop_min_from_left(root, left),
         None => (root.right.take(), root)
@@ -300,7 +450,7 @@ fn drop_min(mut root: Box<AVLNode>) -> (Option<Box<AVLNode>>, Box<AVLNode>) {
 }
 
 // Return a new AVL tree, as the combination of two subtrees with max(l) <= min(r)
-fn combine_two_subtrees(l: Box<AVLNode>, r: Box<AVLNode>) -> Box<AVLNode> {
+fn combine_two_subtrees<K: Element>(l: Box<AVLNode<K>>, r: Box<AVLNode<K>>) -> Box<AVLNode<K>> {
     let (remaining_tree, min) = drop_min(r);
     let mut new_root = min;
     new_root.left = Some(l);
@@ -309,7 +459,7 @@ fn combine_two_subtrees(l: Box<AVLNode>, r: Box<AVLNode>) -> Box<AVLNode> {
 }
 
 // Return a new AVL tree, where the root has been removed
-fn delete_root(mut root: Box<AVLNode>) -> Option<Box<AVLNode>> {
+fn delete_root<K: Element>(mut root: AVLNode<K>) -> Option<Box<AVLNode<K>>> {
     match ( root.left.take(), root.right.take() ) {
         ( None,     None)    => None,
         ( Some(l),  None)    => Some(l),
@@ -319,9 +469,9 @@ fn delete_root(mut root: Box<AVLNode>) -> Option<Box<AVLNode>> {
 }
 
 // Deletes `key` from the tree `root`. Returns either `Some` tree or None
-pub fn delete(key: i64, mut root: Box<AVLNode>) -> Option<Box<AVLNode>> {
-    match root.key.cmp(&key) {
-        Ordering::Equal =>  return delete_root(root),
+pub fn delete<K: Element>(key: K, mut root: Box<AVLNode<K>>) -> Option<Box<AVLNode<K>>> {
+    match compare(&root.key, &key) {
+        Ordering::Equal =>  return delete_root(*root),
         Ordering::Less  => {
             if let Some(successor) = root.right.take() {
                 root.right = delete(key, successor);
@@ -335,116 +485,533 @@ pub fn delete(key: i64, mut root: Box<AVLNode>) -> Option<Box<AVLNode>> {
             }
         }
     }
-    return Some(root);
+    Some(root)
 }
 
 // For all keys > THRESHOLD, subtract their value by AMOUNT.
 // Assumed that threshold is a key in the current index
-pub fn subtract_where_greater_than(threshold: i64, amount: usize, root: &mut Box<AVLNode>) {
+pub fn subtract_where_greater_than<K: Element>(threshold: K, amount: usize, root: &mut Box<AVLNode<K>>) {
     if root.key > threshold {
         root.data -= amount;
-        root.left.as_mut().map(|t| subtract_where_greater_than(threshold, amount, t));
-        root.right.as_mut().map(|t| subtract_where_greater_than(threshold, amount, t));
-    } else if root.right.is_some() {
-        root.right.as_mut().map(|t| subtract_where_greater_than(threshold, amount, t));
+        if let Some(ref mut t) = root.left {
+            subtract_where_greater_than(threshold, amount, t);
+        }
+    }
+    if let Some(ref mut t) = root.right {
+        subtract_where_greater_than(threshold, amount, t);
+    }
+}
+
+// For all keys > THRESHOLD, add AMOUNT to their value.
+pub fn add_where_greater_than<K: Element>(threshold: K, amount: usize, root: &mut Box<AVLNode<K>>) {
+    if root.key > threshold {
+        root.data += amount;
+        if let Some(ref mut t) = root.left {
+            add_where_greater_than(threshold, amount, t);
+        }
+    }
+    if let Some(ref mut t) = root.right {
+        add_where_greater_than(threshold, amount, t);
     }
 }
 
-pub fn print_nodes(root: &Box<AVLNode>) {
+pub fn print_nodes<K: Element>(root: &AVLNode<K>) {
     print!("{} -> {} | ", root.key, root.data);
-    root.left.as_ref().map(|t| print_nodes(&t));
-    root.right.as_ref().map(|t| print_nodes(&t));
+    if let Some(ref t) = root.left {
+        print_nodes(t);
+    }
+    if let Some(ref t) = root.right {
+        print_nodes(t);
+    }
+}
+
+// Appends the key value pairs of the tree given by root to PAIRS, in key order.
+pub fn collect_pairs<K: Element>(root: &AVLNode<K>, pairs: &mut Vec<(K, usize)>) {
+    if let Some(ref t) = root.left {
+        collect_pairs(t, pairs);
+    }
+    pairs.push((root.key, root.data));
+    if let Some(ref t) = root.right {
+        collect_pairs(t, pairs);
+    }
 }
 
 #[derive(Clone)]
-pub struct AVLCrackerIndex {
-    pub root: Option<Box<AVLNode>>
+pub struct AVLCrackerIndex<K: Element = i64> {
+    pub root: Option<Box<AVLNode<K>>>
 }
 
-impl AVLCrackerIndex {
-    pub fn new() -> AVLCrackerIndex {
+impl<K: Element> AVLCrackerIndex<K> {
+    pub fn new() -> AVLCrackerIndex<K> {
         AVLCrackerIndex{root: None}
     }
 
-    pub fn insert(&mut self, key: i64, data: usize) {
+    pub fn insert(&mut self, key: K, data: usize) {
         match self.root.take() {
             Some(box_to_node) => self.root = Some(insert(key, data, box_to_node)),
             None              => self.root = Some(Box::new(AVLNode::new(key,data))),
         }
     }
 
-    pub fn delete(&mut self, key: i64) {
-        match self.root.take() {
-            Some(box_to_node) => self.root = delete(key,box_to_node),
-            None              => return
+    pub fn delete(&mut self, key: K) {
+        if let Some(box_to_node) = self.root.take() {
+            self.root = delete(key, box_to_node);
         }
     }
 
-    pub fn get(&self, key: i64) -> Option<usize> {
-        match self.root {
-            Some(ref box_to_node) => {
-                match search(&key, box_to_node) {
-                    Some(node) => Some(*node),
-                    None           => None
-                }
-            },
-            None                  => None
-        }
+    pub fn get(&self, key: K) -> Option<usize> {
+        self.root.as_ref().and_then(|box_to_node| search(&key, box_to_node)).copied()
     }
 
-    pub fn get_or<'a>(&'a self, key: i64, default: usize) -> usize {
+    pub fn get_or(&self, key: K, default: usize) -> usize {
         self.get(key).map_or(default, |data| data)
     }
 
-    pub fn contains(&self, key: i64) -> bool {
+    pub fn contains(&self, key: K) -> bool {
         self.get(key).is_some()
     }
 
     pub fn empty(&self) -> bool { self.root.is_none() }
 
     // Returns the smallest key >= key
-    pub fn upper_bound(&self, key: &i64) -> Option<usize> {
-        match self.root {
-            Some(ref tree) => {
-                match min_after(key, tree) {
-                    Some((_k, v)) => Some(*v),
-                    None          => None
-                }
-            },
-            None => None
-        }
+    pub fn upper_bound(&self, key: &K) -> Option<usize> {
+        self.root.as_ref().and_then(|tree| min_after(key, tree)).map(|(_k, v)| *v)
     }
 
     // Returns the largest key <= key
-    pub fn lower_bound(&self, key: &i64) -> Option<usize> {
-        match self.root {
-            Some(ref tree) => {
-                match max_before(key, tree) {
-                    Some((_k, v)) => Some(*v),
-                    None          => None
-                }
-            },
-            None => None
-        }
+    pub fn lower_bound(&self, key: &K) -> Option<usize> {
+        self.root.as_ref().and_then(|tree| max_before(key, tree)).map(|(_k, v)| *v)
     }
 
     // For all keys > THRESHOLD, subtract their value by AMOUNT.
     // Assumed that threshold is a key in the current index.
-    pub fn subtract_where_greater_than(&mut self, threshold: i64, amount: usize) {
-        match self.root {
-            Some(ref mut root) => subtract_where_greater_than(threshold, amount, root),
-            None => {}
+    pub fn subtract_where_greater_than(&mut self, threshold: K, amount: usize) {
+        if let Some(ref mut root) = self.root {
+            subtract_where_greater_than(threshold, amount, root);
+        }
+    }
+
+    // For all keys > THRESHOLD, add AMOUNT to their value.
+    pub fn add_where_greater_than(&mut self, threshold: K, amount: usize) {
+        if let Some(ref mut root) = self.root {
+            add_where_greater_than(threshold, amount, root);
+        }
+    }
+
+    // Returns every key value pair in the index, in key order.
+    pub fn pairs(&self) -> Vec<(K, usize)> {
+        let mut pairs = Vec::new();
+        if let Some(ref root) = self.root {
+            collect_pairs(root, &mut pairs);
         }
+        pairs
     }
 
     pub fn print(&self) {
-        match self.root {
-            Some(ref root) => {
-                print!("| ");
-                print_nodes(root);
-                println!();
+        if let Some(ref root) = self.root {
+            print!("| ");
+            print_nodes(root);
+            println!();
+        }
+    }
+}
+
+impl<K: Element> Default for AVLCrackerIndex<K> {
+    fn default() -> AVLCrackerIndex<K> {
+        AVLCrackerIndex::new()
+    }
+}
+
+impl<K: Element> CrackerIndex<K> for AVLCrackerIndex<K> {
+    fn insert(&mut self, key: K, data: usize) {
+        AVLCrackerIndex::insert(self, key, data)
+    }
+
+    fn delete(&mut self, key: K) {
+        AVLCrackerIndex::delete(self, key)
+    }
+
+    fn get(&self, key: K) -> Option<usize> {
+        AVLCrackerIndex::get(self, key)
+    }
+
+    fn upper_bound(&self, key: &K) -> Option<usize> {
+        AVLCrackerIndex::upper_bound(self, key)
+    }
+
+    fn lower_bound(&self, key: &K) -> Option<usize> {
+        AVLCrackerIndex::lower_bound(self, key)
+    }
+
+    fn subtract_where_greater_than(&mut self, threshold: K, amount: usize) {
+        AVLCrackerIndex::subtract_where_greater_than(self, threshold, amount)
+    }
+
+    fn add_where_greater_than(&mut self, threshold: K, amount: usize) {
+        AVLCrackerIndex::add_where_greater_than(self, threshold, amount)
+    }
+
+    fn pairs(&self) -> Vec<(K, usize)> {
+        AVLCrackerIndex::pairs(self)
+    }
+
+    fn clear(&mut self) {
+        self.root = None;
+    }
+
+    fn depth(&self) -> usize {
+        height(&self.root) as usize
+    }
+
+    fn box_clone(&self) -> Box<dyn CrackerIndex<K>> {
+        Box::new(self.clone())
+    }
+}
+
+// The most keys a B+-tree node holds before it is split. Each node keeps its keys in one vector, so
+// that a lookup scans a few contiguous cache lines per level rather than following a pointer per key.
+const BTREE_ORDER: usize = 32;
+
+#[derive(Clone)]
+enum BTreeNode<K: Element> {
+    // Keys, in order, with their data.
+    Leaf { keys: Vec<K>, data: Vec<usize> },
+    // Every key below children[i] is < keys[i], and every key below children[i + 1] is >= keys[i].
+    Internal { keys: Vec<K>, children: Vec<BTreeNode<K>> },
+}
+
+impl<K: Element> BTreeNode<K> {
+    fn is_empty(&self) -> bool {
+        match *self {
+            BTreeNode::Leaf { ref keys, .. } => keys.is_empty(),
+            BTreeNode::Internal { ref children, .. } => children.is_empty(),
+        }
+    }
+}
+
+// Returns the number of KEYS <= KEY, which for an internal node is the child KEY belongs under.
+fn keys_at_most<K: Element>(keys: &[K], key: &K) -> usize {
+    keys.partition_point(|k| compare(k, key) != Ordering::Greater)
+}
+
+// Inserts the (key, data) pair below NODE. If NODE overflows it is split, and the key separating it
+// from its new right sibling is returned along with the sibling.
+fn btree_insert<K: Element>(node: &mut BTreeNode<K>, key: K, d: usize) -> Option<(K, BTreeNode<K>)> {
+    match *node {
+        BTreeNode::Leaf { ref mut keys, ref mut data } => {
+            match keys.binary_search_by(|k| compare(k, &key)) {
+                Ok(i) => {
+                    data[i] = d;
+                    return None;
+                },
+                Err(i) => {
+                    keys.insert(i, key);
+                    data.insert(i, d);
+                },
+            }
+            if keys.len() <= BTREE_ORDER {
+                return None;
+            }
+            let mid = keys.len() / 2;
+            let right_keys = keys.split_off(mid);
+            let right_data = data.split_off(mid);
+            Some((right_keys[0], BTreeNode::Leaf { keys: right_keys, data: right_data }))
+        },
+        BTreeNode::Internal { ref mut keys, ref mut children } => {
+            let i = keys_at_most(keys, &key);
+            if let Some((separator, right)) = btree_insert(&mut children[i], key, d) {
+                keys.insert(i, separator);
+                children.insert(i + 1, right);
+            }
+            if keys.len() <= BTREE_ORDER {
+                return None;
+            }
+            // The middle key moves up to separate the two halves.
+            let mid = keys.len() / 2;
+            let mut right_keys = keys.split_off(mid);
+            let separator = right_keys.remove(0);
+            let right_children = children.split_off(mid + 1);
+            Some((separator, BTreeNode::Internal { keys: right_keys, children: right_children }))
+        },
+    }
+}
+
+// Deletes KEY from below NODE. Nodes are not merged as they shrink, but a node left empty is removed
+// from its parent, so that every node but the root holds at least one key.
+fn btree_delete<K: Element>(node: &mut BTreeNode<K>, key: &K) {
+    match *node {
+        BTreeNode::Leaf { ref mut keys, ref mut data } => {
+            if let Ok(i) = keys.binary_search_by(|k| compare(k, key)) {
+                keys.remove(i);
+                data.remove(i);
+            }
+        },
+        BTreeNode::Internal { ref mut keys, ref mut children } => {
+            let i = keys_at_most(keys, key);
+            btree_delete(&mut children[i], key);
+            if children[i].is_empty() {
+                children.remove(i);
+                if !keys.is_empty() {
+                    keys.remove(if i == 0 { 0 } else { i - 1 });
+                }
+            }
+        },
+    }
+}
+
+fn btree_search<K: Element>(node: &BTreeNode<K>, key: &K) -> Option<usize> {
+    match *node {
+        BTreeNode::Leaf { ref keys, ref data } => keys.binary_search_by(|k| compare(k, key)).ok().map(|i| data[i]),
+        BTreeNode::Internal { ref keys, ref children } => btree_search(&children[keys_at_most(keys, key)], key),
+    }
+}
+
+// Returns the data of the smallest key below NODE.
+fn btree_first<K: Element>(node: &BTreeNode<K>) -> usize {
+    match *node {
+        BTreeNode::Leaf { ref data, .. } => data[0],
+        BTreeNode::Internal { ref children, .. } => btree_first(&children[0]),
+    }
+}
+
+// Returns the data of the largest key below NODE.
+fn btree_last<K: Element>(node: &BTreeNode<K>) -> usize {
+    match *node {
+        BTreeNode::Leaf { ref data, .. } => data[data.len() - 1],
+        BTreeNode::Internal { ref children, .. } => btree_last(&children[children.len() - 1]),
+    }
+}
+
+// Returns the data of the smallest key k below NODE s.t. k >= given key.
+fn btree_min_after<K: Element>(node: &BTreeNode<K>, key: &K) -> Option<usize> {
+    match *node {
+        BTreeNode::Leaf { ref keys, ref data } => {
+            let i = keys.partition_point(|k| compare(k, key) == Ordering::Less);
+            data.get(i).cloned()
+        },
+        BTreeNode::Internal { ref keys, ref children } => {
+            let i = keys_at_most(keys, key);
+            btree_min_after(&children[i], key).or_else(|| children.get(i + 1).map(btree_first))
+        },
+    }
+}
+
+// Returns the data of the greatest key k below NODE s.t. k <= given key.
+fn btree_max_before<K: Element>(node: &BTreeNode<K>, key: &K) -> Option<usize> {
+    match *node {
+        BTreeNode::Leaf { ref keys, ref data } => {
+            let i = keys_at_most(keys, key);
+            if i == 0 { None } else { Some(data[i - 1]) }
+        },
+        BTreeNode::Internal { ref keys, ref children } => {
+            let i = keys_at_most(keys, key);
+            btree_max_before(&children[i], key).or_else(|| if i == 0 { None } else { Some(btree_last(&children[i - 1])) })
+        },
+    }
+}
+
+// Applies F to the data of every key > THRESHOLD below NODE.
+fn btree_update_greater_than<K: Element, F: Fn(&mut usize)>(node: &mut BTreeNode<K>, threshold: &K, f: &F) {
+    match *node {
+        BTreeNode::Leaf { ref keys, ref mut data } => {
+            for d in data[keys_at_most(keys, threshold)..].iter_mut() {
+                f(d);
+            }
+        },
+        BTreeNode::Internal { ref keys, ref mut children } => {
+            for child in children[keys_at_most(keys, threshold)..].iter_mut() {
+                btree_update_greater_than(child, threshold, f);
+            }
+        },
+    }
+}
+
+fn btree_depth<K: Element>(node: &BTreeNode<K>) -> usize {
+    match *node {
+        BTreeNode::Leaf { .. } => 1,
+        BTreeNode::Internal { ref children, .. } => 1 + btree_depth(&children[0]),
+    }
+}
+
+fn btree_collect_pairs<K: Element>(node: &BTreeNode<K>, pairs: &mut Vec<(K, usize)>) {
+    match *node {
+        BTreeNode::Leaf { ref keys, ref data } => pairs.extend(keys.iter().cloned().zip(data.iter().cloned())),
+        BTreeNode::Internal { ref children, .. } => {
+            for child in children {
+                btree_collect_pairs(child, pairs);
+            }
+        },
+    }
+}
+
+#[derive(Clone)]
+pub struct BPlusTreeCrackerIndex<K: Element = i64> {
+    root: BTreeNode<K>
+}
+
+impl<K: Element> BPlusTreeCrackerIndex<K> {
+    pub fn new() -> BPlusTreeCrackerIndex<K> {
+        BPlusTreeCrackerIndex { root: BTreeNode::Leaf { keys: Vec::new(), data: Vec::new() } }
+    }
+
+    pub fn empty(&self) -> bool { self.root.is_empty() }
+}
+
+impl<K: Element> Default for BPlusTreeCrackerIndex<K> {
+    fn default() -> BPlusTreeCrackerIndex<K> {
+        BPlusTreeCrackerIndex::new()
+    }
+}
+
+impl<K: Element> CrackerIndex<K> for BPlusTreeCrackerIndex<K> {
+    fn insert(&mut self, key: K, data: usize) {
+        if let Some((separator, right)) = btree_insert(&mut self.root, key, data) {
+            let left = mem::replace(&mut self.root, BTreeNode::Leaf { keys: Vec::new(), data: Vec::new() });
+            self.root = BTreeNode::Internal { keys: vec![separator], children: vec![left, right] };
+        }
+    }
+
+    fn delete(&mut self, key: K) {
+        btree_delete(&mut self.root, &key);
+        // A root left with a single child is replaced by it, and an empty one by an empty leaf.
+        loop {
+            let child = match self.root {
+                BTreeNode::Internal { ref mut children, .. } if children.len() <= 1 => children.pop(),
+                _ => return,
+            };
+            self.root = child.unwrap_or(BTreeNode::Leaf { keys: Vec::new(), data: Vec::new() });
+        }
+    }
+
+    fn get(&self, key: K) -> Option<usize> {
+        btree_search(&self.root, &key)
+    }
+
+    fn upper_bound(&self, key: &K) -> Option<usize> {
+        btree_min_after(&self.root, key)
+    }
+
+    fn lower_bound(&self, key: &K) -> Option<usize> {
+        btree_max_before(&self.root, key)
+    }
+
+    fn subtract_where_greater_than(&mut self, threshold: K, amount: usize) {
+        btree_update_greater_than(&mut self.root, &threshold, &|d: &mut usize| *d -= amount);
+    }
+
+    fn add_where_greater_than(&mut self, threshold: K, amount: usize) {
+        btree_update_greater_than(&mut self.root, &threshold, &|d: &mut usize| *d += amount);
+    }
+
+    fn pairs(&self) -> Vec<(K, usize)> {
+        let mut pairs = Vec::new();
+        btree_collect_pairs(&self.root, &mut pairs);
+        pairs
+    }
+
+    fn clear(&mut self) {
+        *self = BPlusTreeCrackerIndex::new();
+    }
+
+    fn depth(&self) -> usize {
+        if self.empty() { 0 } else { btree_depth(&self.root) }
+    }
+
+    fn box_clone(&self) -> Box<dyn CrackerIndex<K>> {
+        Box::new(self.clone())
+    }
+}
+
+// Keys and data in two sorted vectors, searched by bisection. Lookups touch the fewest cache lines
+// of any of the indices, but an insert shifts every larger key along, so it suits workloads whose
+// pieces settle after the first queries.
+#[derive(Clone)]
+pub struct SortedCrackerIndex<K: Element = i64> {
+    keys: Vec<K>,
+    data: Vec<usize>,
+}
+
+impl<K: Element> SortedCrackerIndex<K> {
+    pub fn new() -> SortedCrackerIndex<K> {
+        SortedCrackerIndex { keys: Vec::new(), data: Vec::new() }
+    }
+
+    pub fn empty(&self) -> bool { self.keys.is_empty() }
+
+    fn find(&self, key: &K) -> Result<usize, usize> {
+        self.keys.binary_search_by(|k| compare(k, key))
+    }
+}
+
+impl<K: Element> Default for SortedCrackerIndex<K> {
+    fn default() -> SortedCrackerIndex<K> {
+        SortedCrackerIndex::new()
+    }
+}
+
+impl<K: Element> CrackerIndex<K> for SortedCrackerIndex<K> {
+    fn insert(&mut self, key: K, data: usize) {
+        match self.find(&key) {
+            Ok(i) => self.data[i] = data,
+            Err(i) => {
+                self.keys.insert(i, key);
+                self.data.insert(i, data);
             },
-            None => {}
         }
     }
-}
\ No newline at end of file
+
+    fn delete(&mut self, key: K) {
+        if let Ok(i) = self.find(&key) {
+            self.keys.remove(i);
+            self.data.remove(i);
+        }
+    }
+
+    fn get(&self, key: K) -> Option<usize> {
+        self.find(&key).ok().map(|i| self.data[i])
+    }
+
+    fn upper_bound(&self, key: &K) -> Option<usize> {
+        let i = match self.find(key) { Ok(i) => i, Err(i) => i };
+        self.data.get(i).cloned()
+    }
+
+    fn lower_bound(&self, key: &K) -> Option<usize> {
+        match self.find(key) {
+            Ok(i) => Some(self.data[i]),
+            Err(0) => None,
+            Err(i) => Some(self.data[i - 1]),
+        }
+    }
+
+    fn subtract_where_greater_than(&mut self, threshold: K, amount: usize) {
+        let i = keys_at_most(&self.keys, &threshold);
+        for d in self.data[i..].iter_mut() {
+            *d -= amount;
+        }
+    }
+
+    fn add_where_greater_than(&mut self, threshold: K, amount: usize) {
+        let i = keys_at_most(&self.keys, &threshold);
+        for d in self.data[i..].iter_mut() {
+            *d += amount;
+        }
+    }
+
+    fn pairs(&self) -> Vec<(K, usize)> {
+        self.keys.iter().cloned().zip(self.data.iter().cloned()).collect()
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.data.clear();
+    }
+
+    fn depth(&self) -> usize {
+        if self.empty() { 0 } else { 1 }
+    }
+
+    fn box_clone(&self) -> Box<dyn CrackerIndex<K>> {
+        Box::new(self.clone())
+    }
+}
@@ -9,18 +9,18 @@ pub fn graph_density(n: i64, e: usize) -> f64 {
 // Deals out the numbers from 0 to n-1 inclusive in a random order as usizes.
 fn deal(n: usize) -> Vec<usize> {
     // Put 1 - n in a bag.
-    let mut dealing: Vec<usize> = Vec::with_capacity(n as usize);
+    let mut dealing: Vec<usize> = Vec::with_capacity(n);
     for i in 0..n {
         dealing.push(i);
     }
-    let mut dealt: Vec<usize> = Vec::with_capacity(n as usize);
+    let mut dealt: Vec<usize> = Vec::with_capacity(n);
     let mut rng = rand::thread_rng();
     let mut chosen = 0;
     while dealing.len() > 1 {
         let random = rng.gen_range(0, n - chosen);
         dealt.push(dealing[random]);
         dealing.remove(random);
-        chosen = chosen + 1;
+        chosen += 1;
     }
     dealt.push(dealing[0]);
     dealt
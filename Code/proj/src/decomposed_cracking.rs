@@ -3,48 +3,104 @@
 
 // uses map! macro.
 
-use cracker_index::AVLCrackerIndex;
+use cracker_index::IndexKind;
 use column::Column;
+use column::Col;
+use column::Dictionary;
+use column::Element;
+use column::half_open;
 use column::IntCol;
+use column::Values;
+use column::worker_threads;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
+use cracked_table::owned_column_names;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::slice::Iter;
+use std::ops::Range;
 
+// Every column of a table holds the same element type: i64 unless given, u32 or i32 to halve the
+// memory of a graph whose node ids fit in 32 bits, or f64 for weights and ranks.
 #[derive(Clone)]
-pub struct DeCrackedTable {
+pub struct DeCrackedTable<T: Element = i64> {
     pub count: usize,
-    pub crk_col: IntCol,
-    pub columns: HashMap<String, IntCol>,
+    pub crk_col_name: String,
+    pub crk_col: Col<T>,
+    // Cracker columns on other columns, parked by the name of the column they crack until use_crk_col
+    // switches to them.
+    pub crk_cols: HashMap<String, Col<T>>,
+    pub settings: CrackSettings,
+    // Base indices of the deleted rows. Their values stay in the base columns, so that base indices
+    // remain valid.
+    pub deleted: HashSet<usize>,
+    pub columns: HashMap<String, Col<T>>,
+    // The dictionaries of the string columns, whose codes are held in columns.
+    pub dicts: HashMap<String, Dictionary>,
 }
 
-impl DeCrackedTable {
-    pub fn new() -> DeCrackedTable {
+impl<T: Element> DeCrackedTable<T> {
+    pub fn new() -> DeCrackedTable<T> {
         DeCrackedTable {
             count: 0,
-            crk_col: IntCol::empty(),
-            columns: HashMap::new()
+            crk_col_name: "".to_string(),
+            crk_col: Col::empty(),
+            crk_cols: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
+            columns: HashMap::new(),
+            dicts: HashMap::new(),
         }
     }
 
     pub fn new_columns(&mut self, col_names: Vec<String>) {
         for col in col_names {
-            self.columns.insert(col, IntCol::empty());
+            self.columns.insert(col, Col::empty());
         }
     }
 
     pub fn set_crk_col(&mut self, col_name: String) {
-        match self.columns.get(&col_name) {
-            Some(ref c) => {
-                self.crk_col.v        = c.v.clone();
-                self.crk_col.crk      = c.v.clone();
-                self.crk_col.base_idx = (0..self.count).collect();
-            },
+        // The current cracker column is parked, so that use_crk_col can switch back to it.
+        if self.crk_col_name != col_name {
+            self.crk_col.park(&self.crk_col_name, &mut self.crk_cols);
+        }
+        self.crk_cols.remove(&col_name);
+        self.crk_col = match self.columns.get(&col_name) {
+            Some(c) => Col::cracking(&c.v, &self.deleted, self.settings.index_kind),
             None => panic!("set_crk_col: no such col"),
         };
+        self.crk_col_name = col_name;
+    }
+
+    // Moves the cracker index of every cracker column into a structure of the given KIND, keeping the
+    // cracks made so far. The array index can only be used by i64 tables.
+    pub fn set_cracker_index(&mut self, kind: IndexKind) {
+        self.settings.index_kind = kind;
+        for c in self.cracker_columns_mut() {
+            c.set_index(kind.new_index());
+        }
+    }
+
+    // Returns every cracker column of the table, the one in use and those parked by use_crk_col.
+    pub fn cracker_columns_mut(&mut self) -> Vec<&mut Col<T>> {
+        let mut cols: Vec<&mut Col<T>> = self.crk_cols.values_mut().collect();
+        cols.push(&mut self.crk_col);
+        cols
+    }
+
+    // Makes the cracker column on COL_NAME the one which queries crack, parking the current one. The
+    // cracker column is built if the table has none on COL_NAME yet.
+    pub fn use_crk_col(&mut self, col_name: &str) {
+        if !self.crk_col.unpark(&mut self.crk_col_name, col_name, &mut self.crk_cols) {
+            self.set_crk_col(col_name.to_string());
+        }
     }
 
     // TODO: Improve exception handling in this function
-    pub fn insert(&mut self, new_values: &mut HashMap<String, Vec<i64>>) {
+    pub fn insert(&mut self, new_values: &mut HashMap<String, Vec<T>>) {
+        let old_count = self.count;
         let mut n_new_tuples = 0;
         for (key, val) in self.columns.iter_mut() {
             let new_elements = new_values.get_mut(key).unwrap();
@@ -57,37 +113,47 @@ impl DeCrackedTable {
             }
         }
         self.count += n_new_tuples;
+
+        // Rows inserted after the cracker column is set are merged into it by the queries which need them
+        if !self.crk_col_name.is_empty() {
+            self.crk_col.add_pending(&self.columns[&self.crk_col_name].v, old_count..self.count);
+        }
+        for (name, c) in self.crk_cols.iter_mut() {
+            c.add_pending(&self.columns[name].v, old_count..self.count);
+        }
     }
 
-    pub fn get_col(&self, col: String) -> Option<&IntCol> {
+    pub fn get_col(&self, col: String) -> Option<&Col<T>> {
         self.columns.get(&col)
     }
 
-    pub fn get_indices(&self, indices: Iter<usize>) -> DeCrackedTable {
-        let mut selection: HashMap<String, IntCol> = HashMap::new();
+    pub fn get_indices(&self, indices: Iter<usize>) -> DeCrackedTable<T> {
+        let mut selection: HashMap<String, Col<T>> = HashMap::new();
         for (name, col) in &self.columns {
             let mut v_buffer = Vec::with_capacity(indices.len());
             for &i in indices.clone() {
                 v_buffer.push(col.v[i]);
             }
-            let mut c_buffer = IntCol::empty();
+            let mut c_buffer = Col::empty();
             c_buffer.v = v_buffer;
             selection.insert(name.clone(), c_buffer);
         }
 
         let mut t = DeCrackedTable::new();
+        t.settings = self.settings.clone();
         t.columns = selection;
+        t.dicts = self.dicts.clone();
         t.count = indices.len();
 
         let mut indexed_crk_v = Vec::with_capacity(indices.len());
-        if self.crk_col.crk.len() > 0 {
+        if !self.crk_col.crk.is_empty() {
             let mut indexed_crk_col = Vec::with_capacity(indices.len());
             for &i in indices.clone() {
                 indexed_crk_col.push(self.crk_col.crk[i]);
                 indexed_crk_v.push(self.crk_col.v[i]);
             }
             t.crk_col.crk = indexed_crk_col;
-            t.crk_col.crk_idx = AVLCrackerIndex::new();
+            t.crk_col.crk_idx = self.settings.index_kind.new_index();
         } else {
             for &i in indices.clone() {
                 indexed_crk_v.push(self.crk_col.v[i]);
@@ -97,7 +163,7 @@ impl DeCrackedTable {
         t
     }
 
-    pub fn get_values(&self, indices: Iter<usize>, col: &str) -> Vec<i64> {
+    pub fn get_values(&self, indices: Iter<usize>, col: &str) -> Vec<T> {
         let mut buf = Vec::new();
         for &i in indices {
             buf.push(self.columns[&col.to_string()].v[i]);
@@ -110,51 +176,81 @@ impl DeCrackedTable {
             col.rearrange(indices.clone());
         }
         self.crk_col.rearrange(indices.clone());
+        for c in self.crk_cols.values_mut() {
+            c.rearrange(indices.clone());
+        }
     }
 
-    // Returns the elements of T where the cracker columns's value equals X
-    pub fn cracker_select_specific(&mut self, x: i64, col: &str) -> Vec<i64> {
+    // Cracks the column on X and X + 1, returning the positions of the elements equal to X.
+    pub fn crack_specific(&mut self, x: T) -> Range<usize> {
+        let x1 = match x.successor() {
+            Some(x1) => x1,
+            // Nothing lies above the greatest value, so its elements are those from it onwards.
+            None => return self.crack_select_range(x, x, true, true),
+        };
+        self.crk_col.merge_pending(x, Some(x1), &self.columns);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(x, Some(x1));
+        }
+        self.crk_col.stochastic_crack(x, &mut self.settings);
+        // A piece already cracked out for X is found from the cracker index alone.
+        if let (Some(p_x), Some(p_x1)) = (self.crk_col.crk_idx.get(x), self.crk_col.crk_idx.get(x1)) {
+            return p_x..p_x1;
+        }
         // Start with a pointer at both ends of the array: p_low, p_high
         let mut p_low = self.crk_col.crk_idx.lower_bound(&x).unwrap_or(0);
-        let mut p_high = self.crk_col.crk_idx.upper_bound(&(x + 1)).unwrap_or(self.count) - 1;
-        if p_high + 1 == 0 { return vec![] }; // Value lower than lowest value in column - No results.
+        if p_low >= self.crk_col.crk.len() { return 0..0 }; // Value higher than highest value in column - No results.
+        let p_end = self.crk_col.crk_idx.upper_bound(&x1).unwrap_or(self.crk_col.crk.len());
+        if p_end == 0 { return 0..0 }; // Value lower than lowest value in column - No results.
+        if p_end - p_low > self.settings.parallel_threshold {
+            // A large piece is cracked on X and then on X + 1 with the parallel kernel.
+            let threads = worker_threads();
+            let p_x = match self.crk_col.crk_idx.get(x) {
+                Some(p) => p,
+                None => self.crk_col.partition_parallel(p_low, p_end, x, threads),
+            };
+            let p_x1 = self.crk_col.partition_parallel(p_x, p_end, x1, threads);
+            self.crk_col.crk_idx.insert(x, p_x);
+            self.crk_col.crk_idx.insert(x1, p_x1);
+            return p_x..p_x1;
+        }
+        let mut p_high = p_end - 1;
 
         // while p_low is pointing at an element satisfying c_low,  move it forwards
         while self.crk_col.crk[p_low] < x {
             p_low += 1;
-            if p_low == self.count as usize {
-                return vec![];
+            if p_low == self.crk_col.crk.len() {
+                return 0..0;
             }
         }
 
         // while p_high is pointing at an element satisfying c_high, move it backwards
         while self.crk_col.crk[p_high] > x {
             if p_high == 0 {
-                return vec![];
+                return 0..0;
             }
             p_high -= 1;
         }
 
         if p_low == p_high {
-            return self.get_values(self.crk_col.base_idx[p_low..(p_high + 1)].iter(), col);
+            return p_low..(p_high + 1);
         }
 
 
-        let mut p_itr = p_low.clone();
+        let mut p_itr = p_low;
 
         while p_itr <= p_high {
             if self.crk_col.crk[p_itr] < x {
-                self.crk_col.crk.swap(p_low, p_itr);
-                self.crk_col.base_idx.swap(p_low, p_itr);
+                self.crk_col.swap(p_low, p_itr);
                 while self.crk_col.crk[p_low] < x {
                     p_low += 1;
                 }
                 if p_itr < p_low {
-                    p_itr = p_low.clone();
+                    p_itr = p_low;
                 }
             } else if self.crk_col.crk[p_itr] > x {
-                self.crk_col.crk.swap(p_itr, p_high);
-                self.crk_col.base_idx.swap(p_itr, p_high);
+                self.crk_col.swap(p_itr, p_high);
                 while self.crk_col.crk[p_high] > x {
                     p_high -= 1;
                 }
@@ -164,18 +260,265 @@ impl DeCrackedTable {
         }
 
         self.crk_col.crk_idx.insert(x, p_low);
-        self.crk_col.crk_idx.insert(x + 1, p_high + 1);
-        self.get_values(self.crk_col.base_idx[p_low..(p_high + 1)].iter(), col)
+        self.crk_col.crk_idx.insert(x1, p_high + 1);
+        p_low..(p_high + 1)
+    }
+
+    // Returns the elements of T where the cracker columns's value equals X
+    pub fn cracker_select_specific(&mut self, x: T, col: &str) -> Vec<T> {
+        self.cracker_iter_specific(x, col).collect()
+    }
+
+    // As cracker_select_specific, but borrowing the elements from the table. Tables over i64 get this
+    // from CrackedTable; this one serves every element type.
+    pub fn cracker_iter_specific<'a>(&'a mut self, x: T, col: &str) -> Values<'a, T> {
+        if let Some(r) = self.scan_piece(x, x.successor()) {
+            return self.filtered_values(r, x, x.successor(), col);
+        }
+        let r = self.crack_specific(x);
+        self.values(r, col)
+    }
+
+    // Returns the elements of COL at the positions R of the cracker column, borrowing them from the table.
+    pub fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, T> {
+        self.crk_col.values(r, col, &self.columns[col].v)
+    }
+
+    // Returns the positions of the pieces holding the elements in [L, H) if they are small enough to be
+    // scanned rather than cracked.
+    pub fn scan_piece(&mut self, l: T, h: Option<T>) -> Option<Range<usize>> {
+        self.crk_col.merge_pending(l, h, &self.columns);
+        self.crk_col.small_piece(l, h, self.settings.min_piece_size)
+    }
+
+    // Returns the elements of COL at the positions R whose cracker column values lie in [L, H).
+    pub fn filtered_values<'a>(&'a self, r: Range<usize>, l: T, h: Option<T>, col: &str) -> Values<'a, T> {
+        self.crk_col.filtered_values(r, l, h, &self.columns[col].v)
+    }
+
+    // Adds a cracker map for COL, which from then on is cracked along with the cracker column.
+    pub fn add_crk_map(&mut self, col: &str) {
+        if !self.crk_col.crk_maps.contains_key(col) {
+            let map = self.get_values(self.crk_col.base_idx.iter(), col);
+            self.crk_col.crk_maps.insert(col.to_string(), map);
+        }
+    }
+
+    // Returns the elements of COL where the cracker column's value equals X, as a slice of the cracker
+    // map of COL.
+    pub fn cracker_select_map(&mut self, x: T, col: &str) -> &[T] {
+        self.add_crk_map(col);
+        let r = self.crack_specific(x);
+        &self.crk_col.crk_maps[col][r]
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions.
+    pub fn crack_select_range(&mut self, low: T, high: T, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        self.crk_col.merge_pending(l, h, &self.columns);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(l, h);
+        }
+        self.crk_col.stochastic_crack(l, &mut self.settings);
+        if let Some(h) = h {
+            self.crk_col.stochastic_crack(h, &mut self.settings);
+        }
+        let (p_low, p_high) = self.crk_col.crack_range(l, h, self.settings.parallel_threshold);
+        p_low..p_high
+    }
+
+    // Returns the elements of T where the cracker columns's value is between LOW and HIGH, with
+    // inclusivity given by INC_L and INC_H.
+    pub fn cracker_select_range(&mut self, low: T, high: T, inc_l: bool, inc_h: bool, col: &str) -> Vec<T> {
+        self.cracker_iter_range(low, high, inc_l, inc_h, col).collect()
+    }
+
+    // As cracker_select_range, but borrowing the elements from the table.
+    pub fn cracker_iter_range<'a>(&'a mut self, low: T, high: T, inc_l: bool, inc_h: bool, col: &str) -> Values<'a, T> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return self.values(0..0, col),
+        };
+        if let Some(r) = self.scan_piece(l, h) {
+            return self.filtered_values(r, l, h, col);
+        }
+        let r = self.crack_select_range(low, high, inc_l, inc_h);
+        self.values(r, col)
+    }
+
+    // Returns statistics on how far the cracker column has been cracked.
+    pub fn stats(&self) -> CrackStats {
+        self.crk_col.stats()
+    }
+
+}
+
+impl<T: Element> Default for DeCrackedTable<T> {
+    fn default() -> DeCrackedTable<T> {
+        DeCrackedTable::new()
+    }
+}
+
+// String columns hold their codes as i64, so only tables of i64 can have them.
+impl DeCrackedTable {
+    // Adds an empty string column for each name in COL_NAMES. Rows are inserted with the codes which
+    // encode gives their strings.
+    pub fn new_str_columns(&mut self, col_names: Vec<String>) {
+        for col in col_names {
+            self.columns.insert(col.clone(), IntCol::empty());
+            self.dicts.insert(col, Dictionary::new());
+        }
+    }
+
+    // Returns the codes of VALUES in the string column COL, giving new strings codes of their own.
+    pub fn encode(&mut self, col: &str, values: &[String]) -> Vec<i64> {
+        match self.dicts.get_mut(col) {
+            Some(dict) => values.iter().map(|s| dict.encode(s)).collect(),
+            None => panic!("encode: no string column called {}", col),
+        }
+    }
+
+    // Returns the strings of the string column COL which CODES stand for.
+    pub fn decode(&self, col: &str, codes: &[i64]) -> Vec<String> {
+        match self.dicts.get(col) {
+            Some(dict) => codes.iter().map(|&c| dict.decode(c).to_string()).collect(),
+            None => panic!("decode: no string column called {}", col),
+        }
+    }
+
+    // Returns the elements of COL where the cracker column, which must be a string column, equals X
+    pub fn cracker_select_str_eq(&mut self, x: &str, col: &str) -> Vec<i64> {
+        let code = match self.dicts.get(&self.crk_col_name) {
+            Some(dict) => dict.code(x),
+            None => panic!("cracker_select_str_eq: {} is not a string column", self.crk_col_name),
+        };
+        match code {
+            Some(c) => self.cracker_select_specific(c, col),
+            None => vec![], // No row has ever held X.
+        }
+    }
+
+    // Returns the strings of the string column COL where the cracker columns's value equals X
+    pub fn cracker_select_specific_str(&mut self, x: i64, col: &str) -> Vec<String> {
+        let codes = self.cracker_select_specific(x, col);
+        self.decode(col, &codes)
+    }
+
+    // Returns the strings of the string column COL where the cracker columns's value is between LOW
+    // and HIGH, with inclusivity given by INC_L and INC_H.
+    pub fn cracker_select_range_str(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool, col: &str) -> Vec<String> {
+        let codes = self.cracker_select_range(low, high, inc_l, inc_h, col);
+        self.decode(col, &codes)
+    }
+}
+
+impl CrackedTable for DeCrackedTable {
+    fn new() -> DeCrackedTable {
+        DeCrackedTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        DeCrackedTable::new_columns(self, col_names.iter().map(|c| c.to_string()).collect())
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        DeCrackedTable::insert(self, &mut owned_column_names(new_values))
+    }
+
+    fn use_crk_col(&mut self, col_name: &str) {
+        DeCrackedTable::use_crk_col(self, col_name)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        DeCrackedTable::cracker_columns_mut(self)
+    }
+
+    fn set_cracker_index(&mut self, kind: IndexKind) {
+        DeCrackedTable::set_cracker_index(self, kind)
+    }
+
+    fn stats(&self) -> CrackStats {
+        DeCrackedTable::stats(self)
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        DeCrackedTable::set_crk_col(self, col_name.to_string())
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        DeCrackedTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        DeCrackedTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        DeCrackedTable::values(self, r, col)
+    }
+
+    fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        DeCrackedTable::scan_piece(self, l, h)
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        DeCrackedTable::filtered_values(self, r, l, h, col)
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(DeCrackedTable::get_col(self, col.to_string()).unwrap_or_else(|| panic!("get_col: No column called {}", col)).v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> DeCrackedTable {
+        DeCrackedTable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        DeCrackedTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
     }
 
-    // Counts the places where a given column equals a given value
-    pub fn count_col_eq(&self, col: &str, eq: i64) -> i64 {
-        self.get_col(col.to_string()).unwrap().v.iter().map(|&x|(x==eq)as i64).fold(0,|sum,x|sum+x) as i64
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_col.crk_idx.pairs()
     }
 }
 
 // Returns an adjacency list built from the two vectors of adjacent nodes.
-pub fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str) -> DeCrackedTable {
+pub fn from_adjacency_vectors<T: Element>(src_node: Vec<T>, dst_node: Vec<T>, crk: &str) -> DeCrackedTable<T> {
     let mut adjacency_list = DeCrackedTable::new();
     adjacency_list.new_columns(vec!["src".to_string(), "dst".to_string()]);
     adjacency_list.insert(&mut map!{"src".to_string() => src_node, "dst".to_string() => dst_node});
@@ -0,0 +1,482 @@
+// Compact encodings of the base indices and values held by cracker columns.
+
+use column::Element;
+
+use std::cmp;
+use std::mem;
+use std::ops::Range;
+
+// A sequence of base indices, frame-of-reference encoded: each is stored as its difference from the
+// smallest, in as few bytes as hold the largest difference. Base indices are bounded by the row
+// count, so a block of a table of under 2^32 rows never needs more than half the space of the plain
+// indices, and a block of rows added together needs far less.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ForBlock {
+    reference: usize,
+    // Bytes per difference: 1, 2, 4 or 8; 0 for an empty block.
+    width: usize,
+    bytes: Vec<u8>,
+}
+
+impl ForBlock {
+    pub fn encode(values: &[usize]) -> ForBlock {
+        let reference = match values.iter().min() {
+            Some(&min) => min,
+            None => return ForBlock::default(),
+        };
+        let range = values.iter().max().unwrap() - reference;
+        let width = match range {
+            0..=0xff => 1,
+            0x100..=0xffff => 2,
+            0x1_0000..=0xffff_ffff => 4,
+            _ => 8,
+        };
+        let mut bytes = Vec::with_capacity(values.len() * width);
+        for &x in values {
+            bytes.extend_from_slice(&((x - reference) as u64).to_le_bytes()[..width]);
+        }
+        ForBlock { reference, width, bytes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    // Returns the I-th base index of the block.
+    #[inline]
+    pub fn get(&self, i: usize) -> usize {
+        let mut le = [0u8; 8];
+        le[..self.width].copy_from_slice(&self.bytes[(i * self.width)..((i + 1) * self.width)]);
+        self.reference + u64::from_le_bytes(le) as usize
+    }
+
+    pub fn iter(&self) -> ForIter<'_> {
+        ForIter { block: self, i: 0 }
+    }
+
+    pub fn decode(&self) -> Vec<usize> {
+        self.iter().collect()
+    }
+
+    // Returns the block with X appended.
+    pub fn push(&self, x: usize) -> ForBlock {
+        let mut values = self.decode();
+        values.push(x);
+        ForBlock::encode(&values)
+    }
+
+    // Returns the block without the base index X, or None if it does not hold X.
+    pub fn without(&self, x: usize) -> Option<ForBlock> {
+        let mut values = self.decode();
+        let p = values.iter().position(|&y| y == x)?;
+        values.remove(p);
+        Some(ForBlock::encode(&values))
+    }
+
+    // The number of bytes the block takes up.
+    pub fn size(&self) -> usize {
+        mem::size_of::<ForBlock>() + self.bytes.len()
+    }
+
+    // The parts of the block, for storing it.
+    pub fn parts(&self) -> (usize, usize, &[u8]) {
+        (self.reference, self.width, &self.bytes)
+    }
+
+    pub fn from_parts(reference: usize, width: usize, bytes: Vec<u8>) -> ForBlock {
+        ForBlock { reference, width, bytes }
+    }
+}
+
+pub struct ForIter<'a> {
+    block: &'a ForBlock,
+    i: usize,
+}
+
+impl<'a> Iterator for ForIter<'a> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        if self.i == self.block.len() {
+            return None;
+        }
+        self.i += 1;
+        Some(self.block.get(self.i - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.block.len() - self.i;
+        (left, Some(left))
+    }
+}
+
+// A column of integers, each stored as its difference from the smallest in as few bits as hold the
+// difference of the largest, packed end to end into words. Elements are read and swapped in place,
+// so that a column of node ids under 2^20 takes up less than a third of the space of an i64 column.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BitPacked {
+    reference: i64,
+    // Bits per difference, from 0 for a column of a single value up to 64.
+    width: u32,
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitPacked {
+    pub fn pack(values: &[i64]) -> BitPacked {
+        let (min, max) = match (values.iter().min(), values.iter().max()) {
+            (Some(&min), Some(&max)) => (min, max),
+            _ => return BitPacked::default(),
+        };
+        let width = 64 - (max.wrapping_sub(min) as u64).leading_zeros();
+        let mut packed = BitPacked { reference: min, width, len: values.len(), words: Vec::new() };
+        packed.words = vec![0; packed.words_for(values.len())];
+        for (i, &x) in values.iter().enumerate() {
+            packed.set(i, x);
+        }
+        packed
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn words_for(&self, len: usize) -> usize {
+        (len * self.width as usize).div_ceil(64)
+    }
+
+    #[inline]
+    fn mask(&self) -> u64 {
+        if self.width == 64 { !0 } else { (1 << self.width) - 1 }
+    }
+
+    // Returns whether X can be stored without repacking the column.
+    pub fn fits(&self, x: i64) -> bool {
+        let d = x as i128 - self.reference as i128;
+        d >= 0 && d <= self.mask() as i128
+    }
+
+    // Returns the I-th element.
+    #[inline]
+    pub fn get(&self, i: usize) -> i64 {
+        if self.width == 0 {
+            return self.reference;
+        }
+        let bit = i * self.width as usize;
+        let (w, o) = (bit / 64, bit % 64);
+        let mut d = self.words[w] >> o;
+        if o + self.width as usize > 64 {
+            d |= self.words[w + 1] << (64 - o);
+        }
+        self.reference.wrapping_add((d & self.mask()) as i64)
+    }
+
+    // Overwrites the I-th element with X, which must fit.
+    #[inline]
+    pub fn set(&mut self, i: usize, x: i64) {
+        if !self.fits(x) {
+            panic!("set: {} does not fit in {} bits from {}", x, self.width, self.reference);
+        }
+        if self.width == 0 {
+            return;
+        }
+        let d = x.wrapping_sub(self.reference) as u64;
+        let mask = self.mask();
+        let bit = i * self.width as usize;
+        let (w, o) = (bit / 64, bit % 64);
+        self.words[w] = (self.words[w] & !(mask << o)) | (d << o);
+        if o + self.width as usize > 64 {
+            let spill = o + self.width as usize - 64;
+            self.words[w + 1] = (self.words[w + 1] & !((1 << spill) - 1)) | (d >> (64 - o));
+        }
+    }
+
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let x = self.get(a);
+        let y = self.get(b);
+        self.set(a, y);
+        self.set(b, x);
+    }
+
+    // Appends VALUES, repacking the column at a greater width, or from a smaller reference, if any of
+    // them do not fit.
+    pub fn extend(&mut self, values: &[i64]) {
+        if self.is_empty() || !values.iter().all(|&x| self.fits(x)) {
+            let mut all = self.unpack();
+            all.extend_from_slice(values);
+            *self = BitPacked::pack(&all);
+            return;
+        }
+        let len = self.len + values.len();
+        let words = self.words_for(len);
+        self.words.resize(words, 0);
+        for (i, &x) in values.iter().enumerate() {
+            self.set(self.len + i, x);
+        }
+        self.len = len;
+    }
+
+    // Removes the last element.
+    pub fn pop(&mut self) -> Option<i64> {
+        if self.is_empty() {
+            return None;
+        }
+        let x = self.get(self.len - 1);
+        self.len -= 1;
+        let words = self.words_for(self.len);
+        self.words.truncate(words);
+        Some(x)
+    }
+
+    pub fn iter(&self) -> BitPackedIter<'_> {
+        BitPackedIter { packed: self, i: 0 }
+    }
+
+    pub fn unpack(&self) -> Vec<i64> {
+        self.iter().collect()
+    }
+
+    // The number of bytes the packed elements take up.
+    pub fn size(&self) -> usize {
+        self.words.len() * mem::size_of::<u64>()
+    }
+
+    // The parts of the column, for storing it.
+    pub fn parts(&self) -> (i64, u32, usize, &[u64]) {
+        (self.reference, self.width, self.len, &self.words)
+    }
+
+    pub fn from_parts(reference: i64, width: u32, len: usize, words: Vec<u64>) -> BitPacked {
+        BitPacked { reference, width, len, words }
+    }
+}
+
+pub struct BitPackedIter<'a> {
+    packed: &'a BitPacked,
+    i: usize,
+}
+
+impl<'a> Iterator for BitPackedIter<'a> {
+    type Item = i64;
+
+    #[inline]
+    fn next(&mut self) -> Option<i64> {
+        if self.i == self.packed.len() {
+            return None;
+        }
+        self.i += 1;
+        Some(self.packed.get(self.i - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.packed.len() - self.i;
+        (left, Some(left))
+    }
+}
+
+// A column run-length encoded as the value of each run and the position just past its end. Elements
+// are read by position, and can be overwritten, inserted and removed by position, so that the column
+// can be kept aligned with a cracker column as it is cracked. It pays off for columns, such as the
+// payload of a hub vertex with duplicate edges, whose runs are long.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunLengthColumn<T: Element> {
+    values: Vec<T>,
+    ends: Vec<usize>,
+}
+
+// Appends a run of X ending at END, extending the last run instead if it holds X too.
+fn push_run<T: Element>(values: &mut Vec<T>, ends: &mut Vec<usize>, x: T, end: usize) {
+    if values.last() == Some(&x) {
+        *ends.last_mut().unwrap() = end;
+    } else {
+        values.push(x);
+        ends.push(end);
+    }
+}
+
+impl<T: Element> RunLengthColumn<T> {
+    pub fn encode(column: &[T]) -> RunLengthColumn<T> {
+        let mut encoded = RunLengthColumn { values: Vec::new(), ends: Vec::new() };
+        for (p, &x) in column.iter().enumerate() {
+            push_run(&mut encoded.values, &mut encoded.ends, x, p + 1);
+        }
+        encoded
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.last().cloned().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    // Returns the number of runs the column is held in.
+    pub fn runs(&self) -> usize {
+        self.values.len()
+    }
+
+    // Returns the run holding position P.
+    #[inline]
+    fn run_of(&self, p: usize) -> usize {
+        self.ends.partition_point(|&e| e <= p)
+    }
+
+    fn run_start(&self, k: usize) -> usize {
+        if k == 0 { 0 } else { self.ends[k - 1] }
+    }
+
+    #[inline]
+    pub fn get(&self, p: usize) -> T {
+        self.values[self.run_of(p)]
+    }
+
+    // Returns the elements at the positions R.
+    pub fn iter(&self, r: Range<usize>) -> RunIter<'_, T> {
+        RunIter { column: self, run: self.run_of(r.start), r }
+    }
+
+    pub fn decode(&self, r: Range<usize>) -> Vec<T> {
+        self.iter(r).collect()
+    }
+
+    // Overwrites the elements from START onwards with VALUES, merging the runs this leaves side by
+    // side which hold the same value.
+    pub fn write(&mut self, start: usize, values: &[T]) {
+        if values.is_empty() {
+            return;
+        }
+        let end = start + values.len();
+        let (first, last) = (self.run_of(start), self.run_of(end - 1));
+        // The runs either side of those written over are encoded again too, in case they merge.
+        let lo = first.saturating_sub(1);
+        let hi = cmp::min(last + 1, self.runs() - 1);
+
+        let mut new_values = Vec::new();
+        let mut new_ends = Vec::new();
+        if lo < first {
+            push_run(&mut new_values, &mut new_ends, self.values[lo], self.ends[lo]);
+        }
+        if self.run_start(first) < start {
+            push_run(&mut new_values, &mut new_ends, self.values[first], start);
+        }
+        for (i, &x) in values.iter().enumerate() {
+            push_run(&mut new_values, &mut new_ends, x, start + i + 1);
+        }
+        if self.ends[last] > end {
+            push_run(&mut new_values, &mut new_ends, self.values[last], self.ends[last]);
+        }
+        if hi > last {
+            push_run(&mut new_values, &mut new_ends, self.values[hi], self.ends[hi]);
+        }
+        self.values.splice(lo..(hi + 1), new_values);
+        self.ends.splice(lo..(hi + 1), new_ends);
+    }
+
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        let (x, y) = (self.get(a), self.get(b));
+        if x != y {
+            self.write(a, &[y]);
+            self.write(b, &[x]);
+        }
+    }
+
+    // Swaps the L elements starting at A with the L elements starting at B. The ranges may overlap,
+    // in which case the swap is performed element by element from the front.
+    pub fn swap_range(&mut self, l: usize, a: usize, b: usize) {
+        if a + l <= b || b + l <= a {
+            let x = self.decode(a..(a + l));
+            let y = self.decode(b..(b + l));
+            self.write(a, &y);
+            self.write(b, &x);
+        } else {
+            for i in 0..l {
+                self.swap(a + i, b + i);
+            }
+        }
+    }
+
+    // Inserts X at position P, moving the elements from P onwards along by one.
+    pub fn insert(&mut self, p: usize, x: T) {
+        if p == self.len() {
+            let end = p + 1;
+            push_run(&mut self.values, &mut self.ends, x, end);
+            return;
+        }
+        // The run holding P grows by one, and then X is written over its new element.
+        let k = self.run_of(p);
+        for e in self.ends[k..].iter_mut() {
+            *e += 1;
+        }
+        self.write(p, &[x]);
+    }
+
+    // Removes the element at position P, moving the elements after it back by one.
+    pub fn remove(&mut self, p: usize) {
+        let k = self.run_of(p);
+        for e in self.ends[k..].iter_mut() {
+            *e -= 1;
+        }
+        if self.run_start(k) == self.ends[k] {
+            self.values.remove(k);
+            self.ends.remove(k);
+            // The runs either side of the one removed merge if they hold the same value.
+            if k > 0 && k < self.runs() && self.values[k - 1] == self.values[k] {
+                self.values.remove(k - 1);
+                self.ends.remove(k - 1);
+            }
+        }
+    }
+
+    // The number of bytes the runs take up.
+    pub fn size(&self) -> usize {
+        self.values.len() * mem::size_of::<T>() + self.ends.len() * mem::size_of::<usize>()
+    }
+
+    // The parts of the column, for storing it.
+    pub fn parts(&self) -> (&[T], &[usize]) {
+        (&self.values, &self.ends)
+    }
+
+    pub fn from_parts(values: Vec<T>, ends: Vec<usize>) -> RunLengthColumn<T> {
+        RunLengthColumn { values, ends }
+    }
+}
+
+pub struct RunIter<'a, T: Element + 'a> {
+    column: &'a RunLengthColumn<T>,
+    run: usize,
+    r: Range<usize>,
+}
+
+impl<'a, T: Element> Iterator for RunIter<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        let p = self.r.next()?;
+        while self.column.ends[self.run] <= p {
+            self.run += 1;
+        }
+        Some(self.column.values[self.run])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.r.size_hint()
+    }
+}
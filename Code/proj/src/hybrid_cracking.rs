@@ -0,0 +1,630 @@
+// Hybrid cracking
+// No compression
+
+// uses map! macro.
+
+// The hybrids of cracking and adaptive merging. The cracker column starts out split into initial
+// runs of a fixed size. Each query moves the values it asks for out of every run and into the final
+// partition, so that the runs shrink away as the queries cover the domain. The mode decides how the
+// runs and the final partition are organised, which places the table between plain cracking (no
+// upfront work, slow to converge) and sorting the column before the first query.
+
+use cracker_index::CrackerIndex;
+use column::Column;
+use column::Element;
+use column::IntCol;
+use column::Values;
+use column::half_open;
+use column::in_half_open;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
+use cracked_table::owned_column_names;
+
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem;
+use std::ops::Range;
+use std::slice::Iter;
+
+pub const DEFAULT_RUN_SIZE: usize = 4096;
+
+// The number of bits of each value used to cluster an initial run in the radix mode.
+const RADIX_BITS: u32 = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HybridMode {
+    // Runs are cracked on the query bounds and the final partition is cracked too (HCC).
+    CrackCrack,
+    // Runs are cracked on the query bounds and the values moved into the final partition are sorted,
+    // so that it can be searched rather than cracked (HCS).
+    CrackSort,
+    // Runs are radix clustered when they are made and then cracked on the query bounds, and the final
+    // partition is sorted (HRS).
+    RadixSort,
+}
+
+// A cracker column's final partition, runs and merged intervals.
+pub type CrkState = (IntCol, Vec<IntCol>, Vec<(i64, Option<i64>)>);
+
+#[derive(Clone)]
+pub struct HybridTable {
+    pub count: usize,
+    pub crk_col_name: String,
+    // The final partition, holding the values moved out of the runs.
+    pub crk_col: IntCol,
+    // Cracker columns on other columns, with their runs and merged intervals, parked by the name of the
+    // column they crack until use_crk_col switches to them.
+    pub crk_cols: HashMap<String, CrkState>,
+    // The convergence threshold and minimum piece size do not apply. The stochastic mode is only used
+    // on the final partition of the crack-crack mode, as the sorted modes never crack it.
+    pub settings: CrackSettings,
+    // Base indices of the deleted rows. Their values stay in the base columns, so that base indices
+    // remain valid.
+    pub deleted: HashSet<usize>,
+    pub columns: HashMap<String, IntCol>,
+    pub mode: HybridMode,
+    pub run_size: usize,
+    // The initial runs, each with a cracker index of its own.
+    pub runs: Vec<IntCol>,
+    // The disjoint half-open intervals of values which have been moved to the final partition, in order.
+    pub merged: Vec<(i64, Option<i64>)>,
+}
+
+impl HybridTable {
+    pub fn new() -> HybridTable {
+        HybridTable::with_mode(HybridMode::CrackCrack, DEFAULT_RUN_SIZE)
+    }
+
+    pub fn with_mode(mode: HybridMode, run_size: usize) -> HybridTable {
+        if run_size == 0 {
+            panic!("with_mode: runs must hold at least one element");
+        }
+        HybridTable {
+            count: 0,
+            crk_col_name: "".to_string(),
+            crk_col: IntCol::empty(),
+            crk_cols: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
+            columns: HashMap::new(),
+            mode,
+            run_size,
+            runs: Vec::new(),
+            merged: Vec::new(),
+        }
+    }
+
+    pub fn new_columns(&mut self, col_names: Vec<String>) {
+        for col in col_names {
+            self.columns.insert(col, IntCol::empty());
+        }
+    }
+
+    pub fn set_crk_col(&mut self, col_name: String) {
+        // The current cracker column is parked, so that use_crk_col can switch back to it.
+        let current = self.take_crk_state();
+        if !self.crk_col_name.is_empty() && self.crk_col_name != col_name {
+            self.crk_cols.insert(self.crk_col_name.clone(), current);
+        }
+        self.crk_cols.remove(&col_name);
+        self.crk_col.crk_idx = self.settings.index_kind.new_index();
+        self.crk_col_name = col_name.clone();
+
+        match self.columns.get(&col_name) {
+            Some(c) => self.crk_col.v = c.v.clone(),
+            None => panic!("set_crk_col: no such col"),
+        };
+        let rows = (0..self.count).filter(|i| !self.deleted.contains(i)).collect();
+        self.add_runs(rows);
+    }
+
+    // Makes the cracker column on COL_NAME the one which queries crack, parking the current one. The
+    // cracker column is built if the table has none on COL_NAME yet.
+    pub fn use_crk_col(&mut self, col_name: &str) {
+        if self.crk_col_name == col_name {
+            return;
+        }
+        match self.crk_cols.remove(col_name) {
+            Some((crk_col, runs, merged)) => {
+                let current = self.take_crk_state();
+                self.crk_col = crk_col;
+                self.runs = runs;
+                self.merged = merged;
+                let current_name = mem::replace(&mut self.crk_col_name, col_name.to_string());
+                self.crk_cols.insert(current_name, current);
+            },
+            None => self.set_crk_col(col_name.to_string()),
+        }
+    }
+
+    // Takes the cracker column, its runs and its merged intervals out of the table.
+    fn take_crk_state(&mut self) -> CrkState {
+        (mem::replace(&mut self.crk_col, IntCol::empty()), mem::take(&mut self.runs), mem::take(&mut self.merged))
+    }
+
+    // Calls F with each parked cracker column in turn switched in, then switches back to the current one.
+    fn for_each_parked<F: FnMut(&mut HybridTable)>(&mut self, mut f: F) {
+        let current_name = self.crk_col_name.clone();
+        let names: Vec<String> = self.crk_cols.keys().cloned().collect();
+        for name in names {
+            self.use_crk_col(&name);
+            f(self);
+        }
+        self.use_crk_col(&current_name);
+    }
+
+    // Splits the rows at base indices ROWS into new initial runs.
+    fn add_runs(&mut self, rows: Vec<usize>) {
+        for chunk in rows.chunks(self.run_size) {
+            let mut run = IntCol::empty();
+            run.crk_idx  = self.settings.index_kind.new_index();
+            run.crk      = chunk.iter().map(|&i| self.crk_col.v[i]).collect();
+            run.base_idx = chunk.to_vec();
+            if self.mode == HybridMode::RadixSort {
+                radix_cluster(&mut run);
+            }
+            self.runs.push(run);
+        }
+    }
+
+    // TODO: Improve exception handling in this function
+    pub fn insert(&mut self, new_values: &mut HashMap<String, Vec<i64>>) {
+        let old_count = self.count;
+        let mut n_new_tuples = 0;
+        for (key, val) in self.columns.iter_mut() {
+            let new_elements = new_values.get_mut(key).unwrap();
+            let n = new_elements.len();
+            if n_new_tuples == 0 || n_new_tuples == n {
+                val.v.append(new_elements);
+                n_new_tuples = n;
+            } else {
+                panic!("insert: new_values has vectors of differing lengths");
+            }
+        }
+        self.count += n_new_tuples;
+
+        if !self.crk_col_name.is_empty() {
+            let rows = old_count..self.count;
+            self.add_rows(rows.clone());
+            self.for_each_parked(|t| t.add_rows(rows.clone()));
+        }
+    }
+
+    // Adds the rows at base indices ROWS to the cracker column. Rows whose values have already been
+    // merged go straight into the final partition, and the rest make new runs.
+    fn add_rows(&mut self, rows: Range<usize>) {
+        let mut new_rows = Vec::new();
+        for i in rows {
+            let value = self.columns[&self.crk_col_name].v[i];
+            self.crk_col.v.push(value);
+            if self.is_merged(value) {
+                self.insert_final(i);
+            } else {
+                new_rows.push(i);
+            }
+        }
+        self.add_runs(new_rows);
+    }
+
+    // Deletes the rows at the given base indices, so that no query returns them.
+    pub fn delete_indices(&mut self, indices: Iter<usize>) {
+        for &i in indices {
+            if i >= self.count {
+                panic!("delete_indices: no row at base index {}", i);
+            }
+            if self.deleted.insert(i) && !self.crk_col_name.is_empty() {
+                self.remove_row(i);
+                self.for_each_parked(|t| t.remove_row(i));
+            }
+        }
+    }
+
+    fn is_merged(&self, value: i64) -> bool {
+        self.merged.iter().any(|&(l, h)| in_half_open(value, l, h))
+    }
+
+    // Puts the row at base index I into the final partition, next to the other elements of its value.
+    fn insert_final(&mut self, i: usize) {
+        let value = self.crk_col.v[i];
+        let p = match self.mode {
+            HybridMode::CrackCrack => value.successor().and_then(|v| self.crk_col.crk_idx.upper_bound(&v)).unwrap_or(self.crk_col.crk.len()),
+            _ => self.crk_col.crk.partition_point(|&c| c <= value),
+        };
+        self.crk_col.crk.insert(p, value);
+        self.crk_col.base_idx.insert(p, i);
+        self.crk_col.crk_idx.add_where_greater_than(value, 1);
+    }
+
+    // Takes the row at base index I out of whichever run or final partition holds it.
+    fn remove_row(&mut self, i: usize) {
+        let value = self.crk_col.v[i];
+        let in_final = self.is_merged(value);
+        let col = if in_final {
+            &mut self.crk_col
+        } else {
+            match self.runs.iter_mut().find(|r| r.base_idx[piece(r, value)].contains(&i)) {
+                Some(r) => r,
+                None => panic!("remove_row: row {} is in no run", i),
+            }
+        };
+        let r = if in_final && self.mode != HybridMode::CrackCrack {
+            col.crk.partition_point(|&c| c < value)..col.crk.partition_point(|&c| c <= value)
+        } else {
+            piece(col, value)
+        };
+        let p = match col.base_idx[r.clone()].iter().position(|&j| j == i) {
+            Some(o) => r.start + o,
+            None => panic!("remove_row: row {} is not in its piece", i),
+        };
+        col.crk.remove(p);
+        col.base_idx.remove(p);
+        col.crk_idx.subtract_where_greater_than(value, 1);
+    }
+
+    pub fn get_col(&self, col: String) -> Option<&IntCol> {
+        self.columns.get(&col)
+    }
+
+    pub fn get_indices(&self, indices: Iter<usize>) -> HybridTable {
+        let mut t = HybridTable::with_mode(self.mode, self.run_size);
+        for (name, col) in &self.columns {
+            let mut c_buffer = IntCol::empty();
+            c_buffer.v = indices.clone().map(|&i| col.v[i]).collect();
+            t.columns.insert(name.clone(), c_buffer);
+        }
+        t.count = indices.len();
+        t.settings = self.settings.clone();
+        if !self.crk_col_name.is_empty() {
+            t.set_crk_col(self.crk_col_name.clone());
+        }
+        t
+    }
+
+    pub fn get_values(&self, indices: Iter<usize>, col: &str) -> Vec<i64> {
+        let mut buf = Vec::new();
+        for &i in indices {
+            buf.push(self.columns[&col.to_string()].v[i]);
+        }
+        buf
+    }
+
+    pub fn rearrange(&mut self, indices: Iter<usize>) {
+        for col in self.columns.values_mut() {
+            col.rearrange(indices.clone());
+        }
+        self.crk_col.rearrange(indices.clone());
+        self.runs.clear();
+        self.merged.clear();
+        for c in self.crk_cols.values_mut() {
+            c.0.rearrange(indices.clone());
+            c.1.clear();
+            c.2.clear();
+        }
+    }
+
+    // Moves every value in [L, H) which is still in the runs into the final partition. An H of None
+    // leaves the range open above.
+    fn merge_range(&mut self, l: i64, h: Option<i64>) {
+        let mut gaps = Vec::new();
+        let mut start = Some(l);
+        for &(m_l, m_h) in &self.merged {
+            let s = match start {
+                Some(s) => s,
+                None => break,
+            };
+            if m_h.is_some_and(|m_h| m_h <= s) {
+                continue;
+            }
+            if h.is_some_and(|h| m_l >= h) {
+                break;
+            }
+            if m_l > s {
+                gaps.push((s, Some(m_l)));
+            }
+            start = m_h.map(|m_h| cmp::max(s, m_h));
+        }
+        if let Some(s) = start {
+            if h.is_none_or(|h| s < h) {
+                gaps.push((s, h));
+            }
+        }
+        if gaps.is_empty() {
+            return;
+        }
+        for (g_l, g_h) in gaps {
+            self.merge_gap(g_l, g_h);
+        }
+
+        // Record [L, H) as merged, joining it to the intervals it overlaps or touches.
+        self.merged.push((l, h));
+        self.merged.sort_by_key(|m| m.0);
+        let mut merged: Vec<(i64, Option<i64>)> = Vec::with_capacity(self.merged.len());
+        for &(m_l, m_h) in &self.merged {
+            match merged.last_mut() {
+                Some(last) if last.1.is_none_or(|e| m_l <= e) => last.1 = last.1.and_then(|e| m_h.map(|m_h| cmp::max(e, m_h))),
+                _ => merged.push((m_l, m_h)),
+            }
+        }
+        self.merged = merged;
+    }
+
+    // Moves the values in [L, H), none of which have been merged yet, from the runs into the final
+    // partition.
+    fn merge_gap(&mut self, l: i64, h: Option<i64>) {
+        let mut crk = Vec::new();
+        let mut base_idx = Vec::new();
+        for run in self.runs.iter_mut() {
+            let p_l = run.crack_in_two(l, self.settings.parallel_threshold);
+            let p_h = match h {
+                Some(h) => run.crack_in_two(h, self.settings.parallel_threshold),
+                None => run.crk.len(),
+            };
+            crk.extend(run.crk.drain(p_l..p_h));
+            base_idx.extend(run.base_idx.drain(p_l..p_h));
+            remove_keys_between(&mut *run.crk_idx, l, h);
+            run.crk_idx.subtract_where_greater_than(l, p_h - p_l);
+        }
+        self.runs.retain(|r| !r.crk.is_empty());
+
+        if self.mode != HybridMode::CrackCrack {
+            let mut rows: Vec<(i64, usize)> = crk.into_iter().zip(base_idx).collect();
+            rows.sort_by_key(|r| r.0);
+            crk = rows.iter().map(|r| r.0).collect();
+            base_idx = rows.iter().map(|r| r.1).collect();
+        }
+
+        let p = self.final_position(l);
+        let n = crk.len();
+        self.crk_col.crk.splice(p..p, crk);
+        self.crk_col.base_idx.splice(p..p, base_idx);
+        if self.mode == HybridMode::CrackCrack {
+            self.crk_col.crk_idx.add_where_greater_than(l, n);
+            if let Some(h) = h {
+                self.crk_col.crk_idx.insert(h, p + n);
+            }
+        }
+    }
+
+    // Returns the position of the first element of the final partition which is at least V, cracking
+    // on V in the crack-crack mode and searching for it otherwise.
+    fn final_position(&mut self, v: i64) -> usize {
+        match self.mode {
+            HybridMode::CrackCrack => self.crk_col.crack_in_two(v, self.settings.parallel_threshold),
+            _ => self.crk_col.crk.partition_point(|&c| c < v),
+        }
+    }
+
+    // Merges [L, H) into the final partition, returning the positions of its values there.
+    fn select_final(&mut self, l: i64, h: Option<i64>) -> (usize, usize) {
+        self.merge_range(l, h);
+        if self.mode == HybridMode::CrackCrack {
+            self.crk_col.stochastic_crack(l, &mut self.settings);
+            if let Some(h) = h {
+                self.crk_col.stochastic_crack(h, &mut self.settings);
+            }
+        }
+        let p_h = match h {
+            Some(h) => self.final_position(h),
+            None => self.crk_col.crk.len(),
+        };
+        (self.final_position(l), p_h)
+    }
+
+    // Merges X into the final partition, returning the positions of the elements equal to X.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        let (p_low, p_high) = self.select_final(x, x.successor());
+        p_low..p_high
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        let (p_low, p_high) = self.select_final(l, h);
+        p_low..p_high
+    }
+
+    // Returns the elements of COL at the positions R of the cracker column, borrowing them from the table.
+    pub fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        self.crk_col.values(r, col, &self.columns[col].v)
+    }
+
+    // Returns statistics on how far the cracker column has been cracked, over the final partition and
+    // the initial runs together.
+    pub fn stats(&self) -> CrackStats {
+        let mut cols = vec![&self.crk_col];
+        cols.extend(self.runs.iter());
+        let pieces = cols.iter().flat_map(|c| c.pieces().into_iter().map(move |r| {
+            let piece = &c.crk[r];
+            (piece.len(), piece.iter().all(|&x| x == piece[0]))
+        }));
+        let depth = cols.iter().map(|c| c.crk_idx.depth()).max().unwrap_or(0);
+        CrackStats::new(pieces, depth)
+    }
+
+}
+
+// Returns the positions of the piece of COL which holds the value V.
+fn piece(col: &IntCol, v: i64) -> ::std::ops::Range<usize> {
+    col.crk_idx.lower_bound(&v).unwrap_or(0)..v.successor().and_then(|v| col.crk_idx.upper_bound(&v)).unwrap_or(col.crk.len())
+}
+
+// Removes the keys strictly between L and H from IDX.
+fn remove_keys_between(idx: &mut dyn CrackerIndex, l: i64, h: Option<i64>) {
+    for (k, _) in idx.pairs() {
+        if l < k && h.is_none_or(|h| k < h) {
+            idx.delete(k);
+        }
+    }
+}
+
+// Clusters the run on the top RADIX_BITS bits of each value's offset from the smallest, cracking it
+// at each cluster boundary. Offsets are taken unsigned, as they may not fit in an i64.
+fn radix_cluster(run: &mut IntCol) {
+    let (min, max) = match (run.crk.iter().min(), run.crk.iter().max()) {
+        (Some(&min), Some(&max)) if min < max => (min, max),
+        _ => return,
+    };
+    let bits = 64 - (max.wrapping_sub(min) as u64).leading_zeros();
+    let shift = bits.saturating_sub(RADIX_BITS);
+    let cluster = |c: i64| (c.wrapping_sub(min) as u64 >> shift) as usize;
+
+    let mut starts = vec![0; (1 << (bits - shift)) + 1];
+    for &c in &run.crk {
+        starts[cluster(c) + 1] += 1;
+    }
+    for k in 1..starts.len() {
+        starts[k] += starts[k - 1];
+    }
+
+    let mut crk = vec![0; run.crk.len()];
+    let mut base_idx = vec![0; run.crk.len()];
+    let mut next = starts.clone();
+    for (&c, &i) in run.crk.iter().zip(&run.base_idx) {
+        let p = &mut next[cluster(c)];
+        crk[*p] = c;
+        base_idx[*p] = i;
+        *p += 1;
+    }
+    run.crk = crk;
+    run.base_idx = base_idx;
+    for (k, &p) in starts.iter().enumerate().take(starts.len() - 1).skip(1) {
+        run.crk_idx.insert(min.wrapping_add(((k as u64) << shift) as i64), p);
+    }
+}
+
+impl Default for HybridTable {
+    fn default() -> HybridTable {
+        HybridTable::new()
+    }
+}
+
+impl CrackedTable for HybridTable {
+    fn new() -> HybridTable {
+        HybridTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        HybridTable::new_columns(self, col_names.iter().map(|c| c.to_string()).collect())
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        HybridTable::insert(self, &mut owned_column_names(new_values))
+    }
+
+    fn delete_indices(&mut self, indices: Iter<usize>) {
+        HybridTable::delete_indices(self, indices)
+    }
+
+    fn use_crk_col(&mut self, col_name: &str) {
+        HybridTable::use_crk_col(self, col_name)
+    }
+
+    // The final partition of the sorted modes is sorted already, and that of the cracking modes is
+    // merged into from the runs, so the table has no sorted form to converge to.
+    fn set_convergence_threshold(&mut self, _threshold: usize) {}
+
+    // Queries on the runs of the cracking modes move elements into the final partition, so its pieces
+    // are always cracked.
+    fn set_min_piece_size(&mut self, _size: usize) {}
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    // The runs of every cracker column are cracked too, so they count as cracker columns of their own.
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        let mut cols: Vec<&mut IntCol> = Vec::new();
+        for &mut (ref mut crk_col, ref mut runs, _) in self.crk_cols.values_mut() {
+            cols.push(crk_col);
+            cols.extend(runs.iter_mut());
+        }
+        cols.push(&mut self.crk_col);
+        cols.extend(self.runs.iter_mut());
+        cols
+    }
+
+    fn stats(&self) -> CrackStats {
+        HybridTable::stats(self)
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        HybridTable::set_crk_col(self, col_name.to_string())
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        HybridTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        HybridTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        HybridTable::values(self, r, col)
+    }
+
+    fn scan_piece(&mut self, _l: i64, _h: Option<i64>) -> Option<Range<usize>> {
+        None
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        self.crk_col.filtered_values(r, l, h, &self.columns[col].v)
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(HybridTable::get_col(self, col.to_string()).unwrap_or_else(|| panic!("get_col: No column called {}", col)).v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> HybridTable {
+        HybridTable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        HybridTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
+    }
+
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_col.crk_idx.pairs()
+    }
+}
+
+// Returns an adjacency list built from the two vectors of adjacent nodes, organised by MODE.
+pub fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str, mode: HybridMode) -> HybridTable {
+    let mut adjacency_list = HybridTable::with_mode(mode, DEFAULT_RUN_SIZE);
+    adjacency_list.new_columns(vec!["src".to_string(), "dst".to_string()]);
+    adjacency_list.insert(&mut map!{"src".to_string() => src_node, "dst".to_string() => dst_node});
+    adjacency_list.set_crk_col(crk.to_string());
+    adjacency_list
+}
@@ -5,9 +5,20 @@ extern crate bit_vec;
 pub mod utils;
 
 pub mod cracker_index;
+pub mod encoding;
 pub mod column;
-pub mod overswap_rle_compression;
+pub mod cracked_table;
+pub mod decomposed_cracking;
+pub mod nary_cracking;
+pub mod recognitive_compression;
 pub mod compactive_compression;
+pub mod underswap_rle_compression;
+pub mod overswap_rle_compression;
+pub mod hybrid_cracking;
+pub mod concurrent_cracking;
+pub mod adaptive_compression;
+pub mod packed_cracking;
+pub mod persist;
 
 #[cfg(test)]
 pub mod test;
\ No newline at end of file
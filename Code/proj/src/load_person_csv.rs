@@ -41,16 +41,13 @@ pub fn edges(social_network: String) -> String {
 
 // NODES/PEOPLE
 
+// Fields are matched to the CSV's header by name, so the columns which are never read are left out.
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct RawPerson {
     id: i64,
-    firstName: String,
-    lastName: String,
-    gender: String,
-    birthday: String,
-    creationDate: String,
-    locationIP: String,
-    browserUsed: String,
+    first_name: String,
+    last_name: String,
 }
 
 #[derive(Debug)]
@@ -60,7 +57,7 @@ pub struct Person {
     pub last_name: String,
 }
 
-fn read_people(file_path: String) -> Result<Vec<Person>, Box<Error>> {
+fn read_people(file_path: String) -> Result<Vec<Person>, Box<dyn Error>> {
     let file = File::open(file_path)?;
     let mut rdr = csv::ReaderBuilder::new()
         .delimiter(b'|')
@@ -70,7 +67,7 @@ fn read_people(file_path: String) -> Result<Vec<Person>, Box<Error>> {
         // Notice that we need to provide a type hint for automatic
         // deserialization.
         let raw_person: RawPerson = result?;
-        let person = Person { id: raw_person.id, first_name: raw_person.firstName, last_name: raw_person.lastName};
+        let person = Person { id: raw_person.id, first_name: raw_person.first_name, last_name: raw_person.last_name};
         people.push(person);
     }
     Ok(people)
@@ -89,7 +86,7 @@ pub fn read_nodes(file_path: String) -> Vec<Person> {
 struct RawFriendship {
     p1id: i64,
     p2id: i64,
-    creationDate: String,
+    _creation_date: String,
 }
 
 pub struct Friendship {
@@ -97,7 +94,7 @@ pub struct Friendship {
     pub p2id: i64,
 }
 
-fn read_friendships(file_path: String) -> Result<Vec<Friendship>, Box<Error>> {
+fn read_friendships(file_path: String) -> Result<Vec<Friendship>, Box<dyn Error>> {
     let file = File::open(file_path)?;
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
@@ -115,7 +112,7 @@ fn read_friendships(file_path: String) -> Result<Vec<Friendship>, Box<Error>> {
     Ok(friendships)
 }
 
-fn read_friendships_adjl(file_path: String) -> Result<(Vec<i64>, Vec<i64>), Box<Error>> {
+fn read_friendships_adjl(file_path: String) -> Result<(Vec<i64>, Vec<i64>), Box<dyn Error>> {
     let file = File::open(file_path)?;
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
@@ -188,8 +185,8 @@ pub fn sf10_edges_adjl() -> (Vec<i64>, Vec<i64>) {
 
 pub fn test() {
     let people = sf1_nodes();
-    for i in 0..10 {
-        println!("{:?}", people[i]);
+    for person in people.iter().take(10) {
+        println!("{:?}", person);
     }
     println!("...");
     println!("Successfully read {} people", people.len());
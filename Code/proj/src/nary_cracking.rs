@@ -0,0 +1,445 @@
+// Decomposed cracking
+// No compression, n-ary cracking
+
+// uses map! macro.
+
+use column::Column;
+use column::Element;
+use column::IntCol;
+use column::Values;
+use column::half_open;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
+use cracked_table::owned_column_names;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::slice::Iter;
+use std::ops::Range;
+
+#[derive(Clone)]
+pub struct NaryCrackedTable {
+    pub count: usize,
+    pub crk_col_name: String,
+    pub crk_col: IntCol,
+    // Cracker columns on other columns, parked by the name of the column they crack until use_crk_col
+    // switches to them.
+    pub crk_cols: HashMap<String, IntCol>,
+    pub settings: CrackSettings,
+    // Base indices of the deleted rows. Their values stay in the base columns, so that base indices
+    // remain valid.
+    pub deleted: HashSet<usize>,
+    pub columns: HashMap<String, IntCol>,
+}
+
+impl NaryCrackedTable {
+    pub fn new() -> NaryCrackedTable {
+        NaryCrackedTable {
+            count: 0,
+            crk_col_name: "".to_string(),
+            crk_col: IntCol::empty(),
+            crk_cols: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
+            columns: HashMap::new()
+        }
+    }
+
+    pub fn new_columns(&mut self, col_names: Vec<String>) {
+        for col in col_names {
+            self.columns.insert(col, IntCol::empty());
+        }
+    }
+
+    pub fn set_crk_col(&mut self, col_name: String) {
+        // The current cracker column is parked, so that use_crk_col can switch back to it.
+        if self.crk_col_name != col_name {
+            self.crk_col.park(&self.crk_col_name, &mut self.crk_cols);
+        }
+        self.crk_cols.remove(&col_name);
+        self.crk_col = match self.columns.get(&col_name) {
+            Some(c) => IntCol::cracking(&c.v, &self.deleted, self.settings.index_kind),
+            None => panic!("set_crk_col: no such col"),
+        };
+        self.crk_col_name = col_name;
+    }
+
+    // Makes the cracker column on COL_NAME the one which queries crack, parking the current one. The
+    // cracker column is built if the table has none on COL_NAME yet.
+    pub fn use_crk_col(&mut self, col_name: &str) {
+        if !self.crk_col.unpark(&mut self.crk_col_name, col_name, &mut self.crk_cols) {
+            self.set_crk_col(col_name.to_string());
+        }
+    }
+
+    // TODO: Improve exception handling in this function
+    pub fn insert(&mut self, new_values: &mut HashMap<String, Vec<i64>>) {
+        let old_count = self.count;
+        let mut n_new_tuples = 0;
+        for (key, val) in self.columns.iter_mut() {
+            let new_elements = new_values.get_mut(key).unwrap();
+            let n = new_elements.len();
+            if n_new_tuples == 0 || n_new_tuples == n {
+                val.v.append(new_elements);
+                n_new_tuples = n;
+            } else {
+                panic!("insert: new_values has vectors of differing lengths");
+            }
+        }
+        self.count += n_new_tuples;
+
+        // Rows inserted after the cracker column is set are merged into it by the queries which need them
+        if !self.crk_col_name.is_empty() {
+            self.crk_col.add_pending(&self.columns[&self.crk_col_name].v, old_count..self.count);
+        }
+        for (name, c) in self.crk_cols.iter_mut() {
+            c.add_pending(&self.columns[name].v, old_count..self.count);
+        }
+    }
+
+    pub fn get_col(&self, col: String) -> Option<&IntCol> {
+        self.columns.get(&col)
+    }
+
+    pub fn get_indices(&self, indices: Iter<usize>) -> NaryCrackedTable {
+        let mut selection: HashMap<String, IntCol> = HashMap::new();
+        for (name, col) in &self.columns {
+            let mut v_buffer = Vec::with_capacity(indices.len());
+            for &i in indices.clone() {
+                v_buffer.push(col.v[i]);
+            }
+            let mut c_buffer = IntCol::empty();
+            c_buffer.v = v_buffer;
+            selection.insert(name.clone(), c_buffer);
+        }
+
+        let mut t = NaryCrackedTable::new();
+        t.columns = selection;
+        t.count = indices.len();
+        t.settings = self.settings.clone();
+
+        let mut indexed_crk_v = Vec::with_capacity(indices.len());
+        if !self.crk_col.crk.is_empty() {
+            let mut indexed_crk_col = Vec::with_capacity(indices.len());
+            for &i in indices.clone() {
+                indexed_crk_col.push(self.crk_col.crk[i]);
+                indexed_crk_v.push(self.crk_col.v[i]);
+            }
+            t.crk_col.crk = indexed_crk_col;
+            t.crk_col.crk_idx = self.settings.index_kind.new_index();
+        } else {
+            for &i in indices.clone() {
+                indexed_crk_v.push(self.crk_col.v[i]);
+            }
+        }
+        t.crk_col.v = indexed_crk_v;
+        t
+    }
+
+    pub fn get_values(&self, indices: Iter<usize>, col: &str) -> Vec<i64> {
+        let mut buf = Vec::new();
+        for &i in indices {
+            buf.push(self.columns[&col.to_string()].v[i]);
+        }
+        buf
+    }
+
+    pub fn rearrange(&mut self, indices: Iter<usize>) {
+        for col in self.columns.values_mut() {
+            col.rearrange(indices.clone());
+        }
+        self.crk_col.rearrange(indices.clone());
+        for c in self.crk_cols.values_mut() {
+            c.rearrange(indices.clone());
+        }
+    }
+
+    // Partitions the piece from START up to (but excluding) END around every one of the sorted PIVOTS
+    // in a single pass, in the manner of an American flag sort. Returns, for each pivot, the position
+    // from which the elements are at least that pivot.
+    fn partition_around(&mut self, start: usize, end: usize, pivots: &[i64]) -> Vec<usize> {
+        let n_buckets = pivots.len() + 1;
+        let bucket_of = |c: i64| match pivots.binary_search(&c) {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        };
+
+        let mut counts = vec![0; n_buckets];
+        for i in start..end {
+            counts[bucket_of(self.crk_col.crk[i])] += 1;
+        }
+
+        let mut bucket_starts = Vec::with_capacity(n_buckets);
+        let mut bucket_ends = Vec::with_capacity(n_buckets);
+        let mut p = start;
+        for &count in counts.iter() {
+            bucket_starts.push(p);
+            p += count;
+            bucket_ends.push(p);
+        }
+
+        // Place every element by swapping it directly into the next free slot of its bucket.
+        let mut next = bucket_starts.clone();
+        for b in 0..n_buckets {
+            while next[b] < bucket_ends[b] {
+                let target = bucket_of(self.crk_col.crk[next[b]]);
+                if target == b {
+                    next[b] += 1;
+                } else {
+                    self.crk_col.crk.swap(next[b], next[target]);
+                    self.crk_col.base_idx.swap(next[b], next[target]);
+                    next[target] += 1;
+                }
+            }
+        }
+
+        bucket_starts.split_off(1)
+    }
+
+    // Cracks the column on every value in BOUNDARIES, so that each becomes a key of the cracker index.
+    // Boundaries which fall into the same piece are all cracked on in one pass over that piece.
+    pub fn crack_at(&mut self, mut boundaries: Vec<i64>) {
+        boundaries.sort();
+        boundaries.dedup();
+
+        let mut i = 0;
+        while i < boundaries.len() {
+            let b = boundaries[i];
+            if self.crk_col.crk_idx.contains(b) {
+                i += 1;
+                continue;
+            }
+            let piece_start = self.crk_col.crk_idx.lower_bound(&b);
+            let piece_end = self.crk_col.crk_idx.upper_bound(&b);
+
+            // Gather the following boundaries which lie in the same piece.
+            let mut j = i + 1;
+            while j < boundaries.len() {
+                let c = boundaries[j];
+                let same_piece = !self.crk_col.crk_idx.contains(c)
+                    && self.crk_col.crk_idx.lower_bound(&c) == piece_start
+                    && self.crk_col.crk_idx.upper_bound(&c) == piece_end;
+                if !same_piece {
+                    break;
+                }
+                j += 1;
+            }
+
+            let positions = self.partition_around(piece_start.unwrap_or(0), piece_end.unwrap_or(self.crk_col.crk.len()), &boundaries[i..j]);
+            for k in i..j {
+                self.crk_col.crk_idx.insert(boundaries[k], positions[k - i]);
+            }
+            i = j;
+        }
+    }
+
+    // Returns, for each X in XS, the elements of T where the cracker column's value equals X. Every
+    // piece touched by the batch is cracked only once.
+    pub fn cracker_select_batch(&mut self, xs: &Vec<i64>, col: &str) -> Vec<Vec<i64>> {
+        let mut boundaries = Vec::with_capacity(2 * xs.len());
+        for &x in xs {
+            boundaries.push(x);
+            boundaries.extend(x.successor());
+        }
+        for &x in xs {
+            self.crk_col.merge_pending(x, x.successor(), &self.columns);
+        }
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if !self.crk_col.sorted {
+            self.crack_at(boundaries);
+        }
+
+        let mut results = Vec::with_capacity(xs.len());
+        for &x in xs {
+            let r = if self.crk_col.sorted {
+                self.crk_col.sorted_range(x, x.successor())
+            } else {
+                self.crk_col.crk_idx.get(x).unwrap()..self.cracked_end(x.successor())
+            };
+            results.push(self.get_values(self.crk_col.base_idx[r].iter(), col));
+        }
+        results
+    }
+
+    // Cracks the column on X and X + 1, returning the positions of the elements equal to X.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        let x1 = match x.successor() {
+            Some(x1) => x1,
+            // Nothing lies above i64::MAX, so its elements are those from it onwards.
+            None => return self.crack_select_range(x, x, true, true),
+        };
+        self.crk_col.merge_pending(x, Some(x1), &self.columns);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(x, Some(x1));
+        }
+        self.crk_col.stochastic_crack(x, &mut self.settings);
+        self.crack_at(vec![x, x1]);
+        let p_low = self.crk_col.crk_idx.get(x).unwrap();
+        let p_high = self.crk_col.crk_idx.get(x1).unwrap();
+        p_low..p_high
+    }
+
+    // Returns the position cracked on H, or the end of the cracker column for an H of None.
+    fn cracked_end(&self, h: Option<i64>) -> usize {
+        h.map_or(self.crk_col.crk.len(), |h| self.crk_col.crk_idx.get(h).unwrap())
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        self.crk_col.merge_pending(l, h, &self.columns);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(l, h);
+        }
+        self.crk_col.stochastic_crack(l, &mut self.settings);
+        if let Some(h) = h {
+            self.crk_col.stochastic_crack(h, &mut self.settings);
+        }
+        self.crack_at(Some(l).into_iter().chain(h).collect());
+        let p_low = self.crk_col.crk_idx.get(l).unwrap();
+        p_low..self.cracked_end(h)
+    }
+
+    // Returns the elements of COL at the positions R of the cracker column, borrowing them from the table.
+    pub fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        self.crk_col.values(r, col, &self.columns[col].v)
+    }
+
+    // Returns the positions of the pieces holding the elements in [L, H) if they are small enough to be
+    // scanned rather than cracked.
+    pub fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        self.crk_col.merge_pending(l, h, &self.columns);
+        self.crk_col.small_piece(l, h, self.settings.min_piece_size)
+    }
+
+    // Returns the elements of COL at the positions R whose cracker column values lie in [L, H).
+    pub fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        self.crk_col.filtered_values(r, l, h, &self.columns[col].v)
+    }
+
+    // Returns statistics on how far the cracker column has been cracked.
+    pub fn stats(&self) -> CrackStats {
+        self.crk_col.stats()
+    }
+
+}
+
+impl Default for NaryCrackedTable {
+    fn default() -> NaryCrackedTable {
+        NaryCrackedTable::new()
+    }
+}
+
+impl CrackedTable for NaryCrackedTable {
+    fn new() -> NaryCrackedTable {
+        NaryCrackedTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        NaryCrackedTable::new_columns(self, col_names.iter().map(|c| c.to_string()).collect())
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        NaryCrackedTable::insert(self, &mut owned_column_names(new_values))
+    }
+
+    fn use_crk_col(&mut self, col_name: &str) {
+        NaryCrackedTable::use_crk_col(self, col_name)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        let mut cols: Vec<&mut IntCol> = self.crk_cols.values_mut().collect();
+        cols.push(&mut self.crk_col);
+        cols
+    }
+
+    fn stats(&self) -> CrackStats {
+        NaryCrackedTable::stats(self)
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        NaryCrackedTable::set_crk_col(self, col_name.to_string())
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        NaryCrackedTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        NaryCrackedTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        NaryCrackedTable::values(self, r, col)
+    }
+
+    fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        NaryCrackedTable::scan_piece(self, l, h)
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        NaryCrackedTable::filtered_values(self, r, l, h, col)
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(NaryCrackedTable::get_col(self, col.to_string()).unwrap_or_else(|| panic!("get_col: No column called {}", col)).v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> NaryCrackedTable {
+        NaryCrackedTable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        NaryCrackedTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
+    }
+
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_col.crk_idx.pairs()
+    }
+}
+
+// Returns an adjacency list built from the two vectors of adjacent nodes.
+pub fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str) -> NaryCrackedTable {
+    let mut adjacency_list = NaryCrackedTable::new();
+    adjacency_list.new_columns(vec!["src".to_string(), "dst".to_string()]);
+    adjacency_list.insert(&mut map!{"src".to_string() => src_node, "dst".to_string() => dst_node});
+    adjacency_list.set_crk_col(crk.to_string());
+    adjacency_list
+}
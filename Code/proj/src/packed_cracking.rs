@@ -0,0 +1,440 @@
+// Packed cracking
+// Bit-packed columns
+
+// uses map! macro.
+
+// A decomposed cracking table whose columns, cracker column and base index are bit-packed, each in
+// as few bits as hold the spread between its smallest and largest elements. The crack kernels read
+// and swap the packed elements in place, so that a graph whose node ids need 20 bits is cracked in
+// well under half the memory of a DeCrackedTable.
+
+use column::Element;
+use column::IntCol;
+use column::Values;
+use column::half_open;
+use column::in_half_open;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
+use cracked_table::owned_column_names;
+use cracker_index::CrackerIndex;
+use cracker_index::IndexKind;
+use encoding::BitPacked;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::slice::Iter;
+use std::ops::Range;
+
+pub struct PackedTable {
+    pub count: usize,
+    pub crk_col_name: String,
+    pub crk: BitPacked,
+    // Base indices are packed like any other column, in as few bits as hold the row count.
+    pub base_idx: BitPacked,
+    pub crk_idx: Box<dyn CrackerIndex>,
+    // The table cracks on the queried values alone, so of its settings only the index kind applies.
+    pub settings: CrackSettings,
+    pub deleted: HashSet<usize>,
+    pub columns: HashMap<String, BitPacked>,
+}
+
+impl PackedTable {
+    pub fn new() -> PackedTable {
+        PackedTable {
+            count: 0,
+            crk_col_name: "".to_string(),
+            crk: BitPacked::default(),
+            base_idx: BitPacked::default(),
+            crk_idx: IndexKind::AVL.new_index(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
+            columns: HashMap::new(),
+        }
+    }
+
+    pub fn new_columns(&mut self, col_names: Vec<String>) {
+        for col in col_names {
+            self.columns.insert(col, BitPacked::default());
+        }
+    }
+
+    pub fn set_crk_col(&mut self, col_name: String) {
+        let crk = match self.columns.get(&col_name) {
+            Some(c) => c.clone(),
+            None => panic!("set_crk_col: no such col"),
+        };
+        let base_idx: Vec<i64> = (0..self.count).filter(|i| !self.deleted.contains(i)).map(|i| i as i64).collect();
+        self.crk = BitPacked::pack(&base_idx.iter().map(|&i| crk.get(i as usize)).collect::<Vec<i64>>());
+        self.base_idx = BitPacked::pack(&base_idx);
+        self.crk_idx = self.settings.index_kind.new_index();
+        self.crk_col_name = col_name;
+    }
+
+    // Appends the given values to every column, repacking any column they do not fit. Rows inserted
+    // once the cracker column is set are merged into the pieces holding their values, so the cracks
+    // made so far are kept.
+    pub fn insert(&mut self, new_values: &mut HashMap<String, Vec<i64>>) {
+        let old_count = self.count;
+        let mut n_new_tuples = 0;
+        for (key, val) in self.columns.iter_mut() {
+            let new_elements = new_values.get_mut(key).unwrap();
+            let n = new_elements.len();
+            if n_new_tuples == 0 || n_new_tuples == n {
+                val.extend(new_elements);
+                n_new_tuples = n;
+            } else {
+                panic!("insert: new_values has vectors of differing lengths");
+            }
+        }
+        self.count += n_new_tuples;
+
+        if !self.crk_col_name.is_empty() {
+            let rows: Vec<i64> = (old_count as i64..self.count as i64).collect();
+            let values: Vec<i64> = rows.iter().map(|&i| self.columns[&self.crk_col_name].get(i as usize)).collect();
+            // Deleted rows have left the cracker column, so the new rows start at its end.
+            let first = self.crk.len();
+            self.crk.extend(&values);
+            self.base_idx.extend(&rows);
+            for (p, &x) in (first..self.crk.len()).zip(values.iter()) {
+                self.merge_row(p, x);
+            }
+        }
+    }
+
+    // Moves the element X at position P, just past the end of the merged part of the cracker column,
+    // to the end of the piece holding X. Rather than shifting everything after that piece along, the
+    // slot it needs is rippled up from P by moving one element across each later piece boundary, and
+    // each boundary key moves by one.
+    fn merge_row(&mut self, p: usize, x: i64) {
+        let mut hole = p;
+        for s in self.starts_after(x).into_iter().rev() {
+            if s < hole {
+                self.swap(s, hole);
+                hole = s;
+            }
+        }
+        self.crk_idx.add_where_greater_than(x, 1);
+    }
+
+    // Deletes the rows at the given base indices, taking each out of the cracker column straight away.
+    // The hole a row leaves is rippled to the end of the column by moving one element across each
+    // later piece boundary, and each boundary key moves down by one.
+    pub fn delete_indices(&mut self, indices: Iter<usize>) {
+        for &i in indices {
+            if i >= self.count {
+                panic!("delete_indices: no row at base index {}", i);
+            }
+            if !self.deleted.insert(i) || self.crk_col_name.is_empty() {
+                continue;
+            }
+            let x = self.columns[&self.crk_col_name].get(i);
+            let start = self.crk_idx.lower_bound(&x).unwrap_or(0);
+            let mut hole = (start..self.crk.len()).find(|&p| self.base_idx.get(p) == i as i64).unwrap();
+            for b in self.starts_after(x).into_iter().chain(Some(self.crk.len())) {
+                if b > hole {
+                    self.swap(hole, b - 1);
+                    hole = b - 1;
+                }
+            }
+            self.crk.pop();
+            self.base_idx.pop();
+            self.crk_idx.subtract_where_greater_than(x, 1);
+        }
+    }
+
+    // Returns the start of each piece after the one holding X, in order, leaving out empty pieces.
+    fn starts_after(&self, x: i64) -> Vec<usize> {
+        let mut starts: Vec<usize> = self.crk_idx.pairs().into_iter().filter(|&(k, _)| k > x).map(|(_, s)| s).collect();
+        starts.dedup();
+        starts
+    }
+
+    // Returns the positions of the pieces of the cracker column, in order.
+    pub fn pieces(&self) -> Vec<Range<usize>> {
+        let mut bounds: Vec<usize> = self.crk_idx.pairs().into_iter().map(|(_, p)| p).filter(|&p| p < self.crk.len()).collect();
+        bounds.push(0);
+        bounds.push(self.crk.len());
+        bounds.sort();
+        bounds.dedup();
+        bounds.windows(2).map(|w| w[0]..w[1]).collect()
+    }
+
+    // Returns statistics on how far the cracker column has been cracked.
+    pub fn stats(&self) -> CrackStats {
+        let pieces = self.pieces().into_iter().map(|r| {
+            let first = self.crk.get(r.start);
+            (r.len(), r.clone().all(|p| self.crk.get(p) == first))
+        });
+        CrackStats::new(pieces, self.crk_idx.depth())
+    }
+
+    // Returns the elements of COL at the given base indices.
+    pub fn get_values(&self, indices: Iter<usize>, col: &str) -> Vec<i64> {
+        let column = &self.columns[col];
+        indices.map(|&i| column.get(i)).collect()
+    }
+
+    // Returns the elements of COL at the positions R of the cracker column.
+    pub fn values(&self, r: Range<usize>, col: &str) -> Vec<i64> {
+        let column = &self.columns[col];
+        r.map(|p| column.get(self.base_idx.get(p) as usize)).collect()
+    }
+
+    // Swaps the elements at A and B, along with their base indices.
+    #[inline]
+    fn swap(&mut self, a: usize, b: usize) {
+        self.crk.swap(a, b);
+        self.base_idx.swap(a, b);
+    }
+
+    // Cracks the piece containing V in two, so that every element before the returned position is
+    // less than V and every element from it onwards is at least V. The position is stored against V
+    // in the cracker index.
+    pub fn crack_in_two(&mut self, v: i64) -> usize {
+        if let Some(p) = self.crk_idx.get(v) {
+            return p;
+        }
+        let mut p_low = self.crk_idx.lower_bound(&v).unwrap_or(0);
+        let mut p_high = self.crk_idx.upper_bound(&v).unwrap_or(self.crk.len());
+
+        while p_low < p_high {
+            if self.crk.get(p_low) < v {
+                p_low += 1;
+            } else if self.crk.get(p_high - 1) >= v {
+                p_high -= 1;
+            } else {
+                self.swap(p_low, p_high - 1);
+                p_low += 1;
+                p_high -= 1;
+            }
+        }
+
+        self.crk_idx.insert(v, p_low);
+        p_low
+    }
+
+    // Cracks a single piece into three around the half-open interval [L, H) in one pass. Both L and H
+    // must fall within the same piece. Returns the positions stored against L and H.
+    pub fn crack_in_three(&mut self, l: i64, h: i64) -> (usize, usize) {
+        let mut p_low = self.crk_idx.lower_bound(&l).unwrap_or(0);
+        let mut p_high = self.crk_idx.upper_bound(&h).unwrap_or(self.crk.len());
+
+        let mut p_itr = p_low;
+        while p_itr < p_high {
+            let c = self.crk.get(p_itr);
+            if c < l {
+                self.swap(p_low, p_itr);
+                p_low += 1;
+                p_itr += 1;
+            } else if c >= h {
+                p_high -= 1;
+                self.swap(p_itr, p_high);
+            } else {
+                p_itr += 1;
+            }
+        }
+
+        self.crk_idx.insert(l, p_low);
+        self.crk_idx.insert(h, p_high);
+        (p_low, p_high)
+    }
+
+    // Cracks the column so that the values in [L, H) are contiguous, returning their position range.
+    // An H of None needs no crack.
+    fn crack_range(&mut self, l: i64, h: Option<i64>) -> (usize, usize) {
+        let h = match h {
+            Some(h) => h,
+            None => return (self.crack_in_two(l), self.crk.len()),
+        };
+        let idx = &self.crk_idx;
+        let same_piece = !idx.contains(l) && !idx.contains(h)
+            && idx.lower_bound(&l) == idx.lower_bound(&h)
+            && idx.upper_bound(&l) == idx.upper_bound(&h);
+        if same_piece {
+            self.crack_in_three(l, h)
+        } else {
+            (self.crack_in_two(l), self.crack_in_two(h))
+        }
+    }
+
+    // Cracks the column on X and X + 1, returning the positions of the elements equal to X.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        let (p_low, p_high) = self.crack_range(x, x.successor());
+        p_low..p_high
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        let (p_low, p_high) = self.crack_range(l, h);
+        p_low..p_high
+    }
+
+    // Returns the number of bytes taken up by the packed columns, cracker column and base index.
+    pub fn size(&self) -> usize {
+        self.columns.values().map(|c| c.size()).sum::<usize>() + self.crk.size() + self.base_idx.size()
+    }
+
+    // Returns a new, uncracked table containing the rows at the given base indices.
+    pub fn get_indices(&self, indices: Iter<usize>) -> PackedTable {
+        let mut t = PackedTable::new();
+        t.settings = self.settings.clone();
+        t.count = indices.len();
+        for (name, col) in &self.columns {
+            let values: Vec<i64> = indices.clone().map(|&i| col.get(i)).collect();
+            t.columns.insert(name.clone(), BitPacked::pack(&values));
+        }
+        t
+    }
+
+    // Lays the rows out in the order of the given base indices, dropping the cracker column, which is
+    // built afresh by set_crk_col.
+    pub fn rearrange(&mut self, indices: Iter<usize>) {
+        for col in self.columns.values_mut() {
+            let values: Vec<i64> = indices.clone().map(|&i| col.get(i)).collect();
+            *col = BitPacked::pack(&values);
+        }
+        self.crk = BitPacked::default();
+        self.base_idx = BitPacked::default();
+        self.crk_idx.clear();
+        self.crk_col_name = "".to_string();
+    }
+}
+
+impl Default for PackedTable {
+    fn default() -> PackedTable {
+        PackedTable::new()
+    }
+}
+
+impl CrackedTable for PackedTable {
+    fn new() -> PackedTable {
+        PackedTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        PackedTable::new_columns(self, col_names.iter().map(|c| c.to_string()).collect())
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        PackedTable::insert(self, &mut owned_column_names(new_values))
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        PackedTable::set_crk_col(self, col_name.to_string())
+    }
+
+    // The table keeps a single cracker column, so moving to another builds it afresh.
+    fn use_crk_col(&mut self, col_name: &str) {
+        if self.crk_col_name != col_name {
+            PackedTable::set_crk_col(self, col_name.to_string())
+        }
+    }
+
+    fn delete_indices(&mut self, indices: Iter<usize>) {
+        PackedTable::delete_indices(self, indices)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    // The packed cracker column is no IntCol, so deletes and index changes are made by the table's own
+    // delete_indices and set_cracker_index.
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        Vec::new()
+    }
+
+    fn set_cracker_index(&mut self, kind: IndexKind) {
+        self.settings.index_kind = kind;
+        let mut idx = kind.new_index();
+        for (k, p) in self.crk_idx.pairs() {
+            idx.insert(k, p);
+        }
+        self.crk_idx = idx;
+    }
+
+    fn stats(&self) -> CrackStats {
+        PackedTable::stats(self)
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        PackedTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        PackedTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        Values::Gathered(PackedTable::values(self, r, col).into_iter())
+    }
+
+    fn scan_piece(&mut self, _l: i64, _h: Option<i64>) -> Option<Range<usize>> {
+        None
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        let column = &self.columns[col];
+        let values: Vec<i64> = r.filter(|&p| in_half_open(self.crk.get(p), l, h))
+            .map(|p| column.get(self.base_idx.get(p) as usize))
+            .collect();
+        Values::Gathered(values.into_iter())
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Gathered(self.columns[col].unpack().into_iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> PackedTable {
+        PackedTable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        PackedTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
+    }
+
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_idx.pairs()
+    }
+}
+
+// Returns an adjacency list built from the two vectors of adjacent nodes.
+pub fn from_adjacency_vectors(src_node: Vec<i64>, dst_node: Vec<i64>, crk: &str) -> PackedTable {
+    let mut adjacency_list = PackedTable::new();
+    adjacency_list.new_columns(vec!["src".to_string(), "dst".to_string()]);
+    adjacency_list.insert(&mut map!{"src".to_string() => src_node, "dst".to_string() => dst_node});
+    adjacency_list.set_crk_col(crk.to_string());
+    adjacency_list
+}
@@ -1,11 +1,13 @@
 use utils;
 use datagen;
 
-use decomposed_cracking;
-use recognitive_compression;
-use compactive_compression;
-use underswap_rle_compression;
-use overswap_rle_compression;
+use cracked_table::CrackedTable;
+use cracked_table::StochasticMode;
+use decomposed_cracking::DeCrackedTable;
+use recognitive_compression::ReCoTable;
+use compactive_compression::CoCoTable;
+use underswap_rle_compression::UnderswapRLETable;
+use overswap_rle_compression::OverswapRLETable;
 
 /* PAGERANK
     Given an adjacency list of two i64 vecs, SRC_NODE and DST_NODE and a vector of PAGERANKS, where
@@ -24,7 +26,7 @@ fn initialise_pageranks(n: usize) -> Vec<f64> {
     pageranks
 }
 
-fn terminate(pageranks: &Vec<f64>, new_pageranks: &Vec<f64>, n: usize, epsilon: f64) -> bool {
+fn terminate(pageranks: &[f64], new_pageranks: &[f64], n: usize, epsilon: f64) -> bool {
     let mut sum_of_squared_differences = 0.0;
     for v in 1..n {
         let d = new_pageranks[v] - pageranks[v];
@@ -43,19 +45,21 @@ pub fn example_test_pagerank_methods() {
     println!("Preclustered RLE");
     pagerank_example_test(preclustered_rle_pagerank);
     println!("Decracked");
-    pagerank_example_test(decracked_pagerank);
+    pagerank_example_test(cracked_pagerank::<DeCrackedTable>);
+    println!("Decracked DDR");
+    pagerank_example_test(|src, dst, prs, d, e, i| stochastic_pagerank::<DeCrackedTable>(StochasticMode::DDR(4), src, dst, prs, d, e, i));
     println!("Reco");
-    pagerank_example_test(reco_pagerank);
+    pagerank_example_test(cracked_pagerank::<ReCoTable>);
     println!("Coco");
-    pagerank_example_test(coco_pagerank);
+    pagerank_example_test(cracked_pagerank::<CoCoTable>);
     println!("Underswap RLE");
-    pagerank_example_test(underswap_rle_pagerank);
+    pagerank_example_test(cracked_pagerank::<UnderswapRLETable>);
     println!("Overswap RLE");
-    pagerank_example_test(overswap_rle_pagerank);
+    pagerank_example_test(cracked_pagerank::<OverswapRLETable>);
 }
 
 // Example from https://en.wikipedia.org/wiki/PageRank
-fn pagerank_example_test<F>(mut pagerank: F) where F: FnMut(Vec<i64>, Vec<i64>, &mut Vec<f64>, f64, f64, i64) -> Vec<f64> {
+fn pagerank_example_test<F>(mut pagerank: F) where F: FnMut(Vec<i64>, Vec<i64>, &mut [f64], f64, f64, i64) -> Vec<f64> {
     let src = vec![2, 3, 4, 4, 5, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 11];
     let dst = vec![3, 2, 1, 2, 2, 4, 6, 2, 5, 2, 5, 2, 5, 2, 5, 5,  5];
     let n = 11; // Number of nodes.
@@ -86,7 +90,7 @@ pub fn bait() {
     println!("let dst = vec!{:?};", dst);
 
     let true_prs = unoptimised_pagerank(src.clone(), dst.clone(), &mut initialise_pageranks(n), 0.85, 0.05, 20);
-    let test_prs =        coco_pagerank(src.clone(), dst.clone(), &mut initialise_pageranks(n), 0.85, 0.05, 20);
+    let test_prs =        cracked_pagerank::<CoCoTable>(src.clone(), dst.clone(), &mut initialise_pageranks(n), 0.85, 0.05, 20);
 
     let mut failed = false;
 
@@ -107,7 +111,7 @@ fn inherit(inherited_rank: &mut f64, prw: f64, lw: i64) {
     (*inherited_rank) += contribution;
 }
 
-fn unoptimised_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
+fn unoptimised_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut [f64], d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
     let e = src_node.len();
     let n = prs.len();
     let m = (1.0 - d) / (n as f64);
@@ -115,11 +119,11 @@ fn unoptimised_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f6
     let mut l = Vec::with_capacity(1 + n);
     for _ in 0..(n + 1) { l.push(-1); }
 
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
+    let mut pageranks     = prs.to_vec();
+    let mut new_pageranks = prs.to_vec();
     let mut iterations = 0;
     loop {
-        for v in 1..n {
+        for (v, new_rank) in new_pageranks.iter_mut().enumerate().skip(1) {
             let mut inherited_rank = 0.0;
             for i in 0..e {
                 if dst_node[i] == v as i64 {
@@ -128,7 +132,7 @@ fn unoptimised_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f6
                     inherit(&mut inherited_rank, pageranks[w], lw);
                 }
             }
-            new_pageranks[v] = m + d * inherited_rank;
+            *new_rank = m + d * inherited_rank;
         }
         if terminate(&pageranks, &new_pageranks, n, epsilon) {
             break;
@@ -142,7 +146,7 @@ fn unoptimised_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f6
     new_pageranks
 }
 
-fn preclustered_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
+fn preclustered_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut [f64], d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
     let e = src_node.len();
     let mut src_col = src_node.clone();
     let mut dst_col = dst_node.clone();
@@ -164,34 +168,31 @@ fn preclustered_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f
     let mut l = Vec::with_capacity(1 + n);
     for _ in 0..(n + 1) { l.push(-1); }
 
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
+    let mut pageranks     = prs.to_vec();
+    let mut new_pageranks = prs.to_vec();
 
     let mut iterations = 0;
     loop {
-        for v in 1..n {
+        for (v, new_rank) in new_pageranks.iter_mut().enumerate().skip(1) {
             let mut inherited_rank = 0.0;
 
-            let i;
-            match dst_col.binary_search(&(v as i64)) {
-                Ok(x)  => i = x,
+            let i = match dst_col.binary_search(&(v as i64)) {
+                Ok(x)  => x,
                 Err(_) => {
-                    new_pageranks[v] = m;
+                    *new_rank = m;
                     continue;
                 },
-            }
+            };
 
-            let mut inc_idx = i.clone();
-            let mut dec_idx = i.clone();
+            let mut inc_idx = i;
+            let mut dec_idx = i;
 
             loop {
                 let w = src_col[inc_idx] as usize;
                 let lw = if l[w] == -1 { l[w] = src_col.iter().fold(0, |acc, x| acc + ((x == &(w as i64)) as i64)); l[w] } else { l[w] };
                 inc_idx += 1;
                 inherit(&mut inherited_rank, pageranks[w], lw);
-                if inc_idx >= src_col.len() {
-                    break;
-                } else if dst_col[inc_idx] != (v as i64) {
+                if inc_idx >= src_col.len() || dst_col[inc_idx] != (v as i64) {
                     break;
                 }
             }
@@ -204,7 +205,7 @@ fn preclustered_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f
                 let lw = if l[w] == -1 { l[w] = src_col.iter().fold(0, |acc, x| acc + ((x == &(w as i64)) as i64)); l[w] } else { l[w] };
                 inherit(&mut inherited_rank, pageranks[w], lw);
             }
-            new_pageranks[v] = m + d * inherited_rank;
+            *new_rank = m + d * inherited_rank;
         }
         if terminate(&pageranks, &new_pageranks, n, epsilon) {
             break;
@@ -218,7 +219,7 @@ fn preclustered_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f
     new_pageranks
 }
 
-fn preclustered_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
+fn preclustered_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut [f64], d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
     let e = src_node.len();
     let n = prs.len();
     let m = (1.0 - d) / (n as f64);
@@ -246,12 +247,12 @@ fn preclustered_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut V
     let mut l = Vec::with_capacity(1 + n);
     for _ in 0..(n + 1) { l.push(-1); }
 
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
+    let mut pageranks     = prs.to_vec();
+    let mut new_pageranks = prs.to_vec();
 
     let mut iterations = 0;
     loop {
-        for v in 1..n {
+        for (v, new_rank) in new_pageranks.iter_mut().enumerate().skip(1) {
             let mut inherited_rank = 0.0;
 
             let in_neighbours = encoded_col[v].iter().map(|&x|x as usize);
@@ -259,123 +260,8 @@ fn preclustered_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut V
                 let lw = if l[w] == -1 { l[w] = src_node.iter().fold(0, |acc, x| acc + ((x == &(w as i64)) as i64)); l[w] } else { l[w] };
                 inherit(&mut inherited_rank, pageranks[w], lw);
             }
-            new_pageranks[v] = m + d * inherited_rank;
-        }
-        if terminate(&pageranks, &new_pageranks, n, epsilon) {
-            break;
-        }
-        pageranks = new_pageranks.clone();
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-    new_pageranks
-}
-
-// Decomposed cracking
-fn decracked_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
-    let mut adjacency_list = decomposed_cracking::from_adjacency_vectors(src_node, dst_node, "dst");
-
-    let n = prs.len();
-    let m = (1.0 - d) / (n as f64);
-
-    let mut l = Vec::with_capacity(1 + n);
-    for _ in 0..(n+1) { l.push(-1); }
-
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
-
-    let mut iterations = 0;
-    loop {
-        for v in 1..n {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(v as i64, "src").iter().map(|&x|x as usize) {
-                let lw = if l[w] == -1 { l[w] = (&adjacency_list).count_col_eq("src", w as i64); l[w] } else { l[w] };
-                inherit(&mut inherited_rank, pageranks[w], lw);
-            }
-
-            new_pageranks[v] = m + d * inherited_rank;
-        }
-
-        if terminate(&pageranks, &new_pageranks, n, epsilon) {
-            break;
-        }
-        pageranks = new_pageranks.clone();
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-    new_pageranks
-}
-
-// Recognitive compression
-fn reco_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
-    let mut adjacency_list = recognitive_compression::from_adjacency_vectors(src_node, dst_node, "dst");
-
-    let n = prs.len();
-    let m = (1.0 - d) / (n as f64);
-
-    let mut l = Vec::with_capacity(1 + n);
-    for _ in 0..(n+1) { l.push(-1); }
-
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
-
-    let mut iterations = 0;
-    loop {
-        for v in 1..n {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(v as i64, "src").iter().map(|&x|x as usize) {
-                let lw = if l[w] == -1 { l[w] = (&adjacency_list).count_col_eq("src", w as i64); l[w] } else { l[w] };
-                inherit(&mut inherited_rank, pageranks[w], lw);
-            }
-
-            new_pageranks[v] = m + d * inherited_rank;
-        }
-
-        if terminate(&pageranks, &new_pageranks, n, epsilon) {
-            break;
-        }
-        pageranks = new_pageranks.clone();
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-    new_pageranks
-}
-
-// Compactive compression
-fn coco_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
-    let mut adjacency_list = compactive_compression::from_adjacency_vectors(src_node, dst_node, "dst");
-
-    let n = prs.len();
-    let m = (1.0 - d) / (n as f64);
-
-    let mut l = Vec::with_capacity(1 + n);
-    for _ in 0..(n+1) { l.push(-1); }
-
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
-
-    let mut iterations = 0;
-    loop {
-
-        for v in 1..n {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(v as i64, "src").iter().map(|&x|x as usize) {
-                let lw = if l[w] == -1 { l[w] = (&adjacency_list).count_col_eq("src", w as i64); l[w] } else { l[w] };
-                inherit(&mut inherited_rank, pageranks[w], lw);
-            }
-
-            new_pageranks[v] = m + d * inherited_rank;
+            *new_rank = m + d * inherited_rank;
         }
-
         if terminate(&pageranks, &new_pageranks, n, epsilon) {
             break;
         }
@@ -388,47 +274,15 @@ fn coco_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d:
     new_pageranks
 }
 
-// Underswapping rle
-fn underswap_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
-    let mut adjacency_list = underswap_rle_compression::from_adjacency_vectors(src_node, dst_node, "dst");
-
-    let n = prs.len();
-    let m = (1.0 - d) / (n as f64);
-
-    let mut l = Vec::with_capacity(1 + n);
-    for _ in 0..(n+1) { l.push(-1); }
-
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
-
-    let mut iterations = 0;
-    loop {
-        for v in 1..n {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(v as i64, "src").iter().map(|&x|x as usize) {
-                let lw = if l[w] == -1 { l[w] = (&adjacency_list).count_col_eq("src", w as i64); l[w] } else { l[w] };
-                inherit(&mut inherited_rank, pageranks[w], lw);
-            }
-
-            new_pageranks[v] = m + d * inherited_rank;
-        }
-
-        if terminate(&pageranks, &new_pageranks, n, epsilon) {
-            break;
-        }
-        pageranks = new_pageranks.clone();
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-    new_pageranks
+// Decomposed cracking, with any of the compression schemes
+fn cracked_pagerank<T: CrackedTable>(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut [f64], d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
+    stochastic_pagerank::<T>(StochasticMode::Off, src_node, dst_node, prs, d, epsilon, max_iterations)
 }
 
-// Overswapping
-fn overswap_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f64>, d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
-    let mut adjacency_list = overswap_rle_compression::from_adjacency_vectors(src_node, dst_node, "dst");
+// Every vertex is queried in order on each iteration, which is the worst case for plain cracking.
+fn stochastic_pagerank<T: CrackedTable>(mode: StochasticMode, src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut [f64], d: f64, epsilon: f64, max_iterations: i64) -> Vec<f64> {
+    let mut adjacency_list = T::from_adjacency_vectors(src_node, dst_node, "dst");
+    adjacency_list.set_stochastic_mode(mode);
 
     let n = prs.len();
     let m = (1.0 - d) / (n as f64);
@@ -436,20 +290,27 @@ fn overswap_rle_pagerank(src_node: Vec<i64>, dst_node: Vec<i64>, prs: &mut Vec<f
     let mut l = Vec::with_capacity(1 + n);
     for _ in 0..(n+1) { l.push(-1); }
 
-    let mut pageranks     = prs.clone();
-    let mut new_pageranks = prs.clone();
+    let mut pageranks     = prs.to_vec();
+    let mut new_pageranks = prs.to_vec();
 
     let mut iterations = 0;
     loop {
-        for v in 1..n {
+        for (v, new_rank) in new_pageranks.iter_mut().enumerate().skip(1) {
             let mut inherited_rank = 0.0;
 
-            for w in adjacency_list.cracker_select_specific(v as i64, "src").iter().map(|&x|x as usize) {
-                let lw = if l[w] == -1 { l[w] = (&adjacency_list).count_col_eq("src", w as i64); l[w] } else { l[w] };
+            let sources = match adjacency_list.scan_piece(v as i64, Some(v as i64 + 1)) {
+                Some(r) => adjacency_list.filtered_values(r, v as i64, Some(v as i64 + 1), "src"),
+                None => {
+                    let r = adjacency_list.crack_specific(v as i64);
+                    adjacency_list.values(r, "src")
+                },
+            };
+            for w in sources.map(|x| x as usize) {
+                let lw = if l[w] == -1 { l[w] = adjacency_list.count_col_eq("src", w as i64); l[w] } else { l[w] };
                 inherit(&mut inherited_rank, pageranks[w], lw);
             }
 
-            new_pageranks[v] = m + d * inherited_rank;
+            *new_rank = m + d * inherited_rank;
         }
 
         if terminate(&pageranks, &new_pageranks, n, epsilon) {
@@ -0,0 +1,827 @@
+// Saving and loading tables along with everything cracking has built up in them, so that a table
+// reloaded after a restart answers queries exactly as it would have before.
+
+// A file is the magic bytes, the format version, the name of the table type and then the table's
+// fields in declaration order. Every integer is stored little-endian in 8 bytes, every sequence as
+// its length followed by its elements, and maps with their keys in order. A table's CrackSettings
+// are stored with it, so a loaded table keeps its tuning and its cracker indices' kind.
+
+use cracker_index::AVLCrackerIndex;
+use cracker_index::CrackerIndex;
+use cracker_index::IndexKind;
+use column::Dictionary;
+use column::IntCol;
+use cracked_table::CrackSettings;
+use cracked_table::CrackedTable;
+use cracked_table::PivotRng;
+use cracked_table::StochasticMode;
+use decomposed_cracking::DeCrackedTable;
+use encoding::ForBlock;
+use encoding::RunLengthColumn;
+use nary_cracking::NaryCrackedTable;
+use recognitive_compression::ReCoTable;
+use compactive_compression::CoCoTable;
+use underswap_rle_compression::UnderswapRLETable;
+use overswap_rle_compression::OverswapRLETable;
+use hybrid_cracking::CrkState;
+use hybrid_cracking::HybridMode;
+use hybrid_cracking::HybridTable;
+use packed_cracking::PackedTable;
+use adaptive_compression;
+use adaptive_compression::AdaptiveTable;
+use adaptive_compression::Encoding;
+use concurrent_cracking;
+use concurrent_cracking::ConcurrentTable;
+use encoding::BitPacked;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"CRKT";
+
+// Bumped whenever the layout changes. Files of any other version are refused.
+pub const VERSION: u64 = 7;
+
+pub trait PersistentTable: Sized {
+    // The name stored in the file, so that a table is only ever loaded as the type it was saved as.
+    fn type_name() -> &'static str;
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<Self>;
+
+    // Writes the table, cracker state included, to the file at PATH.
+    fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        w.write_all(MAGIC)?;
+        write_u64(&mut w, VERSION)?;
+        write_string(&mut w, Self::type_name())?;
+        self.write_fields(&mut w)?;
+        w.flush()
+    }
+
+    // Reads a table saved by save from the file at PATH.
+    fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut r = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("load: not a saved table".to_string()));
+        }
+        let version = read_u64(&mut r)?;
+        if version != VERSION {
+            return Err(invalid_data(format!("load: unsupported format version {}", version)));
+        }
+        let type_name = read_string(&mut r)?;
+        if type_name != Self::type_name() {
+            return Err(invalid_data(format!("load: file holds a {}, not a {}", type_name, Self::type_name())));
+        }
+        Self::read_fields(&mut r)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u64<W: Write>(w: &mut W, x: u64) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_i64<W: Write>(w: &mut W, x: i64) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn read_i64<R: Read>(r: &mut R) -> io::Result<i64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(i64::from_le_bytes(bytes))
+}
+
+fn write_usize<W: Write>(w: &mut W, x: usize) -> io::Result<()> {
+    write_u64(w, x as u64)
+}
+
+fn read_usize<R: Read>(r: &mut R) -> io::Result<usize> {
+    let x = read_u64(r)?;
+    if x > usize::MAX as u64 {
+        return Err(invalid_data(format!("read_usize: {} is too large for this platform", x)));
+    }
+    Ok(x as usize)
+}
+
+fn write_bool<W: Write>(w: &mut W, b: bool) -> io::Result<()> {
+    write_u64(w, b as u64)
+}
+
+fn read_bool<R: Read>(r: &mut R) -> io::Result<bool> {
+    match read_u64(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        x => Err(invalid_data(format!("read_bool: {} is not a bool", x))),
+    }
+}
+
+fn write_string<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    write_usize(w, s.len())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_string<R: Read>(r: &mut R) -> io::Result<String> {
+    let bytes = read_vec(r, |r| {
+        let mut b = [0; 1];
+        r.read_exact(&mut b)?;
+        Ok(b[0])
+    })?;
+    String::from_utf8(bytes).map_err(|e| invalid_data(e.to_string()))
+}
+
+fn write_vec<W: Write, T, F>(w: &mut W, items: &[T], write_item: F) -> io::Result<()>
+    where F: Fn(&mut W, &T) -> io::Result<()> {
+    write_usize(w, items.len())?;
+    for item in items {
+        write_item(w, item)?;
+    }
+    Ok(())
+}
+
+fn read_vec<R: Read, T, F>(r: &mut R, read_item: F) -> io::Result<Vec<T>>
+    where F: Fn(&mut R) -> io::Result<T> {
+    let n = read_usize(r)?;
+    // Not reserving N up front, so that a corrupt length fails on the read rather than the allocation.
+    let mut items = Vec::new();
+    for _ in 0..n {
+        items.push(read_item(r)?);
+    }
+    Ok(items)
+}
+
+fn write_i64s<W: Write>(w: &mut W, v: &[i64]) -> io::Result<()> {
+    write_vec(w, v, |w, &x| write_i64(w, x))
+}
+
+fn read_i64s<R: Read>(r: &mut R) -> io::Result<Vec<i64>> {
+    read_vec(r, read_i64)
+}
+
+fn write_usizes<W: Write>(w: &mut W, v: &[usize]) -> io::Result<()> {
+    write_vec(w, v, |w, &x| write_usize(w, x))
+}
+
+fn read_usizes<R: Read>(r: &mut R) -> io::Result<Vec<usize>> {
+    read_vec(r, read_usize)
+}
+
+fn write_map<W: Write, T, F>(w: &mut W, map: &HashMap<String, T>, write_value: F) -> io::Result<()>
+    where F: Fn(&mut W, &T) -> io::Result<()> {
+    let mut names: Vec<&String> = map.keys().collect();
+    names.sort();
+    write_usize(w, names.len())?;
+    for name in names {
+        write_string(w, name)?;
+        write_value(w, &map[name])?;
+    }
+    Ok(())
+}
+
+fn read_map<R: Read, T, F>(r: &mut R, read_value: F) -> io::Result<HashMap<String, T>>
+    where F: Fn(&mut R) -> io::Result<T> {
+    let n = read_usize(r)?;
+    let mut map = HashMap::new();
+    for _ in 0..n {
+        let name = read_string(r)?;
+        let value = read_value(r)?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+// The index is stored as its key value pairs in key order, and rebuilt by inserting them.
+fn write_cracker_index<W: Write>(w: &mut W, idx: &dyn CrackerIndex) -> io::Result<()> {
+    write_vec(w, &idx.pairs(), |w, &(k, p)| {
+        write_i64(w, k)?;
+        write_usize(w, p)
+    })
+}
+
+fn read_cracker_index<R: Read>(r: &mut R) -> io::Result<Box<dyn CrackerIndex>> {
+    let pairs = read_vec(r, |r| Ok((read_i64(r)?, read_usize(r)?)))?;
+    let mut idx: Box<dyn CrackerIndex> = Box::new(AVLCrackerIndex::new());
+    for (k, p) in pairs {
+        idx.insert(k, p);
+    }
+    Ok(idx)
+}
+
+// A frame is stored as its reference, width and encoded bytes, so that it is not re-encoded on load.
+fn write_frame<W: Write>(w: &mut W, frame: &ForBlock) -> io::Result<()> {
+    let (reference, width, bytes) = frame.parts();
+    write_usize(w, reference)?;
+    write_usize(w, width)?;
+    write_vec(w, bytes, |w, &b| w.write_all(&[b]))
+}
+
+fn read_frame<R: Read>(r: &mut R) -> io::Result<ForBlock> {
+    let reference = read_usize(r)?;
+    let width = read_usize(r)?;
+    let bytes = read_vec(r, |r| {
+        let mut b = [0u8];
+        r.read_exact(&mut b)?;
+        Ok(b[0])
+    })?;
+    let valid = match width {
+        0 => bytes.is_empty(),
+        1 | 2 | 4 | 8 => bytes.len() % width == 0,
+        _ => false,
+    };
+    if !valid {
+        return Err(invalid_data(format!("frame of width {} holds {} bytes", width, bytes.len())));
+    }
+    Ok(ForBlock::from_parts(reference, width, bytes))
+}
+
+// An RLE map is stored as the value and end position of each run, so that it is not re-encoded on load.
+fn write_runs<W: Write>(w: &mut W, map: &RunLengthColumn<i64>) -> io::Result<()> {
+    let (values, ends) = map.parts();
+    write_i64s(w, values)?;
+    write_usizes(w, ends)
+}
+
+fn read_runs<R: Read>(r: &mut R) -> io::Result<RunLengthColumn<i64>> {
+    let values = read_i64s(r)?;
+    let ends = read_usizes(r)?;
+    if values.len() != ends.len() || ends.first() == Some(&0) || ends.windows(2).any(|e| e[0] >= e[1]) {
+        return Err(invalid_data(format!("{} runs with {} malformed end positions", values.len(), ends.len())));
+    }
+    Ok(RunLengthColumn::from_parts(values, ends))
+}
+
+fn write_int_col<W: Write>(w: &mut W, col: &IntCol) -> io::Result<()> {
+    write_i64s(w, &col.v)?;
+    write_i64s(w, &col.crk)?;
+    write_cracker_index(w, &*col.crk_idx)?;
+    write_usizes(w, &col.base_idx)?;
+    write_usizes(w, &col.ofs)?;
+    write_vec(w, &col.frames, write_frame)?;
+    write_usizes(w, &col.run_lengths)?;
+    write_map(w, &col.crk_maps, |w, map| write_i64s(w, map))?;
+    write_map(w, &col.rle_maps, write_runs)?;
+    write_usizes(w, &col.pending)?;
+    write_usizes(w, &col.pending_deletes)?;
+    write_bool(w, col.sorted)?;
+    write_usize(w, col.convergence_countdown)
+}
+
+fn read_int_col<R: Read>(r: &mut R) -> io::Result<IntCol> {
+    let v = read_i64s(r)?;
+    let crk = read_i64s(r)?;
+    let crk_idx = read_cracker_index(r)?;
+    let base_idx = read_usizes(r)?;
+    let ofs = read_usizes(r)?;
+    let frames = read_vec(r, read_frame)?;
+    let run_lengths = read_usizes(r)?;
+    let crk_maps = read_map(r, read_i64s)?;
+    let rle_maps = read_map(r, read_runs)?;
+    let pending = read_usizes(r)?;
+    let pending_deletes = read_usizes(r)?;
+    let sorted = read_bool(r)?;
+    let convergence_countdown = read_usize(r)?;
+    Ok(IntCol { v, crk, crk_idx, base_idx, ofs, frames, run_lengths, crk_maps, rle_maps, pending, pending_deletes, sorted, convergence_countdown })
+}
+
+fn write_stochastic<W: Write>(w: &mut W, mode: StochasticMode) -> io::Result<()> {
+    let (tag, max_piece_size) = match mode {
+        StochasticMode::Off => (0, 0),
+        StochasticMode::DD1R => (1, 0),
+        StochasticMode::DDR(size) => (2, size),
+        StochasticMode::DDC(size) => (3, size),
+    };
+    write_u64(w, tag)?;
+    write_usize(w, max_piece_size)
+}
+
+fn read_stochastic<R: Read>(r: &mut R) -> io::Result<StochasticMode> {
+    let tag = read_u64(r)?;
+    let max_piece_size = read_usize(r)?;
+    match tag {
+        0 => Ok(StochasticMode::Off),
+        1 => Ok(StochasticMode::DD1R),
+        2 => Ok(StochasticMode::DDR(max_piece_size)),
+        3 => Ok(StochasticMode::DDC(max_piece_size)),
+        _ => Err(invalid_data(format!("read_stochastic: unknown mode {}", tag))),
+    }
+}
+
+fn write_index_kind<W: Write>(w: &mut W, kind: IndexKind) -> io::Result<()> {
+    write_u64(w, match kind {
+        IndexKind::AVL => 0,
+        IndexKind::BPlusTree => 1,
+        IndexKind::Sorted => 2,
+        IndexKind::Array => 3,
+    })
+}
+
+fn read_index_kind<R: Read>(r: &mut R) -> io::Result<IndexKind> {
+    match read_u64(r)? {
+        0 => Ok(IndexKind::AVL),
+        1 => Ok(IndexKind::BPlusTree),
+        2 => Ok(IndexKind::Sorted),
+        3 => Ok(IndexKind::Array),
+        x => Err(invalid_data(format!("read_index_kind: unknown index kind {}", x))),
+    }
+}
+
+// The settings are stored whole, the state of the pivot generator included, so that a reloaded
+// table draws the same pivots it would have.
+fn write_settings<W: Write>(w: &mut W, settings: &CrackSettings) -> io::Result<()> {
+    write_stochastic(w, settings.stochastic)?;
+    write_u64(w, settings.rng.0)?;
+    write_index_kind(w, settings.index_kind)?;
+    write_usize(w, settings.parallel_threshold)?;
+    write_usize(w, settings.convergence_threshold)?;
+    write_usize(w, settings.min_piece_size)
+}
+
+fn read_settings<R: Read>(r: &mut R) -> io::Result<CrackSettings> {
+    Ok(CrackSettings {
+        stochastic: read_stochastic(r)?,
+        rng: PivotRng(read_u64(r)?),
+        index_kind: read_index_kind(r)?,
+        parallel_threshold: read_usize(r)?,
+        convergence_threshold: read_usize(r)?,
+        min_piece_size: read_usize(r)?,
+    })
+}
+
+// Cracker indices are read back as AVL trees, so those of a table held in another kind of index are
+// moved into it once the table is read.
+fn restore_index_kind<T: CrackedTable>(t: &mut T) {
+    let kind = t.settings().index_kind;
+    if kind != IndexKind::AVL {
+        t.set_cracker_index(kind);
+    }
+}
+
+fn write_deleted<W: Write>(w: &mut W, deleted: &HashSet<usize>) -> io::Result<()> {
+    let mut rows: Vec<usize> = deleted.iter().cloned().collect();
+    rows.sort();
+    write_usizes(w, &rows)
+}
+
+fn read_deleted<R: Read>(r: &mut R) -> io::Result<HashSet<usize>> {
+    Ok(read_usizes(r)?.into_iter().collect())
+}
+
+// A dictionary is stored as the string of each code, in code order.
+fn write_dictionary<W: Write>(w: &mut W, dict: &Dictionary) -> io::Result<()> {
+    write_vec(w, &dict.strings, |w, s| write_string(w, s))
+}
+
+fn read_dictionary<R: Read>(r: &mut R) -> io::Result<Dictionary> {
+    Ok(Dictionary::from_strings(read_vec(r, read_string)?))
+}
+
+impl PersistentTable for DeCrackedTable {
+    fn type_name() -> &'static str { "DeCrackedTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.columns, write_int_col)?;
+        write_map(w, &self.dicts, write_dictionary)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<DeCrackedTable> {
+        let mut t = DeCrackedTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.columns = read_map(r, read_int_col)?;
+        t.dicts = read_map(r, read_dictionary)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+impl PersistentTable for NaryCrackedTable {
+    fn type_name() -> &'static str { "NaryCrackedTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.columns, write_int_col)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<NaryCrackedTable> {
+        let mut t = NaryCrackedTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.columns = read_map(r, read_int_col)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+impl PersistentTable for ReCoTable {
+    fn type_name() -> &'static str { "ReCoTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.int_columns, write_int_col)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<ReCoTable> {
+        let mut t = ReCoTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.int_columns = read_map(r, read_int_col)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+impl PersistentTable for CoCoTable {
+    fn type_name() -> &'static str { "CoCoTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.int_columns, write_int_col)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<CoCoTable> {
+        let mut t = CoCoTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.int_columns = read_map(r, read_int_col)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+impl PersistentTable for UnderswapRLETable {
+    fn type_name() -> &'static str { "UnderswapRLETable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.columns, write_int_col)?;
+        write_bool(w, self.dbg_switch)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<UnderswapRLETable> {
+        let mut t = UnderswapRLETable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.columns = read_map(r, read_int_col)?;
+        t.dbg_switch = read_bool(r)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+impl PersistentTable for OverswapRLETable {
+    fn type_name() -> &'static str { "OverswapRLETable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.columns, write_int_col)?;
+        write_bool(w, self.dbg_switch)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<OverswapRLETable> {
+        let mut t = OverswapRLETable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.columns = read_map(r, read_int_col)?;
+        t.dbg_switch = read_bool(r)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+// An open upper end is stored as a false flag with no bound after it.
+fn write_bounds<W: Write>(w: &mut W, (l, h): (i64, Option<i64>)) -> io::Result<()> {
+    write_i64(w, l)?;
+    write_bool(w, h.is_some())?;
+    match h {
+        Some(h) => write_i64(w, h),
+        None => Ok(()),
+    }
+}
+
+fn read_bounds<R: Read>(r: &mut R) -> io::Result<(i64, Option<i64>)> {
+    let l = read_i64(r)?;
+    let h = if read_bool(r)? { Some(read_i64(r)?) } else { None };
+    Ok((l, h))
+}
+
+fn write_merged<W: Write>(w: &mut W, merged: &[(i64, Option<i64>)]) -> io::Result<()> {
+    write_vec(w, merged, |w, &bounds| write_bounds(w, bounds))
+}
+
+fn read_merged<R: Read>(r: &mut R) -> io::Result<Vec<(i64, Option<i64>)>> {
+    read_vec(r, read_bounds)
+}
+
+fn write_crk_state<W: Write>(w: &mut W, state: &CrkState) -> io::Result<()> {
+    write_int_col(w, &state.0)?;
+    write_vec(w, &state.1, write_int_col)?;
+    write_merged(w, &state.2)
+}
+
+fn read_crk_state<R: Read>(r: &mut R) -> io::Result<CrkState> {
+    Ok((read_int_col(r)?, read_vec(r, read_int_col)?, read_merged(r)?))
+}
+
+impl PersistentTable for HybridTable {
+    fn type_name() -> &'static str { "HybridTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_int_col(w, &self.crk_col)?;
+        write_map(w, &self.crk_cols, write_crk_state)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.columns, write_int_col)?;
+        write_u64(w, match self.mode {
+            HybridMode::CrackCrack => 0,
+            HybridMode::CrackSort => 1,
+            HybridMode::RadixSort => 2,
+        })?;
+        write_usize(w, self.run_size)?;
+        write_vec(w, &self.runs, write_int_col)?;
+        write_merged(w, &self.merged)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<HybridTable> {
+        let mut t = HybridTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk_col = read_int_col(r)?;
+        t.crk_cols = read_map(r, read_crk_state)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.columns = read_map(r, read_int_col)?;
+        t.mode = match read_u64(r)? {
+            0 => HybridMode::CrackCrack,
+            1 => HybridMode::CrackSort,
+            2 => HybridMode::RadixSort,
+            x => return Err(invalid_data(format!("read_fields: unknown hybrid mode {}", x))),
+        };
+        t.run_size = read_usize(r)?;
+        if t.run_size == 0 {
+            return Err(invalid_data("read_fields: runs must hold at least one element".to_string()));
+        }
+        t.runs = read_vec(r, read_int_col)?;
+        t.merged = read_merged(r)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+// A packed column is stored as its reference, width, length and words, so that it is not repacked on load.
+fn write_packed<W: Write>(w: &mut W, packed: &BitPacked) -> io::Result<()> {
+    let (reference, width, len, words) = packed.parts();
+    write_i64(w, reference)?;
+    write_u64(w, width as u64)?;
+    write_usize(w, len)?;
+    write_vec(w, words, |w, &word| write_u64(w, word))
+}
+
+fn read_packed<R: Read>(r: &mut R) -> io::Result<BitPacked> {
+    let reference = read_i64(r)?;
+    let width = read_u64(r)?;
+    let len = read_usize(r)?;
+    let words = read_vec(r, read_u64)?;
+    let bits = len.checked_mul(width as usize);
+    if width > 64 || bits.map(|b| b.div_ceil(64)) != Some(words.len()) {
+        return Err(invalid_data(format!("{} elements of width {} in {} words", len, width, words.len())));
+    }
+    Ok(BitPacked::from_parts(reference, width as u32, len, words))
+}
+
+impl PersistentTable for PackedTable {
+    fn type_name() -> &'static str { "PackedTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_packed(w, &self.crk)?;
+        write_packed(w, &self.base_idx)?;
+        write_cracker_index(w, &*self.crk_idx)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_map(w, &self.columns, write_packed)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<PackedTable> {
+        let mut t = PackedTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.crk = read_packed(r)?;
+        t.base_idx = read_packed(r)?;
+        t.crk_idx = read_cracker_index(r)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        t.columns = read_map(r, read_packed)?;
+        restore_index_kind(&mut t);
+        Ok(t)
+    }
+}
+
+// Pieces are stored in value order. The first must start at i64::MIN and each end where the next
+// starts, as the tables find the piece holding a value from their lower bounds alone.
+fn check_piece_bounds<I: Iterator<Item = (i64, Option<i64>)>>(bounds: I) -> io::Result<()> {
+    let (mut next_low, mut pieces) = (Some(i64::MIN), 0);
+    for (low, high) in bounds {
+        if Some(low) != next_low || high.is_some_and(|h| h <= low) {
+            return Err(invalid_data(format!("piece [{}, {:?}) does not follow on from {:?}", low, high, next_low)));
+        }
+        next_low = high;
+        pieces += 1;
+    }
+    if pieces > 0 && next_low.is_some() {
+        return Err(invalid_data(format!("last piece ends at {:?} rather than being open above", next_low)));
+    }
+    Ok(())
+}
+
+fn write_encoding<W: Write>(w: &mut W, encoding: &Encoding) -> io::Result<()> {
+    match *encoding {
+        Encoding::Plain { ref crk, ref base_idx } => {
+            write_u64(w, 0)?;
+            write_i64s(w, crk)?;
+            write_usizes(w, base_idx)
+        },
+        Encoding::Runs { ref values, ref lengths, ref base_idx } => {
+            write_u64(w, 1)?;
+            write_i64s(w, values)?;
+            write_usizes(w, lengths)?;
+            write_usizes(w, base_idx)
+        },
+        Encoding::Compacted { value, ref base_idx } => {
+            write_u64(w, 2)?;
+            write_i64(w, value)?;
+            write_frame(w, base_idx)
+        },
+    }
+}
+
+fn read_encoding<R: Read>(r: &mut R) -> io::Result<Encoding> {
+    match read_u64(r)? {
+        0 => {
+            let crk = read_i64s(r)?;
+            let base_idx = read_usizes(r)?;
+            if crk.len() != base_idx.len() {
+                return Err(invalid_data(format!("{} values with {} base indices", crk.len(), base_idx.len())));
+            }
+            Ok(Encoding::Plain { crk, base_idx })
+        },
+        1 => {
+            let values = read_i64s(r)?;
+            let lengths = read_usizes(r)?;
+            let base_idx = read_usizes(r)?;
+            let rows = lengths.iter().try_fold(0usize, |sum, &l| sum.checked_add(l));
+            if values.len() != lengths.len() || rows != Some(base_idx.len()) {
+                return Err(invalid_data(format!("{} runs of {:?} rows with {} base indices", values.len(), rows, base_idx.len())));
+            }
+            Ok(Encoding::Runs { values, lengths, base_idx })
+        },
+        2 => Ok(Encoding::Compacted { value: read_i64(r)?, base_idx: read_frame(r)? }),
+        x => Err(invalid_data(format!("read_encoding: unknown encoding {}", x))),
+    }
+}
+
+impl PersistentTable for AdaptiveTable {
+    fn type_name() -> &'static str { "AdaptiveTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_map(w, &self.columns, write_int_col)?;
+        write_settings(w, &self.settings)?;
+        write_deleted(w, &self.deleted)?;
+        write_vec(w, &self.piece_parts(), |w, &(low, high, ref encoding, queries)| {
+            write_bounds(w, (low, high))?;
+            write_encoding(w, encoding)?;
+            write_usize(w, queries)
+        })?;
+        write_usize(w, self.hot_queries)?;
+        write_usize(w, self.compaction_threshold)?;
+        write_usize(w, self.rle_threshold)
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<AdaptiveTable> {
+        let mut t = AdaptiveTable::new();
+        t.count = read_usize(r)?;
+        t.crk_col_name = read_string(r)?;
+        t.columns = read_map(r, read_int_col)?;
+        t.settings = read_settings(r)?;
+        t.deleted = read_deleted(r)?;
+        let parts: Vec<adaptive_compression::PieceParts> = read_vec(r, |r| {
+            let (low, high) = read_bounds(r)?;
+            Ok((low, high, read_encoding(r)?, read_usize(r)?))
+        })?;
+        check_piece_bounds(parts.iter().map(|p| (p.0, p.1)))?;
+        t.set_piece_parts(parts);
+        t.hot_queries = read_usize(r)?;
+        t.compaction_threshold = read_usize(r)?;
+        t.rle_threshold = read_usize(r)?;
+        Ok(t)
+    }
+}
+
+// Saving latches each piece in turn, so a table saved while other threads query it is saved as it
+// stood at some point during the save.
+impl PersistentTable for ConcurrentTable {
+    fn type_name() -> &'static str { "ConcurrentTable" }
+
+    fn write_fields<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_usize(w, self.count)?;
+        write_string(w, &self.crk_col_name)?;
+        write_map(w, &self.columns, write_int_col)?;
+        write_vec(w, &self.piece_parts(), |w, &(low, high, ref crk, ref base_idx)| {
+            write_bounds(w, (low, high))?;
+            write_i64s(w, crk)?;
+            write_usizes(w, base_idx)
+        })
+    }
+
+    fn read_fields<R: Read>(r: &mut R) -> io::Result<ConcurrentTable> {
+        let count = read_usize(r)?;
+        let crk_col_name = read_string(r)?;
+        let columns = read_map(r, read_int_col)?;
+        let parts: Vec<concurrent_cracking::PieceParts> = read_vec(r, |r| {
+            let (low, high) = read_bounds(r)?;
+            let crk = read_i64s(r)?;
+            let base_idx = read_usizes(r)?;
+            if crk.len() != base_idx.len() {
+                return Err(invalid_data(format!("{} values with {} base indices", crk.len(), base_idx.len())));
+            }
+            Ok((low, high, crk, base_idx))
+        })?;
+        check_piece_bounds(parts.iter().map(|p| (p.0, p.1)))?;
+        Ok(ConcurrentTable::from_parts(count, crk_col_name, columns, parts))
+    }
+}
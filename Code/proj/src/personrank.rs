@@ -1,8 +1,9 @@
-use decomposed_cracking;
-use recognitive_compression;
-use compactive_compression;
-use underswap_rle_compression;
-use overswap_rle_compression;
+use cracked_table::CrackedTable;
+use decomposed_cracking::DeCrackedTable;
+use recognitive_compression::ReCoTable;
+use compactive_compression::CoCoTable;
+use underswap_rle_compression::UnderswapRLETable;
+use overswap_rle_compression::OverswapRLETable;
 
 use load_person_csv;
 
@@ -16,15 +17,14 @@ fn get_out_degree(vertices: &Vec<i64>, src: &Vec<i64>) -> HashMap<i64, i64> {
         out_degree.insert(*v, 0);
     }
     for v in src {
-        match out_degree.get_mut(&v) {
-            Some(d) => { *d += 1; },
-            None    => {},
+        if let Some(d) = out_degree.get_mut(v) {
+            *d += 1;
         }
     }
     out_degree
 }
 
-fn get_vertices(people: &Vec<load_person_csv::Person>) -> Vec<i64> {
+fn get_vertices(people: &[load_person_csv::Person]) -> Vec<i64> {
     people.iter().map(|p|p.id).collect()
 }
 
@@ -48,11 +48,11 @@ pub fn benchmark_all(scale_factor: i16, pagerank_iterations: i16, averaging_iter
         let start = PreciseTime::now();
         // let unoptimised_ranks = unoptimised_personrank(vertices.clone(), src.clone(), dst.clone(), max_iterations);
         let (preclustered_ranks, preclustered_t) = preclustered_personrank(vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
-        let (decracked_ranks, decracked_t)       = decracked_personrank(vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
-        let (reco_ranks, reco_t)                 = reco_personrank(vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
-        let (coco_ranks, coco_t)                 = coco_personrank(vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
-        let (underswap_ranks, underswap_t)       = underswap_personrank(vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
-        let (overswap_ranks, overswap_t)         = overswap_personrank(vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
+        let (decracked_ranks, decracked_t)       = cracked_personrank::<DeCrackedTable>("decracked", vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
+        let (reco_ranks, reco_t)                 = cracked_personrank::<ReCoTable>("reco", vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
+        let (coco_ranks, coco_t)                 = cracked_personrank::<CoCoTable>("coco", vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
+        let (underswap_ranks, underswap_t)       = cracked_personrank::<UnderswapRLETable>("underswap", vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
+        let (overswap_ranks, overswap_t)         = cracked_personrank::<OverswapRLETable>("overswap", vertices.clone(), src.clone(), dst.clone(), pagerank_iterations);
 
         preclustered_times.push(preclustered_t);
         decracked_times.push(decracked_t);
@@ -137,8 +137,6 @@ pub fn unoptimised_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>,
 pub fn preclustered_personrank(vertices: Vec<i64>, mut src: Vec<i64>, mut dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
     println!("preclustered personrank");
 
-    let start = PreciseTime::now();
-
     let out_degree = get_out_degree(&vertices, &src);
     let (n, d) = (vertices.len(), 0.85);
 
@@ -173,25 +171,22 @@ pub fn preclustered_personrank(vertices: Vec<i64>, mut src: Vec<i64>, mut dst: V
         for v in &vertices {
             let mut inherited_rank = 0.0;
 
-            let i;
-            match dst.binary_search(v) {
-                Ok(x)  => i = x,
+            let i = match dst.binary_search(v) {
+                Ok(x)  => x,
                 Err(_) => {
                     new_rank.insert(*v, m);
                     continue;
                 },
-            }
+            };
 
-            let mut inc_idx = i.clone();
-            let mut dec_idx = i.clone();
+            let mut inc_idx = i;
+            let mut dec_idx = i;
 
             loop {
                 let w = src[inc_idx];
                 inc_idx += 1;
                 inherited_rank += rank[&w] / (out_degree[&w] as f64);
-                if inc_idx >= e {
-                    break;
-                } else if dst[inc_idx] != *v {
+                if inc_idx >= e || dst[inc_idx] != *v {
                     break;
                 }
             }
@@ -221,113 +216,11 @@ pub fn preclustered_personrank(vertices: Vec<i64>, mut src: Vec<i64>, mut dst: V
     (new_rank, alg_time)
 }
 
-pub fn decracked_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
-    println!("decracked personrank");
-
-    let start = PreciseTime::now();
-
-    let out_degree = get_out_degree(&vertices, &src);
-    let mut adjacency_list = decomposed_cracking::from_adjacency_vectors(src, dst, "dst");
-    let (n, d) = (vertices.len(), 0.85);
-
-    let setup_end = PreciseTime::now();
-
-    // Personrank
-
-    let m = (1.0 - d) / ((n as i64) as f64);
-
-    let mut rank: HashMap<i64, f64> = HashMap::new();
-    let mut new_rank: HashMap<i64, f64> = HashMap::new();
-
-    let initial_rank = (n as f64).recip();
-    for v in &vertices {
-        rank.insert(*v, initial_rank);
-        new_rank.insert(*v, initial_rank);
-    }
-
-    let mut iterations = 0;
-    loop {
-        for v in &vertices {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(*v, "src") {
-                inherited_rank += rank[&w] / (out_degree[&w] as f64);
-            }
-
-            new_rank.insert(*v, m + d * inherited_rank);
-        }
-
-        rank = new_rank.clone();
-
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-
-    let alg_end = PreciseTime::now();
-    let alg_time = setup_end.to(alg_end);
-
-    (new_rank, alg_time)
-}
-
-pub fn reco_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
-    println!("reco personrank");
-
-    let start = PreciseTime::now();
-
-    let out_degree = get_out_degree(&vertices, &src);
-    let mut adjacency_list = recognitive_compression::from_adjacency_vectors(src, dst, "dst");
-    let (n, d) = (vertices.len(), 0.85);
-
-    let setup_end = PreciseTime::now();
-
-    // Personrank
-
-    let m = (1.0 - d) / ((n as i64) as f64);
-
-    let mut rank: HashMap<i64, f64> = HashMap::new();
-    let mut new_rank: HashMap<i64, f64> = HashMap::new();
-
-    let initial_rank = (n as f64).recip();
-    for v in &vertices {
-        rank.insert(*v, initial_rank);
-        new_rank.insert(*v, initial_rank);
-    }
-
-    let mut iterations = 0;
-    loop {
-        for v in &vertices {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(*v, "src") {
-                inherited_rank += rank[&w] / (out_degree[&w] as f64);
-            }
-
-            new_rank.insert(*v, m + d * inherited_rank);
-        }
-
-        rank = new_rank.clone();
-
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-
-    let alg_end = PreciseTime::now();
-    let alg_time = setup_end.to(alg_end);
-
-    (new_rank, alg_time)
-}
-
-pub fn coco_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
-    println!("coco personrank");
-
-    let start = PreciseTime::now();
+pub fn cracked_personrank<T: CrackedTable>(name: &str, vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
+    println!("{} personrank", name);
 
     let out_degree = get_out_degree(&vertices, &src);
-    let mut adjacency_list = compactive_compression::from_adjacency_vectors(src, dst, "dst");
+    let mut adjacency_list = T::from_adjacency_vectors(src, dst, "dst");
     let (n, d) = (vertices.len(), 0.85);
 
     let setup_end = PreciseTime::now();
@@ -350,7 +243,7 @@ pub fn coco_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_ite
         for v in &vertices {
             let mut inherited_rank = 0.0;
 
-            for w in adjacency_list.cracker_select_specific(*v, "src") {
+            for w in adjacency_list.cracker_iter_specific(*v, "src") {
                 inherited_rank += rank[&w] / (out_degree[&w] as f64);
             }
 
@@ -370,104 +263,3 @@ pub fn coco_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_ite
 
     (new_rank, alg_time)
 }
-
-pub fn underswap_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
-    println!("underswap personrank");
-
-    let start = PreciseTime::now();
-
-    let out_degree = get_out_degree(&vertices, &src);
-    let mut adjacency_list = underswap_rle_compression::from_adjacency_vectors(src, dst, "dst");
-
-    let (n, d) = (vertices.len(), 0.85);
-
-    let setup_end = PreciseTime::now();
-
-    // Personrank
-
-    let m = (1.0 - d) / ((n as i64) as f64);
-
-    let mut rank: HashMap<i64, f64> = HashMap::new();
-    let mut new_rank: HashMap<i64, f64> = HashMap::new();
-
-    let initial_rank = (n as f64).recip();
-    for v in &vertices {
-        rank.insert(*v, initial_rank);
-        new_rank.insert(*v, initial_rank);
-    }
-
-    let mut iterations = 0;
-    loop {
-        for v in &vertices {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(*v, "src") {
-                inherited_rank += rank[&w] / (out_degree[&w] as f64);
-            }
-
-            new_rank.insert(*v, m + d * inherited_rank);
-        }
-
-        rank = new_rank.clone();
-
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-
-    let alg_end = PreciseTime::now();
-    let alg_time = setup_end.to(alg_end);
-
-    (new_rank, alg_time)
-}
-
-pub fn overswap_personrank(vertices: Vec<i64>, src: Vec<i64>, dst: Vec<i64>, max_iterations: i16) -> (HashMap<i64, f64>, Duration) {
-    println!("overswap personrank");
-
-    let start = PreciseTime::now();
-    
-    let out_degree = get_out_degree(&vertices, &src);
-    let mut adjacency_list = overswap_rle_compression::from_adjacency_vectors(src, dst, "dst");
-    let (n, d) = (vertices.len(), 0.85);
-
-    let setup_end = PreciseTime::now();
-
-    // Personrank
-
-    let m = (1.0 - d) / ((n as i64) as f64);
-
-    let mut rank: HashMap<i64, f64> = HashMap::new();
-    let mut new_rank: HashMap<i64, f64> = HashMap::new();
-
-    let initial_rank = (n as f64).recip();
-    for v in &vertices {
-        rank.insert(*v, initial_rank);
-        new_rank.insert(*v, initial_rank);
-    }
-
-    let mut iterations = 0;
-    loop {
-        for v in &vertices {
-            let mut inherited_rank = 0.0;
-
-            for w in adjacency_list.cracker_select_specific(*v, "src") {
-                inherited_rank += rank[&w] / (out_degree[&w] as f64);
-            }
-
-            new_rank.insert(*v, m + d * inherited_rank);
-        }
-
-        rank = new_rank.clone();
-
-        iterations += 1;
-        if iterations > max_iterations {
-            break;
-        }
-    }
-
-    let alg_end = PreciseTime::now();
-    let alg_time = setup_end.to(alg_end);
-
-    (new_rank, alg_time)
-}
\ No newline at end of file
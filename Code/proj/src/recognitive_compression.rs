@@ -3,18 +3,32 @@
 
 // uses map! macro.
 
-use cracker_index::AVLCrackerIndex;
 use column::Column;
+use column::Element;
 use column::IntCol;
+use column::Values;
+use column::half_open;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::slice::Iter;
+use std::ops::Range;
 
 #[derive(Clone)]
 pub struct ReCoTable {
     pub count: usize,
     pub crk_col_name: String,
     pub crk_col: IntCol,
+    // Cracker columns on other columns, parked by the name of the column they crack until use_crk_col
+    // switches to them.
+    pub crk_cols: HashMap<String, IntCol>,
+    pub settings: CrackSettings,
+    // Base indices of the deleted rows. Their values stay in the base columns, so that base indices
+    // remain valid.
+    pub deleted: HashSet<usize>,
     pub int_columns: HashMap<String, IntCol>,
 }
 
@@ -24,6 +38,9 @@ impl ReCoTable {
             count: 0,
             crk_col_name: "".to_string(),
             crk_col: IntCol::empty(),
+            crk_cols: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
             int_columns: HashMap::new(),
         }
     }
@@ -36,15 +53,23 @@ impl ReCoTable {
     }
 
     pub fn set_crk_col(&mut self, col_name: &str) {
+        // The current cracker column is parked, so that use_crk_col can switch back to it.
+        if self.crk_col_name != col_name {
+            self.crk_col.park(&self.crk_col_name, &mut self.crk_cols);
+        }
+        self.crk_cols.remove(col_name);
+        self.crk_col = match self.int_columns.get(col_name) {
+            Some(c) => IntCol::cracking(&c.v, &self.deleted, self.settings.index_kind),
+            None => panic!("set_crk_col: no such col"),
+        };
         self.crk_col_name = col_name.to_string();
+    }
 
-        match self.int_columns.get_mut(&(col_name.to_string())) {
-            Some(ref mut c) => {
-                self.crk_col.v        = c.v.clone();
-                self.crk_col.crk      = c.v.clone();
-                self.crk_col.base_idx = (0..self.count).collect();
-            },
-            None => panic!("set_crk_col: no such col"),
+    // Makes the cracker column on COL_NAME the one which queries crack, parking the current one. The
+    // cracker column is built if the table has none on COL_NAME yet.
+    pub fn use_crk_col(&mut self, col_name: &str) {
+        if !self.crk_col.unpark(&mut self.crk_col_name, col_name, &mut self.crk_cols) {
+            self.set_crk_col(col_name);
         }
     }
 
@@ -72,7 +97,7 @@ impl ReCoTable {
         let check_inserted_column_lengths = |v: &Vec<i64>, l: usize| if v.len() != l { panic!("insert: Int columns to be inserted do not have the same length") };
 
         for (k, v) in new_values.iter() {
-            if self.int_columns.contains_key(&(k.to_string())) {
+            if self.int_columns.contains_key(*k) {
                 match l_old {
                     Some(l) => check_inserted_column_lengths(v, l),
                     None    => l_old = Some(v.len()),
@@ -103,21 +128,22 @@ impl ReCoTable {
         // For every old-column entry, append the values to the current column
         // For every new-column entry, create the column and add the values
         for (k, v) in new_values.iter_mut() {
-            if self.int_columns.contains_key(&(k.to_string())) {
-                let c = self.int_columns.get_mut(&(k.to_string())).unwrap();
-                c.append(v);
-            } else {
-                let mut new = IntCol::empty();
-                new.append(v);
-                self.int_columns.insert(k.to_string(), new);
-            }
+            self.int_columns.entry(k.to_string()).or_insert_with(IntCol::empty).append(v);
         }
 
         // Mark the increased size of the table
-        if l_new.is_some() {
-            self.count = l_new.unwrap();
-        } else {
-            self.count += l_old.unwrap();
+        let old_count = self.count;
+        match l_new {
+            Some(l) => self.count = l,
+            None    => self.count += l_old.unwrap(),
+        }
+
+        // Rows inserted after the cracker column is set are merged into it by the queries which need them
+        if !self.crk_col_name.is_empty() && self.count > old_count {
+            self.crk_col.add_pending(&self.int_columns[&self.crk_col_name].v, old_count..self.count);
+        }
+        for (name, c) in self.crk_cols.iter_mut() {
+            c.add_pending(&self.int_columns[name].v, old_count..self.count);
         }
     }
 
@@ -134,10 +160,13 @@ impl ReCoTable {
             col.rearrange(indices.clone());
         }
         self.crk_col.rearrange(indices.clone());
+        for c in self.crk_cols.values_mut() {
+            c.rearrange(indices.clone());
+        }
     }
 
     pub fn get_i64_col(&self, col: &str) -> &IntCol {
-        self.int_columns.get(&(col.to_string())).expect(&*("get_col: No column called ".to_string() + col))
+        self.int_columns.get(col).unwrap_or_else(|| panic!("get_col: No column called {}", col))
     }
 
     pub fn get_indices(&self, indices: Iter<usize>) -> ReCoTable {
@@ -155,16 +184,17 @@ impl ReCoTable {
         let mut t = ReCoTable::new();
         t.int_columns = int_selection;
         t.count = indices.len();
+        t.settings = self.settings.clone();
 
         let mut indexed_crk_v = Vec::with_capacity(indices.len());
-        if self.crk_col.crk.len() > 0 {
+        if !self.crk_col.crk.is_empty() {
             let mut indexed_crk_col = Vec::with_capacity(indices.len());
             for &i in indices.clone() {
                 indexed_crk_col.push(self.crk_col.crk[i]);
                 indexed_crk_v.push(self.crk_col.v[i]);
             }
             t.crk_col.crk     = indexed_crk_col;
-            t.crk_col.crk_idx = AVLCrackerIndex::new();
+            t.crk_col.crk_idx = self.settings.index_kind.new_index();
         } else {
             for &i in indices.clone() {
                 indexed_crk_v.push(self.crk_col.v[i]);
@@ -174,39 +204,52 @@ impl ReCoTable {
         t
     }
 
-    // Returns the elements of T where the cracker columns's value equals X
-    pub fn cracker_select_specific(&mut self, x: i64, col: &str) -> Vec<i64> {
+    // Cracks the column on X and X + 1, returning the positions of the elements equal to X.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        let x1 = match x.successor() {
+            Some(x1) => x1,
+            // Nothing lies above i64::MAX, so its elements are those from it onwards.
+            None => return self.crack_select_range(x, x, true, true),
+        };
+        self.crk_col.merge_pending(x, Some(x1), &self.int_columns);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(x, Some(x1));
+        }
+        self.crk_col.stochastic_crack(x, &mut self.settings);
         // Start with a pointer at both ends of the piece: p_low, p_high
         let mut p_low =  self.crk_col.crk_idx.lower_bound(&x).unwrap_or(0);
-        let mut p_high = self.crk_col.crk_idx.upper_bound(&(x + 1)).unwrap_or(self.count) - 1;
-        if p_high + 1 == 0 { return vec![] };
+        if p_low >= self.crk_col.crk.len() { return 0..0 };
+        let p_end = self.crk_col.crk_idx.upper_bound(&x1).unwrap_or(self.crk_col.crk.len());
+        if p_end == 0 { return 0..0 };
+        let mut p_high = p_end - 1;
 
-        let is_uniform_column_piece = self.crk_col.crk_idx.contains(x) && self.crk_col.crk_idx.contains(x + 1);
+        let is_uniform_column_piece = self.crk_col.crk_idx.contains(x) && self.crk_col.crk_idx.contains(x1);
         if is_uniform_column_piece {
-            return self.get_values(self.crk_col.base_idx[p_low..(p_high + 1)].iter(), col);
+            return p_low..(p_high + 1);
         }
 
         // while p_low is pointing at an element satisfying c_low,  move it forwards
         while self.crk_col.crk[p_low] < x {
             p_low += 1;
-            if p_low == self.count as usize {
-                return vec![]
+            if p_low == self.crk_col.crk.len() {
+                return 0..0
             }
         }
 
         // while p_high is pointing at an element satisfying c_high, move it backwards
         while self.crk_col.crk[p_high] > x {
             if p_high == 0 {
-                return vec![];
+                return 0..0;
             }
             p_high -= 1;
         }
 
         if p_low == p_high {
-            return self.get_values(self.crk_col.base_idx[p_low..(p_high + 1)].iter(), col);
+            return p_low..(p_high + 1);
         }
 
-        let mut p_itr = p_low.clone();
+        let mut p_itr = p_low;
 
         while p_itr <= p_high {
             if self.crk_col.crk[p_itr] < x {
@@ -216,7 +259,7 @@ impl ReCoTable {
                     p_low += 1;
                 }
                 if p_itr < p_low {
-                    p_itr = p_low.clone();
+                    p_itr = p_low;
                 }
             } else if self.crk_col.crk[p_itr] > x {
                 self.crk_col.crk.swap(p_itr, p_high);
@@ -230,13 +273,155 @@ impl ReCoTable {
         }
 
         self.crk_col.crk_idx.insert(x, p_low);
-        self.crk_col.crk_idx.insert(x + 1, p_high + 1);
-        self.get_values(self.crk_col.base_idx[p_low..(p_high + 1)].iter(), col)
+        self.crk_col.crk_idx.insert(x1, p_high + 1);
+        p_low..(p_high + 1)
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        self.crk_col.merge_pending(l, h, &self.int_columns);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(l, h);
+        }
+        self.crk_col.stochastic_crack(l, &mut self.settings);
+        if let Some(h) = h {
+            self.crk_col.stochastic_crack(h, &mut self.settings);
+        }
+        let (p_low, p_high) = self.crk_col.crack_range(l, h, self.settings.parallel_threshold);
+        p_low..p_high
+    }
+
+    // Returns the elements of COL at the positions R of the cracker column, borrowing them from the table.
+    pub fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        self.crk_col.values(r, col, &self.int_columns[col].v)
+    }
+
+    // Returns the positions of the pieces holding the elements in [L, H) if they are small enough to be
+    // scanned rather than cracked.
+    pub fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        self.crk_col.merge_pending(l, h, &self.int_columns);
+        self.crk_col.small_piece(l, h, self.settings.min_piece_size)
+    }
+
+    // Returns the elements of COL at the positions R whose cracker column values lie in [L, H).
+    pub fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        self.crk_col.filtered_values(r, l, h, &self.int_columns[col].v)
+    }
+
+    // Returns statistics on how far the cracker column has been cracked.
+    pub fn stats(&self) -> CrackStats {
+        self.crk_col.stats()
+    }
+
+}
+
+impl Default for ReCoTable {
+    fn default() -> ReCoTable {
+        ReCoTable::new()
+    }
+}
+
+impl CrackedTable for ReCoTable {
+    fn new() -> ReCoTable {
+        ReCoTable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        ReCoTable::new_columns(self, col_names)
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        ReCoTable::insert(self, new_values)
+    }
+
+    fn use_crk_col(&mut self, col_name: &str) {
+        ReCoTable::use_crk_col(self, col_name)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        let mut cols: Vec<&mut IntCol> = self.crk_cols.values_mut().collect();
+        cols.push(&mut self.crk_col);
+        cols
+    }
+
+    fn stats(&self) -> CrackStats {
+        ReCoTable::stats(self)
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        ReCoTable::set_crk_col(self, col_name)
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        ReCoTable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        ReCoTable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        ReCoTable::values(self, r, col)
+    }
+
+    fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        ReCoTable::scan_piece(self, l, h)
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        ReCoTable::filtered_values(self, r, l, h, col)
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(ReCoTable::get_i64_col(self, col).v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> ReCoTable {
+        ReCoTable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        ReCoTable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.int_columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
     }
 
-    // Counts the places where a given column equals a given value
-    pub fn count_col_eq(&self, col: &str, eq: i64) -> i64 {
-        self.get_i64_col(col).v.iter().map(|&x|(x==eq)as i64).fold(0,|sum,x|sum+x) as i64
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_col.crk_idx.pairs()
     }
 }
 
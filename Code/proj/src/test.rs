@@ -1,8 +1,37 @@
 use bit_vec::BitVec;
+use rand::Rng;
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::mem;
+use std::process;
+use std::thread;
 
+use column::Column;
+use column::IntCol;
+use cracked_table::Aggregate;
+use cracked_table::CrackedTable;
+use cracked_table::PivotRng;
+use cracked_table::StochasticMode;
+use cracker_index::IndexKind;
+use encoding::BitPacked;
+use encoding::ForBlock;
+use encoding::RunLengthColumn;
+use decomposed_cracking;
+use nary_cracking;
+use recognitive_compression;
+use compactive_compression;
+use underswap_rle_compression;
 use overswap_rle_compression;
+use hybrid_cracking;
+use concurrent_cracking;
+use adaptive_compression;
+use adaptive_compression::Scheme;
+use packed_cracking;
+use hybrid_cracking::HybridMode;
+use persist::PersistentTable;
 
 fn discover(dst: i64, visited: &mut BitVec, frontier: &mut Vec<i64>) {
     if !visited.get((dst as usize) - 1).unwrap_or(false) && !frontier.contains(&dst) {
@@ -127,4 +156,1337 @@ fn l_side_i_longer_tessellating() {
     let dst = vec![2, 13, 10, 16, 2, 20, 20, 7, 19, 27, 25, 1, 2, 3, 22, 2, 2, 2, 29, 2, 5, 2, 29, 20, 22, 22, 7, 3, 20, 21, 2, 30, 17, 16, 11, 12, 9, 23, 4, 13, 20, 26, 9, 8, 3, 22, 2, 15, 24, 29, 2, 28, 6, 18, 2, 14, 2, 29];
     let start_node = 1;
     assert!(bfs_test(30, src, dst, start_node));
-}
\ No newline at end of file
+}
+
+// Swaps overlapping and disjoint ranges, checking that overlapping ones are swapped element by
+// element from the front.
+#[test]
+fn swap_overlapping_ranges() {
+    let fresh = || {
+        let mut col = IntCol::empty();
+        col.crk = (0..8).collect();
+        col.base_idx = (0..8).collect();
+        col.run_lengths = (0..8).collect();
+        col
+    };
+    let mut col = fresh();
+    col.swap_range(3, 1, 3);
+    assert_eq!(col.crk, vec![0, 3, 4, 5, 2, 1, 6, 7]);
+    assert_eq!(col.base_idx, vec![0, 3, 4, 5, 2, 1, 6, 7]);
+    assert_eq!(col.run_lengths, vec![0, 3, 4, 5, 2, 1, 6, 7]);
+
+    let mut col = fresh();
+    col.swap_range(2, 6, 0);
+    assert_eq!(col.crk, vec![6, 7, 2, 3, 4, 5, 0, 1]);
+    assert_eq!(col.base_idx, vec![6, 7, 2, 3, 4, 5, 0, 1]);
+}
+
+fn sorted(mut v: Vec<i64>) -> Vec<i64> {
+    v.sort();
+    v
+}
+
+// Runs a mix of range and equality selections against a table built from SRC and DST, checking each
+// result against a scan of the original edges.
+pub fn range_test<T, R, S>(mut table: T, src: &[i64], dst: &[i64], mut select_range: R, mut select_specific: S) -> bool
+    where R: FnMut(&mut T, i64, i64, bool, bool) -> Vec<i64>, S: FnMut(&mut T, i64) -> Vec<i64> {
+    let queries = vec![(5, 12, true, true), (0, 3, false, true), (20, 40, true, false), (8, 8, true, true),
+                       (12, 19, false, false), (-3, 0, true, true), (28, 35, true, true), (9, 10, false, false),
+                       (1, 30, true, true), (14, 13, true, true)];
+
+    for (i, &(low, high, inc_l, inc_h)) in queries.iter().enumerate() {
+        let in_range = |s: i64| (low < s || (inc_l && low == s)) && (s < high || (inc_h && s == high));
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| in_range(src[j])).map(|j| dst[j]).collect();
+        let actual = select_range(&mut table, low, high, inc_l, inc_h);
+        if sorted(actual.clone()) != sorted(expected.clone()) {
+            println!("Range ({}, {}, {}, {}): expected {:?}, got {:?}", low, high, inc_l, inc_h, expected, actual);
+            return false;
+        }
+
+        // Interleave equality selections, so that both kinds of crack meet in the cracker index.
+        let x = low + (i as i64 % 3);
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| src[j] == x).map(|j| dst[j]).collect();
+        let actual = select_specific(&mut table, x);
+        if sorted(actual.clone()) != sorted(expected.clone()) {
+            println!("Specific {}: expected {:?}, got {:?}", x, expected, actual);
+            return false;
+        }
+    }
+    true
+}
+
+fn range_test_edges() -> (Vec<i64>, Vec<i64>) {
+    let src = vec![4, 16, 22, 8, 26, 13, 22, 4, 18, 12, 13, 22, 10, 14, 22, 8, 19, 29, 8, 8, 17, 18, 22, 5, 2, 28, 8, 12, 24, 13, 15, 21, 30, 6, 18, 25, 7, 9, 19, 19, 4, 3, 11, 17, 28, 10, 8, 28, 22, 11, 29, 1, 20, 30, 8, 23, 4, 27];
+    let dst = vec![20, 18, 19, 6, 12, 22, 2, 11, 16, 26, 27, 25, 9, 4, 13, 30, 18, 30, 28, 13, 24, 19, 7, 19, 22, 8, 17, 29, 17, 8, 18, 4, 29, 8, 15, 22, 22, 10, 5, 22, 14, 8, 28, 8, 11, 22, 23, 1, 10, 4, 12, 28, 4, 8, 3, 8, 21, 13];
+    (src, dst)
+}
+
+#[test]
+fn decracked_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn reco_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = recognitive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn coco_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn coco_compact_at_first_entry() {
+    // The range select leaves the cracker index at the first entry, so compacting there has nothing below it
+    let src = vec![1, 1, 2, 2, 3, 3, 4, 4];
+    let dst = vec![10, 11, 20, 21, 30, 31, 40, 41];
+    let mut table = compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert_eq!(sorted(table.cracker_select_range(0, 3, true, false, "dst")), vec![10, 11, 20, 21]);
+    assert_eq!(sorted(table.cracker_select_specific(1, "dst")), vec![10, 11]);
+    assert_eq!(sorted(table.cracker_iter_specific(2, "dst").collect()), vec![20, 21]);
+    assert_eq!(table.cracker_aggregate_specific(1, "dst", Aggregate::Sum), Some(21));
+    assert_eq!(sorted(table.cracker_select_range(1, 4, true, true, "dst")), sorted(dst));
+}
+
+#[test]
+fn frame_of_reference_blocks() {
+    let values = vec![1000, 1003, 1255, 1001];
+    let block = ForBlock::encode(&values);
+    assert_eq!(block.decode(), values);
+    assert_eq!(block.get(2), 1255);
+    assert!(block.size() < mem::size_of::<ForBlock>() + values.len() * mem::size_of::<usize>());
+    assert_eq!(block.push(70000).decode(), vec![1000, 1003, 1255, 1001, 70000]);
+    assert_eq!(block.without(1003).unwrap().decode(), vec![1000, 1255, 1001]);
+    assert_eq!(block.without(7), None);
+    assert!(ForBlock::encode(&[]).is_empty());
+}
+
+#[test]
+fn coco_encoded_base_indices() {
+    let src: Vec<i64> = (0..2048).map(|i| (i * 7) % 32).collect();
+    let dst: Vec<i64> = (0..2048).map(|i| i % 29).collect();
+    let mut table = compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let plain_size = table.base_idx_size();
+    for x in 0..32 {
+        table.cracker_select_specific(x, "dst");
+    }
+    // Every piece of equal values has been compacted, so its base indices are held in a frame.
+    assert!(table.base_idx_size() * 2 < plain_size);
+
+    let mut new_values = HashMap::new();
+    new_values.insert("src", vec![8, 31]);
+    new_values.insert("dst", vec![5, 6]);
+    table.insert(&mut new_values);
+    table.delete_where("dst", 22);
+    let kept: Vec<usize> = (0..src.len()).filter(|&j| dst[j] != 22).collect();
+    let mut kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let mut kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+    kept_src.extend(vec![8, 31]);
+    kept_dst.extend(vec![5, 6]);
+    assert!(range_test(table, &kept_src, &kept_dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn underswap_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn overswap_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+// Writes, swaps, inserts and removes elements of a run-length encoded column, checking it against a
+// plain copy after each.
+#[test]
+fn run_length_columns() {
+    let mut rng = PivotRng::new(42);
+    let mut plain: Vec<i64> = (0..200).map(|i| i / 10).collect();
+    let mut encoded = RunLengthColumn::encode(&plain);
+    assert_eq!(encoded.runs(), 20);
+    for _ in 0..2000 {
+        let p = rng.gen_range(0, plain.len());
+        match rng.gen_range(0, 4) {
+            0 => {
+                let values: Vec<i64> = (0..rng.gen_range(1, 6)).map(|_| rng.gen_range(0, 3)).collect();
+                let p = p.min(plain.len() - values.len());
+                plain[p..(p + values.len())].copy_from_slice(&values);
+                encoded.write(p, &values);
+            },
+            1 => {
+                let (a, b) = (rng.gen_range(0, plain.len()), rng.gen_range(0, plain.len()));
+                let l = rng.gen_range(0, plain.len() - a.max(b) + 1).min(8);
+                for i in 0..l {
+                    plain.swap(a + i, b + i);
+                }
+                encoded.swap_range(l, a, b);
+            },
+            2 => {
+                let x = rng.gen_range(0, 3);
+                let p = rng.gen_range(0, plain.len() + 1);
+                plain.insert(p, x);
+                encoded.insert(p, x);
+            },
+            _ => {
+                plain.remove(p);
+                encoded.remove(p);
+            },
+        }
+        assert_eq!(encoded.decode(0..encoded.len()), plain);
+        // Runs side by side always hold different values.
+        assert_eq!(encoded, RunLengthColumn::encode(&plain));
+    }
+}
+
+// Checks selections against the RLE map of a multigraph whose duplicate edges leave long runs of
+// destinations once the sources are cracked, and that the runs take up less than the plain column.
+fn rle_map_test<T, M, Z, R, S>(make_table: M, map_size: Z, mut select_range: R, mut select_specific: S) -> bool
+    where M: Fn(Vec<i64>, Vec<i64>) -> T, Z: Fn(&T) -> usize, R: FnMut(&mut T, i64, i64, bool, bool) -> Vec<i64>,
+          S: FnMut(&mut T, i64) -> Vec<i64> {
+    let src: Vec<i64> = (0..2048).map(|i| (i * 7) % 32).collect();
+    let dst: Vec<i64> = src.iter().enumerate().map(|(i, &s)| if i % 50 == 0 { s + 1 } else { s * 3 % 29 }).collect();
+    let mut table = make_table(src.clone(), dst.clone());
+    for x in 0..32 {
+        let mut expected: Vec<i64> = (0..src.len()).filter(|&j| src[j] == x).map(|j| dst[j]).collect();
+        let mut actual = select_specific(&mut table, x);
+        expected.sort();
+        actual.sort();
+        if actual != expected {
+            println!("Specific {}: expected {:?}, got {:?}", x, expected, actual);
+            return false;
+        }
+    }
+    if map_size(&table) * 4 >= src.len() * mem::size_of::<i64>() {
+        println!("RLE map takes up {} bytes", map_size(&table));
+        return false;
+    }
+    let table = make_table(src.clone(), dst.clone());
+    range_test(table, &src, &dst, &mut select_range, &mut select_specific)
+}
+
+#[test]
+fn rle_select_map() {
+    assert!(rle_map_test(|src, dst| {
+                             let mut t = underswap_rle_compression::from_adjacency_vectors(src, dst, "src");
+                             t.add_rle_map("dst");
+                             t
+                         },
+                         |t| t.crk_col.rle_maps["dst"].size(),
+                         |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                         |t, x| t.cracker_select_specific(x, "dst")));
+    assert!(rle_map_test(|src, dst| {
+                             let mut t = overswap_rle_compression::from_adjacency_vectors(src, dst, "src");
+                             t.add_rle_map("dst");
+                             t
+                         },
+                         |t| t.crk_col.rle_maps["dst"].size(),
+                         |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                         |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn nary_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = nary_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn nary_select_batch() {
+    let (src, dst) = range_test_edges();
+    let mut table = nary_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let batches = vec![vec![8, 22, 3], vec![4, 5, 6, 7, 30, 0], vec![22, 13, 14, 31, 2], vec![1, 8, 9, 10, 11, 12]];
+    for xs in batches {
+        let results = table.cracker_select_batch(&xs, "dst");
+        for (x, actual) in xs.iter().zip(results) {
+            let expected: Vec<i64> = (0..src.len()).filter(|&j| src[j] == *x).map(|j| dst[j]).collect();
+            assert_eq!(sorted(actual), sorted(expected));
+        }
+    }
+}
+
+fn cracked_table_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    range_test(table, &src, &dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn cracked_table_trait() {
+    assert!(cracked_table_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(cracked_table_test::<nary_cracking::NaryCrackedTable>());
+    assert!(cracked_table_test::<recognitive_compression::ReCoTable>());
+    assert!(cracked_table_test::<compactive_compression::CoCoTable>());
+    assert!(cracked_table_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(cracked_table_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(cracked_table_test::<hybrid_cracking::HybridTable>());
+}
+
+// Sweeps equality selections over every vertex in order, then runs the mixed range test, with
+// stochastic cracking in the given mode.
+fn stochastic_test<T: CrackedTable>(mode: StochasticMode) -> bool {
+    let (src, dst) = range_test_edges();
+    let mut table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.set_stochastic_mode(mode);
+    table.set_seed(42);
+    for x in 0..32 {
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| src[j] == x).map(|j| dst[j]).collect();
+        let actual = table.cracker_select_specific(x, "dst");
+        if sorted(actual.clone()) != sorted(expected.clone()) {
+            println!("Sweep {}: expected {:?}, got {:?}", x, expected, actual);
+            return false;
+        }
+    }
+
+    let mut table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.set_stochastic_mode(mode);
+    table.set_seed(42);
+    range_test(table, &src, &dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn stochastic_cracking() {
+    for &mode in &[StochasticMode::DD1R, StochasticMode::DDR(4), StochasticMode::DDC(4)] {
+        assert!(stochastic_test::<decomposed_cracking::DeCrackedTable>(mode));
+        assert!(stochastic_test::<nary_cracking::NaryCrackedTable>(mode));
+        assert!(stochastic_test::<recognitive_compression::ReCoTable>(mode));
+        assert!(stochastic_test::<compactive_compression::CoCoTable>(mode));
+        assert!(stochastic_test::<underswap_rle_compression::UnderswapRLETable>(mode));
+        assert!(stochastic_test::<overswap_rle_compression::OverswapRLETable>(mode));
+        assert!(stochastic_test::<hybrid_cracking::HybridTable>(mode));
+    }
+}
+
+#[test]
+fn decracked_select_map() {
+    let (src, dst) = range_test_edges();
+    let table = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_map(x, "dst").to_vec()));
+}
+
+#[test]
+fn overswap_select_map() {
+    let (src, dst) = range_test_edges();
+    let table = overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_map(x, "dst").to_vec()));
+}
+
+// Inserts half of the edges after the table has been cracked on the other half, then checks that
+// queries see every edge.
+fn insert_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = T::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    table.cracker_select_range(5, 20, true, false, "dst");
+    table.cracker_select_specific(8, "dst");
+    table.cracker_select_specific(13, "dst");
+    table.insert(&mut map!{"src" => src[half..].to_vec(), "dst" => dst[half..].to_vec()});
+    if !range_test(table, &src, &dst,
+                   |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                   |t, x| t.cracker_select_specific(x, "dst")) {
+        return false;
+    }
+
+    // A table cracked before it held any rows.
+    let mut table = T::from_adjacency_vectors(vec![], vec![], "src");
+    table.insert(&mut map!{"src" => src.clone(), "dst" => dst.clone()});
+    range_test(table, &src, &dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn insert_after_cracking() {
+    assert!(insert_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(insert_test::<nary_cracking::NaryCrackedTable>());
+    assert!(insert_test::<recognitive_compression::ReCoTable>());
+    assert!(insert_test::<compactive_compression::CoCoTable>());
+    assert!(insert_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(insert_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(insert_test::<hybrid_cracking::HybridTable>());
+}
+
+// Decompresses a cracked table with rows inserted and deleted since it was cracked, then checks that
+// the rows come out in cracked order, that the cracks carry over, and that the new table answers
+// queries on the remaining edges.
+fn decompress_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = T::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    table.cracker_select_range(5, 20, true, false, "dst");
+    table.cracker_select_specific(8, "dst");
+    table.cracker_select_specific(13, "dst");
+    table.insert(&mut map!{"src" => src[half..].to_vec(), "dst" => dst[half..].to_vec()});
+    table.delete_where("dst", 22);
+    let kept: Vec<usize> = (0..src.len()).filter(|&j| dst[j] != 22).collect();
+    let kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+
+    let columns = table.cracked_columns();
+    let mut rows: Vec<(i64, i64)> = columns["src"].iter().cloned().zip(columns["dst"].iter().cloned()).collect();
+    rows.sort();
+    let mut expected: Vec<(i64, i64)> = kept_src.iter().cloned().zip(kept_dst.iter().cloned()).collect();
+    expected.sort();
+    if rows != expected {
+        println!("Expected rows {:?}, got {:?}", expected, rows);
+        return false;
+    }
+    for (k, p) in table.cracks() {
+        let (lower, upper) = columns["src"].split_at(p.min(columns["src"].len()));
+        if lower.iter().any(|&s| s >= k) || upper.iter().any(|&s| s < k) {
+            println!("Crack {} at {} does not hold in {:?}", k, p, columns["src"]);
+            return false;
+        }
+    }
+
+    let decompressed = table.decompress();
+    if decompressed.crk_col.crk != columns["src"] || decompressed.crk_col.crk_idx.pairs() != table.cracks() {
+        println!("Decompressed cracker column differs");
+        return false;
+    }
+    range_test(decompressed, &kept_src, &kept_dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn decompress_to_plain_table() {
+    assert!(decompress_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(decompress_test::<nary_cracking::NaryCrackedTable>());
+    assert!(decompress_test::<recognitive_compression::ReCoTable>());
+    assert!(decompress_test::<compactive_compression::CoCoTable>());
+    assert!(decompress_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(decompress_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(decompress_test::<hybrid_cracking::HybridTable>());
+    assert!(decompress_test::<packed_cracking::PackedTable>());
+    assert!(decompress_test::<adaptive_compression::AdaptiveTable>());
+}
+
+// Deletes rows from a cracked table, by value and by base index, including rows still waiting to be
+// merged, then checks that queries see only the remaining edges.
+fn delete_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = T::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    table.cracker_select_range(5, 20, true, false, "dst");
+    table.delete_where("src", 8);
+    table.cracker_select_specific(13, "dst");
+    table.insert(&mut map!{"src" => src[half..].to_vec(), "dst" => dst[half..].to_vec()});
+    table.delete_where("dst", 22);
+    table.delete_indices([0, 5, half + 1].iter());
+
+    let kept: Vec<usize> = (0..src.len())
+        .filter(|&j| (src[j] != 8 || j >= half) && dst[j] != 22 && j != 0 && j != 5 && j != half + 1)
+        .collect();
+    if table.count_col_eq("dst", 22) != 0
+        || table.count_col_eq("src", 4) != kept.iter().filter(|&&j| src[j] == 4).count() as i64 {
+        return false;
+    }
+    let kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+    range_test(table, &kept_src, &kept_dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn delete_after_cracking() {
+    assert!(delete_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(delete_test::<nary_cracking::NaryCrackedTable>());
+    assert!(delete_test::<recognitive_compression::ReCoTable>());
+    assert!(delete_test::<compactive_compression::CoCoTable>());
+    assert!(delete_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(delete_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(delete_test::<hybrid_cracking::HybridTable>());
+    assert!(delete_test::<packed_cracking::PackedTable>());
+    assert!(delete_test::<adaptive_compression::AdaptiveTable>());
+}
+
+// Deletes a row from a run which an earlier query recognised, then selects around it, so that the
+// run markers left beside the removed row must not be trusted.
+fn rle_delete_in_run_test<T: CrackedTable>() -> bool {
+    let mut table = T::from_adjacency_vectors(vec![3, 3, 3, 4], vec![6, 6, 7, 7], "src");
+    table.cracker_select_specific(4, "dst");
+    table.delete_indices([1].iter());
+    table.cracker_select_specific(4, "dst") == vec![7]
+        && table.cracker_select_specific(1, "dst").is_empty()
+        && table.cracker_select_specific(3, "dst").len() == 2
+}
+
+#[test]
+fn rle_delete_in_run() {
+    assert!(rle_delete_in_run_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(rle_delete_in_run_test::<overswap_rle_compression::OverswapRLETable>());
+}
+
+// Merges inserts and deletes into a column cracked into many pieces, with a cracker map on dst, then
+// checks that every crack still holds and that the map moved in step with the cracker column.
+#[test]
+fn ripple_merge() {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = decomposed_cracking::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    for x in [3, 8, 12, 17, 22, 26].iter() {
+        table.cracker_select_map(*x, "dst");
+    }
+    table.insert(&mut map!{"src".to_string() => src[half..].to_vec(), "dst".to_string() => dst[half..].to_vec()});
+    table.delete_indices([1, 4, half + 2].iter());
+    table.cracker_select_range(0, 40, true, true, "dst");
+
+    let crk = &table.crk_col.crk;
+    assert_eq!(crk.len(), src.len() - 3);
+    for (k, p) in table.cracks() {
+        assert!(crk[..p].iter().all(|&c| c < k) && crk[p..].iter().all(|&c| c >= k));
+    }
+    let kept: Vec<usize> = (0..src.len()).filter(|&j| j != 1 && j != 4 && j != half + 2).collect();
+    let kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+    assert!(range_test(table, &kept_src, &kept_dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_map(x, "dst").to_vec()));
+}
+
+// Runs the mixed range test on a hybrid table in the given mode, split into runs small enough that
+// every query merges from several of them, with rows inserted and deleted after the first query.
+fn hybrid_test(mode: HybridMode) -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = hybrid_cracking::HybridTable::with_mode(mode, 5);
+    table.new_columns(vec!["src".to_string(), "dst".to_string()]);
+    table.insert(&mut map!{"src".to_string() => src[..half].to_vec(), "dst".to_string() => dst[..half].to_vec()});
+    table.set_crk_col("src".to_string());
+    table.cracker_select_range(10, 20, true, false, "dst");
+    table.insert(&mut map!{"src".to_string() => src[half..].to_vec(), "dst".to_string() => dst[half..].to_vec()});
+    table.delete_where("dst", 22);
+
+    let kept: Vec<usize> = (0..src.len()).filter(|&j| dst[j] != 22).collect();
+    let kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+    range_test(table, &kept_src, &kept_dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn hybrid_modes() {
+    assert!(hybrid_test(HybridMode::CrackCrack));
+    assert!(hybrid_test(HybridMode::CrackSort));
+    assert!(hybrid_test(HybridMode::RadixSort));
+}
+
+// Cracks one table on both src and dst, switching between them around inserts and deletes, then
+// checks queries on each.
+fn multi_crk_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = T::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    table.cracker_select_range(5, 20, true, false, "dst");
+    table.use_crk_col("dst");
+    table.cracker_select_specific(8, "src");
+    table.insert(&mut map!{"src" => src[half..].to_vec(), "dst" => dst[half..].to_vec()});
+    table.delete_where("src", 13);
+
+    let kept: Vec<usize> = (0..src.len()).filter(|&j| src[j] != 13).collect();
+    let kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+    table.use_crk_col("src");
+    for &x in &[8, 13, 22] {
+        let expected: Vec<i64> = (0..kept.len()).filter(|&j| kept_src[j] == x).map(|j| kept_dst[j]).collect();
+        if sorted(table.cracker_select_specific(x, "dst")) != sorted(expected) {
+            return false;
+        }
+    }
+    range_test(table, &kept_dst, &kept_src,
+               |t, l, h, il, ih| {
+                   t.use_crk_col("src");
+                   t.cracker_select_range(l, h, il, ih, "dst");
+                   t.use_crk_col("dst");
+                   t.cracker_select_range(l, h, il, ih, "src")
+               },
+               |t, x| {
+                   t.use_crk_col("dst");
+                   t.cracker_select_specific(x, "src")
+               })
+}
+
+#[test]
+fn multiple_crk_cols() {
+    assert!(multi_crk_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(multi_crk_test::<nary_cracking::NaryCrackedTable>());
+    assert!(multi_crk_test::<recognitive_compression::ReCoTable>());
+    assert!(multi_crk_test::<compactive_compression::CoCoTable>());
+    assert!(multi_crk_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(multi_crk_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(multi_crk_test::<hybrid_cracking::HybridTable>());
+}
+
+// Saves a table partway through cracking, inserting and deleting, then checks that the reloaded
+// table returns the same elements in the same order as the original.
+fn persist_test<T: CrackedTable + PersistentTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = T::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    table.set_stochastic_mode(StochasticMode::DDR(4));
+    table.set_seed(7);
+    table.set_cracker_index(IndexKind::BPlusTree);
+    table.set_min_piece_size(2);
+    table.cracker_select_range(5, 20, true, false, "dst");
+    table.use_crk_col("dst");
+    table.cracker_select_specific(8, "src");
+    table.use_crk_col("src");
+    table.insert(&mut map!{"src" => src[half..].to_vec(), "dst" => dst[half..].to_vec()});
+    table.delete_where("src", 13);
+    table.cracker_select_specific(22, "dst");
+
+    let path = env::temp_dir().join(format!("persist_test_{}_{}", T::type_name(), process::id()));
+    table.save(&path).unwrap();
+    let mut loaded = T::load(&path).unwrap();
+    let wrong_type = if T::type_name() == "DeCrackedTable" {
+        nary_cracking::NaryCrackedTable::load(&path).err()
+    } else {
+        decomposed_cracking::DeCrackedTable::load(&path).err()
+    };
+    ::std::fs::remove_file(&path).unwrap();
+    if wrong_type.map(|e| e.kind()) != Some(io::ErrorKind::InvalidData) {
+        return false;
+    }
+    // The settings come back as they were, so the stochastic cracks below draw the same pivots.
+    if loaded.settings() != table.settings() {
+        println!("Settings differ after reloading");
+        return false;
+    }
+
+    for &(l, h) in &[(0, 10), (4, 5), (12, 30), (20, 22), (-5, 40)] {
+        if table.cracker_select_range(l, h, true, false, "dst") != loaded.cracker_select_range(l, h, true, false, "dst") {
+            println!("Range ({}, {}) differs after reloading", l, h);
+            return false;
+        }
+        table.use_crk_col("dst");
+        loaded.use_crk_col("dst");
+        if table.cracker_select_specific(l, "src") != loaded.cracker_select_specific(l, "src") {
+            println!("Specific {} on dst differs after reloading", l);
+            return false;
+        }
+        table.use_crk_col("src");
+        loaded.use_crk_col("src");
+    }
+    true
+}
+
+#[test]
+fn persist_and_reload() {
+    assert!(persist_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(persist_test::<nary_cracking::NaryCrackedTable>());
+    assert!(persist_test::<recognitive_compression::ReCoTable>());
+    assert!(persist_test::<compactive_compression::CoCoTable>());
+    assert!(persist_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(persist_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(persist_test::<hybrid_cracking::HybridTable>());
+    assert!(persist_test::<packed_cracking::PackedTable>());
+    assert!(persist_test::<adaptive_compression::AdaptiveTable>());
+}
+
+// Saves a concurrent table after it has been cracked into pieces, then checks that the reloaded
+// table has the same pieces and answers queries as the original does.
+#[test]
+fn persist_and_reload_concurrent() {
+    let (src, dst) = range_test_edges();
+    let table = concurrent_cracking::from_adjacency_vectors(src, dst, "src");
+    for &(l, h) in &[(5, 12), (0, 3), (20, 40), (8, 8)] {
+        table.cracker_select_range(l, h, true, false, "dst");
+    }
+
+    let path = env::temp_dir().join(format!("persist_test_ConcurrentTable_{}", process::id()));
+    table.save(&path).unwrap();
+    let loaded = concurrent_cracking::ConcurrentTable::load(&path).unwrap();
+    ::std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.piece_count(), table.piece_count());
+    for &(l, h) in &[(0, 10), (4, 5), (12, 30), (20, 22), (-5, 40)] {
+        assert_eq!(loaded.cracker_select_range(l, h, true, false, "dst"), table.cracker_select_range(l, h, true, false, "dst"));
+        assert_eq!(loaded.cracker_select_specific(l, "dst"), table.cracker_select_specific(l, "dst"));
+    }
+}
+
+// Runs the same queries, each thread starting at a different one, from several threads against one
+// concurrent table.
+#[test]
+fn concurrent_queries() {
+    let (src, dst) = range_test_edges();
+    let table = concurrent_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let queries = vec![(5, 12, true, true), (0, 3, false, true), (20, 40, true, false), (8, 8, true, true),
+                       (12, 19, false, false), (-3, 0, true, true), (28, 35, true, true), (9, 10, false, false),
+                       (1, 30, true, true), (14, 13, true, true)];
+
+    let passed: Vec<bool> = thread::scope(|s| {
+        let handles: Vec<_> = (0..8).map(|t| {
+            let (table, queries, src, dst) = (&table, &queries, &src, &dst);
+            s.spawn(move || {
+                for k in 0..(3 * queries.len()) {
+                    let (low, high, inc_l, inc_h) = queries[(t + k) % queries.len()];
+                    let in_range = |s: i64| (low < s || (inc_l && low == s)) && (s < high || (inc_h && s == high));
+                    let expected: Vec<i64> = (0..src.len()).filter(|&j| in_range(src[j])).map(|j| dst[j]).collect();
+                    if sorted(table.cracker_select_range(low, high, inc_l, inc_h, "dst")) != sorted(expected) {
+                        return false;
+                    }
+
+                    let x = low + (k as i64 % 3);
+                    let expected: Vec<i64> = (0..src.len()).filter(|&j| src[j] == x).map(|j| dst[j]).collect();
+                    if sorted(table.cracker_select_specific(x, "dst")) != sorted(expected) {
+                        return false;
+                    }
+                }
+                true
+            })
+        }).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert!(passed.iter().all(|&p| p));
+    assert!(table.piece_count() > 1);
+}
+
+#[test]
+fn bit_packed_columns() {
+    let mut packed = BitPacked::pack(&[100, 103, 164, 101]);
+    assert_eq!(packed.width(), 7);
+    assert_eq!(packed.unpack(), vec![100, 103, 164, 101]);
+    packed.swap(0, 2);
+    packed.set(1, 150);
+    assert_eq!(packed.unpack(), vec![164, 150, 100, 101]);
+
+    // Values which do not fit repack the column, wider or from a smaller reference.
+    packed.extend(&[120, 5000]);
+    assert_eq!(packed.width(), 13);
+    packed.extend(&[-7]);
+    assert_eq!(packed.unpack(), vec![164, 150, 100, 101, 120, 5000, -7]);
+
+    let extremes = vec![i64::MIN, i64::MAX, 0, -1];
+    assert_eq!(BitPacked::pack(&extremes).unpack(), extremes);
+    assert_eq!(BitPacked::pack(&[9, 9, 9]).unpack(), vec![9, 9, 9]);
+
+    let ids: Vec<i64> = (0..1000).map(|i| (i * 7919) % 1024).collect();
+    let packed = BitPacked::pack(&ids);
+    assert_eq!(packed.unpack(), ids);
+    assert!(packed.size() * 6 < ids.len() * mem::size_of::<i64>());
+}
+
+#[test]
+fn packed_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = packed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+
+    // Inserted rows needing more bits than the table's columns are packed in are still found.
+    let mut table = packed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.cracker_select_range(5, 12, true, true, "dst");
+    table.insert(&mut map!{"src".to_string() => vec![8, 3000], "dst".to_string() => vec![-40, 7]});
+    let (mut src, mut dst) = (src, dst);
+    src.extend(vec![8, 3000]);
+    dst.extend(vec![-40, 7]);
+    assert_eq!(table.cracker_select_specific(3000, "dst"), vec![7]);
+    // The cracks made before the insert still hold.
+    let crk = table.crk.unpack();
+    let cracks = table.crk_idx.pairs();
+    assert!(cracks.iter().any(|&(k, _)| k == 5) && cracks.iter().any(|&(k, _)| k == 13));
+    for (k, p) in cracks {
+        assert!(crk[..p].iter().all(|&c| c < k) && crk[p..].iter().all(|&c| c >= k));
+    }
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn adaptive_select_range() {
+    let (src, dst) = range_test_edges();
+    let table = adaptive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn adaptive_schemes() {
+    // Vertex 3 is a hub, vertices 10 to 19 have their edges listed together, and the rest are a long
+    // tail of single edges.
+    let mut src: Vec<i64> = vec![3; 40];
+    src.extend((10..20).flat_map(|v| vec![v; 6]));
+    src.extend(20..60);
+    let mut dst: Vec<i64> = (0..(src.len() as i64)).collect();
+    let mut table = adaptive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.hot_queries = 2;
+
+    let expected = |src: &Vec<i64>, dst: &Vec<i64>, l: i64, h: i64| -> Vec<i64> {
+        (0..src.len()).filter(|&j| l <= src[j] && src[j] < h).map(|j| dst[j]).collect()
+    };
+    for _ in 0..2 {
+        assert_eq!(sorted(table.cracker_select_specific(3, "dst")), expected(&src, &dst, 3, 4));
+        assert_eq!(sorted(table.cracker_select_range(10, 20, true, false, "dst")), expected(&src, &dst, 10, 20));
+        assert_eq!(sorted(table.cracker_select_specific(42, "dst")), expected(&src, &dst, 42, 43));
+    }
+    assert_eq!(table.scheme_of(3), Scheme::Compacted);
+    assert_eq!(table.scheme_of(15), Scheme::RLE);
+    assert_eq!(table.scheme_of(42), Scheme::Plain);
+    assert_eq!(table.schemes().1, 1);
+    assert!(table.stats().avg_run_length.unwrap() >= 4.0);
+
+    // Cracking an RLE piece moves whole runs, and rows inserted go into their piece's scheme.
+    table.insert(&mut map!{"src".to_string() => vec![3, 14, 14, 42], "dst".to_string() => vec![100, 101, 102, 103]});
+    src.extend(vec![3, 14, 14, 42]);
+    dst.extend(vec![100, 101, 102, 103]);
+    for x in 10..20 {
+        assert_eq!(sorted(table.cracker_select_specific(x, "dst")), expected(&src, &dst, x, x + 1));
+    }
+    assert_eq!(sorted(table.cracker_select_specific(3, "dst")), expected(&src, &dst, 3, 4));
+    // Vertex 14 now has enough edges for its piece, cracked out of the RLE piece, to be compacted.
+    assert_eq!(table.scheme_of(12), Scheme::RLE);
+    assert_eq!(table.scheme_of(14), Scheme::Compacted);
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn adaptive_insert_into_compacted() {
+    // The range select compacts [5, 11), which only holds 7, so a row of 8 must not be taken for a 7.
+    let mut src: Vec<i64> = vec![7; 8];
+    src.extend(vec![1, 20]);
+    let mut dst: Vec<i64> = (0..(src.len() as i64)).collect();
+    let mut table = adaptive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    assert_eq!(sorted(table.cracker_select_range(5, 10, true, true, "dst")), (0..8).collect::<Vec<i64>>());
+    assert_eq!(table.scheme_of(7), Scheme::Compacted);
+    table.insert(&mut map!{"src".to_string() => vec![8, 7], "dst".to_string() => vec![99, 100]});
+    src.extend(vec![8, 7]);
+    dst.extend(vec![99, 100]);
+    assert_eq!(table.cracker_select_specific(8, "dst"), vec![99]);
+    assert_eq!(sorted(table.cracker_select_specific(7, "dst")), sorted((0..8).chain(vec![100]).collect()));
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn parallel_cracking() {
+    // A tiny threshold sends all but the smallest pieces through the parallel kernels.
+    let (src, dst) = range_test_edges();
+    let mut table = decomposed_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.settings.parallel_threshold = 4;
+    assert!(range_test(table.clone(), &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_map(x, "dst").to_vec()));
+
+    let mut table = underswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.settings.parallel_threshold = 4;
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_specific(x, "dst")));
+
+    let mut table = overswap_rle_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    table.settings.parallel_threshold = 4;
+    assert!(range_test(table, &src, &dst,
+                       |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                       |t, x| t.cracker_select_map(x, "dst").to_vec()));
+}
+
+#[test]
+fn generic_columns() {
+    let (src, dst) = range_test_edges();
+    let mut table = decomposed_cracking::from_adjacency_vectors(src.iter().map(|&s| s as u32).collect(),
+                                                                dst.iter().map(|&d| d as u32).collect(), "src");
+    for &(low, high, inc_l, inc_h) in [(5, 12, true, true), (0, 3, false, true), (20, 40, true, false), (12, 19, false, false)].iter() {
+        let in_range = |s: i64| (low < s || (inc_l && low == s)) && (s < high || (inc_h && s == high));
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| in_range(src[j])).map(|j| dst[j]).collect();
+        let actual = table.cracker_select_range(low as u32, high as u32, inc_l, inc_h, "dst");
+        assert_eq!(sorted(actual.iter().map(|&d| d as i64).collect()), sorted(expected));
+
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| src[j] == high).map(|j| dst[j]).collect();
+        let actual = table.cracker_select_specific(high as u32, "dst");
+        assert_eq!(sorted(actual.iter().map(|&d| d as i64).collect()), sorted(expected));
+    }
+
+    // Exclusive bounds on a float column stop just short of the bound itself.
+    let weights: Vec<f64> = src.iter().map(|&s| s as f64 / 4.0).collect();
+    let mut table = decomposed_cracking::from_adjacency_vectors(weights.clone(), dst.iter().map(|&d| d as f64).collect(), "src");
+    table.settings.parallel_threshold = 4;
+    for &(low, high, inc_l, inc_h) in [(1.25, 3.0, true, true), (0.5, 2.0, false, false), (2.0, 2.0, true, true), (5.5, 9.0, false, true)].iter() {
+        let in_range = |w: f64| (low < w || (inc_l && low == w)) && (w < high || (inc_h && w == high));
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| in_range(weights[j])).map(|j| dst[j]).collect();
+        let actual = table.cracker_select_range(low, high, inc_l, inc_h, "dst");
+        assert_eq!(sorted(actual.iter().map(|&d| d as i64).collect()), sorted(expected));
+    }
+}
+
+// Ranges which take in the greatest value of a column's type are left open above rather than
+// overflowing.
+#[test]
+fn greatest_values() {
+    let ids = |v: Vec<u32>| sorted(v.into_iter().map(|d| d as i64).collect());
+    let keys = vec![u32::MAX, 3, u32::MAX, 7, 0, u32::MAX - 1];
+    let mut table = decomposed_cracking::from_adjacency_vectors(keys, (0..6).collect(), "src");
+    assert_eq!(ids(table.cracker_select_specific(u32::MAX, "dst")), vec![0, 2]);
+    assert_eq!(ids(table.cracker_select_range(7, u32::MAX, true, true, "dst")), vec![0, 2, 3, 5]);
+    assert_eq!(ids(table.cracker_select_range(u32::MAX - 1, u32::MAX, false, true, "dst")), vec![0, 2]);
+    assert_eq!(ids(table.cracker_select_range(u32::MAX, u32::MAX, false, true, "dst")), vec![]);
+    assert_eq!(ids(table.cracker_select_specific(3, "dst")), vec![1]);
+
+    let weights = vec![f64::INFINITY, 1.5, f64::MAX, f64::INFINITY];
+    let mut table = decomposed_cracking::from_adjacency_vectors(weights, vec![0.0, 1.0, 2.0, 3.0], "src");
+    assert_eq!(table.cracker_select_specific(f64::INFINITY, "dst").len(), 2);
+    assert_eq!(table.cracker_select_range(1.5, f64::INFINITY, false, true, "dst").len(), 3);
+    assert_eq!(table.cracker_select_specific(f64::MAX, "dst"), vec![2.0]);
+}
+
+// Selects at the edges of the i64 range, where a bound one past the value asked for does not exist.
+fn extreme_bounds_test<T, R, S>(mut table: T, mut select_range: R, mut select_specific: S) -> bool
+    where R: FnMut(&mut T, i64, i64, bool, bool) -> Vec<i64>, S: FnMut(&mut T, i64) -> Vec<i64> {
+    let src = extreme_edges();
+    let queries = [(7, i64::MAX, true, true), (i64::MAX - 1, i64::MAX, false, true), (i64::MAX, i64::MAX, false, true),
+                       (i64::MIN, i64::MAX, true, true), (i64::MIN, 3, true, false), (i64::MAX, i64::MAX, true, true)];
+    for &(low, high, inc_l, inc_h) in queries.iter() {
+        let in_range = |s: i64| (low < s || (inc_l && low == s)) && (s < high || (inc_h && s == high));
+        let expected: Vec<i64> = (0..src.len()).filter(|&j| in_range(src[j])).map(|j| j as i64).collect();
+        let actual = select_range(&mut table, low, high, inc_l, inc_h);
+        if sorted(actual.clone()) != expected {
+            println!("Range ({}, {}, {}, {}): expected {:?}, got {:?}", low, high, inc_l, inc_h, expected, actual);
+            return false;
+        }
+    }
+    sorted(select_specific(&mut table, i64::MAX)) == vec![0, 2] && select_specific(&mut table, 3) == vec![1]
+        && select_specific(&mut table, i64::MIN) == vec![3]
+}
+
+fn extreme_edges() -> Vec<i64> {
+    vec![i64::MAX, 3, i64::MAX, i64::MIN, 7, i64::MAX - 1]
+}
+
+fn extreme_bounds_trait_test<T: CrackedTable>() -> bool {
+    let mut table = T::from_adjacency_vectors(extreme_edges(), (0..6).collect(), "src");
+    if table.cracker_aggregate_range(i64::MAX - 1, i64::MAX, true, true, "dst", Aggregate::Count) != Some(3) {
+        return false;
+    }
+    if sorted(table.cracker_iter_range(i64::MAX, i64::MAX, true, true, "dst").collect()) != vec![0, 2] {
+        return false;
+    }
+    if !extreme_bounds_test(table,
+                            |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                            |t, x| t.cracker_select_specific(x, "dst")) {
+        return false;
+    }
+
+    // Rows holding i64::MAX are kept when the table is decompressed.
+    let mut table = T::from_adjacency_vectors(extreme_edges(), (0..6).collect(), "src");
+    table.cracker_select_range(3, 7, true, true, "dst");
+    if sorted(table.cracked_columns()["src"].clone()) != sorted(extreme_edges()) {
+        return false;
+    }
+    sorted(table.decompress().cracker_select_specific(i64::MAX, "dst")) == vec![0, 2]
+}
+
+#[test]
+fn extreme_bounds() {
+    assert!(extreme_bounds_trait_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(extreme_bounds_trait_test::<nary_cracking::NaryCrackedTable>());
+    assert!(extreme_bounds_trait_test::<recognitive_compression::ReCoTable>());
+    assert!(extreme_bounds_trait_test::<compactive_compression::CoCoTable>());
+    assert!(extreme_bounds_trait_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(extreme_bounds_trait_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(extreme_bounds_trait_test::<hybrid_cracking::HybridTable>());
+    assert!(extreme_bounds_trait_test::<packed_cracking::PackedTable>());
+    assert!(extreme_bounds_trait_test::<adaptive_compression::AdaptiveTable>());
+    for &mode in [HybridMode::CrackCrack, HybridMode::CrackSort, HybridMode::RadixSort].iter() {
+        let table = hybrid_cracking::from_adjacency_vectors(extreme_edges(), (0..6).collect(), "src", mode);
+        assert!(extreme_bounds_test(table,
+                                    |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                                    |t, x| t.cracker_select_specific(x, "dst")));
+    }
+
+    let table = concurrent_cracking::from_adjacency_vectors(extreme_edges(), (0..6).collect(), "src");
+    assert!(extreme_bounds_test(table,
+                                |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+                                |t, x| t.cracker_select_specific(x, "dst")));
+}
+
+#[test]
+fn string_columns() {
+    let people = [(3, "Ana", "Silva"), (1, "Bo", "Chen"), (4, "Ana", "Khan"), (2, "Cy", "Silva"), (5, "Bo", "Okafor")];
+    let mut table = decomposed_cracking::DeCrackedTable::new();
+    table.new_columns(vec!["id".to_string()]);
+    table.new_str_columns(vec!["first_name".to_string(), "last_name".to_string()]);
+    let first_names: Vec<String> = people.iter().map(|p| p.1.to_string()).collect();
+    let last_names: Vec<String> = people.iter().map(|p| p.2.to_string()).collect();
+    let first_name = table.encode("first_name", &first_names);
+    let last_name = table.encode("last_name", &last_names);
+    table.insert(&mut map!{"id".to_string() => people.iter().map(|p| p.0).collect(),
+                           "first_name".to_string() => first_name, "last_name".to_string() => last_name});
+    table.set_crk_col("id".to_string());
+
+    assert_eq!(table.cracker_select_specific_str(4, "first_name"), vec!["Ana".to_string()]);
+    let mut last = table.cracker_select_range_str(2, 4, true, true, "last_name");
+    last.sort();
+    assert_eq!(last, vec!["Khan".to_string(), "Silva".to_string(), "Silva".to_string()]);
+
+    // The codes of a string column are cracked like any other column.
+    table.use_crk_col("last_name");
+    assert_eq!(sorted(table.cracker_select_str_eq("Silva", "id")), vec![2, 3]);
+    assert_eq!(table.cracker_select_str_eq("Smith", "id"), Vec::<i64>::new());
+
+    let path = env::temp_dir().join(format!("string_columns_{}", process::id()));
+    table.save(&path).unwrap();
+    let mut loaded = decomposed_cracking::DeCrackedTable::load(&path).unwrap();
+    ::std::fs::remove_file(&path).unwrap();
+    loaded.use_crk_col("first_name");
+    let mut names = loaded.cracker_select_str_eq("Bo", "last_name");
+    names.sort();
+    assert_eq!(loaded.decode("last_name", &names), vec!["Chen".to_string(), "Okafor".to_string()]);
+}
+
+// Runs the range_test queries as aggregates over dst, checking each against the aggregate of a scan.
+fn aggregate_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let mut table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let queries = vec![(5, 12, true, true), (0, 3, false, true), (20, 40, true, false), (8, 8, true, true),
+                       (12, 19, false, false), (-3, 0, true, true), (28, 35, true, true), (9, 10, false, false),
+                       (1, 30, true, true), (14, 13, true, true)];
+    let aggs = [Aggregate::Count, Aggregate::Sum, Aggregate::Min, Aggregate::Max];
+
+    for (i, &(low, high, inc_l, inc_h)) in queries.iter().enumerate() {
+        let in_range = |s: i64| (low < s || (inc_l && low == s)) && (s < high || (inc_h && s == high));
+        let agg = aggs[i % aggs.len()];
+        let expected = agg.fold((0..src.len()).filter(|&j| in_range(src[j])).map(|j| dst[j]));
+        let actual = table.cracker_aggregate_range(low, high, inc_l, inc_h, "dst", agg);
+        if actual != expected {
+            println!("{:?} of range ({}, {}, {}, {}): expected {:?}, got {:?}", agg, low, high, inc_l, inc_h, expected, actual);
+            return false;
+        }
+
+        // Each value is aggregated twice, the second time over a piece which is already cracked out.
+        let x = low + (i as i64 % 3);
+        for &agg in aggs.iter() {
+            let expected = agg.fold((0..src.len()).filter(|&j| src[j] == x).map(|j| dst[j]));
+            let actual = table.cracker_aggregate_specific(x, "dst", agg);
+            if actual != expected {
+                println!("{:?} of specific {}: expected {:?}, got {:?}", agg, x, expected, actual);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[test]
+fn aggregates() {
+    assert!(aggregate_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(aggregate_test::<nary_cracking::NaryCrackedTable>());
+    assert!(aggregate_test::<recognitive_compression::ReCoTable>());
+    assert!(aggregate_test::<compactive_compression::CoCoTable>());
+    assert!(aggregate_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(aggregate_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(aggregate_test::<hybrid_cracking::HybridTable>());
+}
+
+fn iter_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let mut table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let queries = vec![(5, 12, true, true), (0, 3, false, true), (20, 40, true, false), (8, 8, true, true),
+                       (12, 19, false, false), (-3, 0, true, true), (28, 35, true, true), (9, 10, false, false),
+                       (1, 30, true, true), (14, 13, true, true)];
+
+    for (i, &(low, high, inc_l, inc_h)) in queries.iter().enumerate() {
+        let in_range = |s: i64| (low < s || (inc_l && low == s)) && (s < high || (inc_h && s == high));
+        let mut expected: Vec<i64> = (0..src.len()).filter(|&j| in_range(src[j])).map(|j| dst[j]).collect();
+        let mut actual: Vec<i64> = table.cracker_iter_range(low, high, inc_l, inc_h, "dst").collect();
+        expected.sort();
+        actual.sort();
+        if actual != expected {
+            println!("range ({}, {}, {}, {}): expected {:?}, got {:?}", low, high, inc_l, inc_h, expected, actual);
+            return false;
+        }
+
+        // Both columns are read through the same positions, so the rows they make up must exist.
+        let x = low + (i as i64 % 3);
+        let r = table.crack_specific(x);
+        let rows: Vec<(i64, i64)> = table.values(r.clone(), "src").zip(table.values(r, "dst")).collect();
+        let expected = (0..src.len()).filter(|&j| src[j] == x).count();
+        if rows.len() != expected || rows.iter().any(|&(s, d)| s != x || !(0..src.len()).any(|j| src[j] == s && dst[j] == d)) {
+            println!("specific {}: expected {} rows, got {:?}", x, expected, rows);
+            return false;
+        }
+    }
+    true
+}
+
+#[test]
+fn borrowing_iterators() {
+    assert!(iter_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(iter_test::<nary_cracking::NaryCrackedTable>());
+    assert!(iter_test::<recognitive_compression::ReCoTable>());
+    assert!(iter_test::<compactive_compression::CoCoTable>());
+    assert!(iter_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(iter_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(iter_test::<hybrid_cracking::HybridTable>());
+}
+
+const INDEX_KINDS: [IndexKind; 4] = [IndexKind::AVL, IndexKind::BPlusTree, IndexKind::Sorted, IndexKind::Array];
+
+#[test]
+fn cracker_index_backends() {
+    let mut rng = PivotRng::new(42);
+    for &kind in INDEX_KINDS.iter() {
+        let mut idx = kind.new_index::<i64>();
+        let mut reference: BTreeMap<i64, usize> = BTreeMap::new();
+        for i in 0..5000 {
+            let key = rng.gen_range(-1000, 1000);
+            match rng.gen_range(0, 4) {
+                0 | 1 => {
+                    idx.insert(key, i);
+                    reference.insert(key, i);
+                },
+                2 => {
+                    idx.delete(key);
+                    reference.remove(&key);
+                },
+                _ => {
+                    idx.add_where_greater_than(key, 3);
+                    idx.subtract_where_greater_than(key, 2);
+                    for (_, d) in reference.range_mut(key + 1..) {
+                        *d += 1;
+                    }
+                },
+            }
+
+            let probe = rng.gen_range(-1010, 1010);
+            assert_eq!(idx.get(probe), reference.get(&probe).cloned(), "{:?} get {}", kind, probe);
+            assert_eq!(idx.upper_bound(&probe), reference.range(probe..).next().map(|(_, &d)| d), "{:?} upper_bound {}", kind, probe);
+            assert_eq!(idx.lower_bound(&probe), reference.range(..=probe).next_back().map(|(_, &d)| d), "{:?} lower_bound {}", kind, probe);
+        }
+        assert_eq!(idx.pairs(), reference.into_iter().collect::<Vec<_>>());
+    }
+}
+
+// Keys at either end of i64, probed from the other end, must neither overflow nor be found where
+// they are not.
+#[test]
+fn cracker_index_extreme_keys() {
+    let probes = [i64::MIN, i64::MIN + 1, i64::MIN + 3, -1, 0, i64::MAX - 3, i64::MAX - 1, i64::MAX];
+    for keys in [[i64::MAX - 2, i64::MAX, i64::MAX - 4], [i64::MIN + 2, i64::MIN, i64::MIN + 4]].iter() {
+        for &kind in INDEX_KINDS.iter() {
+            let mut idx = kind.new_index::<i64>();
+            let mut reference: BTreeMap<i64, usize> = BTreeMap::new();
+            for (i, &key) in keys.iter().enumerate() {
+                idx.insert(key, 10 * (i + 1));
+                reference.insert(key, 10 * (i + 1));
+            }
+            idx.add_where_greater_than(i64::MIN, 2);
+            idx.subtract_where_greater_than(i64::MAX, 1);
+            for (_, d) in reference.range_mut(i64::MIN + 1..) {
+                *d += 2;
+            }
+            for &probe in probes.iter() {
+                assert_eq!(idx.get(probe), reference.get(&probe).cloned(), "{:?} get {}", kind, probe);
+                assert_eq!(idx.upper_bound(&probe), reference.range(probe..).next().map(|(_, &d)| d), "{:?} upper_bound {}", kind, probe);
+                assert_eq!(idx.lower_bound(&probe), reference.range(..=probe).next_back().map(|(_, &d)| d), "{:?} lower_bound {}", kind, probe);
+            }
+            assert_eq!(idx.pairs(), reference.into_iter().collect::<Vec<_>>());
+        }
+    }
+}
+
+fn cracker_index_test<T: CrackedTable>(kind: IndexKind) -> bool {
+    let (src, dst) = range_test_edges();
+    let mut table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    // The cracks made before the switch must carry over to the new index.
+    table.cracker_select_range(6, 20, true, false, "dst");
+    table.set_cracker_index(kind);
+    range_test(table, &src, &dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn cracker_index_kinds() {
+    for &kind in INDEX_KINDS.iter() {
+        assert!(cracker_index_test::<decomposed_cracking::DeCrackedTable>(kind));
+        assert!(cracker_index_test::<nary_cracking::NaryCrackedTable>(kind));
+        assert!(cracker_index_test::<recognitive_compression::ReCoTable>(kind));
+        assert!(cracker_index_test::<compactive_compression::CoCoTable>(kind));
+        assert!(cracker_index_test::<underswap_rle_compression::UnderswapRLETable>(kind));
+        assert!(cracker_index_test::<overswap_rle_compression::OverswapRLETable>(kind));
+        assert!(cracker_index_test::<hybrid_cracking::HybridTable>(kind));
+    }
+}
+
+fn stats_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let mut table = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let stats = table.stats();
+    if stats.tuples != src.len() || stats.converged > 0.0 {
+        println!("Uncracked: {:?}", stats);
+        return false;
+    }
+
+    // Once every value has been selected, every piece holds a single value.
+    for x in 0..32 {
+        table.cracker_select_specific(x, "dst");
+    }
+    let stats = table.stats();
+    let mut values = src.clone();
+    values.sort();
+    values.dedup();
+    if stats.tuples != src.len() || stats.pieces != values.len() || stats.converged != 1.0
+        || stats.piece_sizes.iter().sum::<usize>() != stats.pieces || stats.index_depth == 0 {
+        println!("Converged: {:?}", stats);
+        return false;
+    }
+    true
+}
+
+#[test]
+fn crack_stats() {
+    assert!(stats_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(stats_test::<nary_cracking::NaryCrackedTable>());
+    assert!(stats_test::<recognitive_compression::ReCoTable>());
+    assert!(stats_test::<compactive_compression::CoCoTable>());
+    assert!(stats_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(stats_test::<overswap_rle_compression::OverswapRLETable>());
+    assert!(stats_test::<hybrid_cracking::HybridTable>());
+
+    let (src, dst) = range_test_edges();
+    let mut coco = compactive_compression::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    for x in 0..32 {
+        coco.cracker_select_specific(x, "dst");
+    }
+    assert_eq!(coco.stats().compaction_ratio, Some(coco.stats().pieces as f64 / src.len() as f64));
+    assert!(underswap_rle_compression::from_adjacency_vectors(src, dst, "src").stats().avg_run_length.is_some());
+}
+
+fn convergence_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let half = src.len() / 2;
+    let mut table = T::from_adjacency_vectors(src[..half].to_vec(), dst[..half].to_vec(), "src");
+    table.set_convergence_threshold(8);
+
+    // Two sweeps over every value leave no piece larger than the threshold, and give the table a
+    // chance to notice.
+    for _ in 0..2 {
+        for x in 0..32 {
+            table.cracker_select_specific(x, "dst");
+        }
+    }
+    let stats = table.stats();
+    if stats.index_depth != 0 || stats.converged != 1.0 {
+        println!("Not converged: {:?}", stats);
+        return false;
+    }
+
+    // Inserts and deletes are merged into the sorted column.
+    table.insert(&mut map!{"src" => src[half..].to_vec(), "dst" => dst[half..].to_vec()});
+    table.delete_where("src", 8);
+    table.delete_indices([0, half + 1].iter());
+    table.cracker_select_range(0, 32, true, false, "dst");
+    let stats = table.stats();
+    if stats.index_depth != 0 || stats.converged != 1.0 {
+        println!("Unsorted by updates: {:?}", stats);
+        return false;
+    }
+
+    let kept: Vec<usize> = (0..src.len()).filter(|&j| src[j] != 8 && j != 0 && j != half + 1).collect();
+    let kept_src: Vec<i64> = kept.iter().map(|&j| src[j]).collect();
+    let kept_dst: Vec<i64> = kept.iter().map(|&j| dst[j]).collect();
+    range_test(table, &kept_src, &kept_dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn convergence_to_sorted() {
+    assert!(convergence_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(convergence_test::<nary_cracking::NaryCrackedTable>());
+    assert!(convergence_test::<recognitive_compression::ReCoTable>());
+    assert!(convergence_test::<compactive_compression::CoCoTable>());
+    assert!(convergence_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(convergence_test::<overswap_rle_compression::OverswapRLETable>());
+
+    let (src, dst) = range_test_edges();
+    let mut nary = nary_cracking::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    nary.settings.convergence_threshold = 8;
+    let expected = nary.cracker_select_batch(&(0..32).collect(), "dst");
+    for _ in 0..64 {
+        nary.cracker_select_specific(5, "dst");
+    }
+    assert!(nary.crk_col.sorted);
+    assert_eq!(nary.cracker_select_batch(&(0..32).collect(), "dst"), expected);
+}
+
+fn min_piece_test<T: CrackedTable>() -> bool {
+    let (src, dst) = range_test_edges();
+    let mut cracked = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    let mut scanned = T::from_adjacency_vectors(src.clone(), dst.clone(), "src");
+    scanned.set_min_piece_size(16);
+    for x in 0..32 {
+        let expected = (0..src.len()).filter(|&j| src[j] == x).count() as i64;
+        cracked.cracker_select_specific(x, "dst");
+        if scanned.cracker_iter_specific(x, "dst").count() as i64 != expected
+            || scanned.cracker_aggregate_specific(x, "dst", Aggregate::Count) != Some(expected) {
+            println!("Scanning miscounted {}", x);
+            return false;
+        }
+    }
+
+    // Small pieces are never cracked, so the scanning table is left with fewer, larger pieces.
+    let (cracked_stats, scanned_stats) = (cracked.stats(), scanned.stats());
+    if scanned_stats.pieces >= cracked_stats.pieces {
+        println!("Cracked: {:?}\nScanned: {:?}", cracked_stats, scanned_stats);
+        return false;
+    }
+    range_test(scanned, &src, &dst,
+               |t, l, h, il, ih| t.cracker_select_range(l, h, il, ih, "dst"),
+               |t, x| t.cracker_select_specific(x, "dst"))
+}
+
+#[test]
+fn min_piece_size() {
+    assert!(min_piece_test::<decomposed_cracking::DeCrackedTable>());
+    assert!(min_piece_test::<nary_cracking::NaryCrackedTable>());
+    assert!(min_piece_test::<recognitive_compression::ReCoTable>());
+    assert!(min_piece_test::<compactive_compression::CoCoTable>());
+    assert!(min_piece_test::<underswap_rle_compression::UnderswapRLETable>());
+    assert!(min_piece_test::<overswap_rle_compression::OverswapRLETable>());
+}
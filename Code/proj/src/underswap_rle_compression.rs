@@ -4,15 +4,32 @@
 // uses map! macro.
 
 use column::Column;
+use column::Element;
 use column::IntCol;
-use cracker_index::AVLCrackerIndex;
+use column::Values;
+use column::half_open;
+use column::worker_threads;
+use cracked_table::CrackSettings;
+use cracked_table::CrackStats;
+use cracked_table::CrackedTable;
+use encoding::RunLengthColumn;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::slice::Iter;
+use std::ops::Range;
 
 #[derive(Clone)]
 pub struct UnderswapRLETable {
     pub count: usize,
+    pub crk_col_name: String,
     pub crk_col: IntCol,
+    // Cracker columns on other columns, parked by the name of the column they crack until use_crk_col
+    // switches to them.
+    pub crk_cols: HashMap<String, IntCol>,
+    pub settings: CrackSettings,
+    // Base indices of the deleted rows. Their values stay in the base columns, so that base indices
+    // remain valid.
+    pub deleted: HashSet<usize>,
     pub columns: HashMap<String, IntCol>,
     pub dbg_switch: bool,
 }
@@ -21,7 +38,11 @@ impl UnderswapRLETable {
     pub fn new() -> UnderswapRLETable {
         UnderswapRLETable {
             count: 0,
+            crk_col_name: "".to_string(),
             crk_col: IntCol::empty(),
+            crk_cols: HashMap::new(),
+            settings: CrackSettings::default(),
+            deleted: HashSet::new(),
             columns: HashMap::new(),
             dbg_switch: false,
         }
@@ -46,12 +67,12 @@ impl UnderswapRLETable {
         for i in (lower + 1)..upper {
             print!(", {}", self.crk_col.crk[i])
         }
-        print!("]\n");
+        println!("]");
         print!("rls: [{}", self.crk_col.run_lengths[lower]);
         for i in (lower + 1)..upper {
             print!(", {}", self.crk_col.run_lengths[i])
         }
-        print!("]\n");
+        println!("]");
     }
 
     pub fn print_crk(&self) {
@@ -65,19 +86,30 @@ impl UnderswapRLETable {
     }
 
     pub fn set_crk_col(&mut self, col_name: &str) {
-        match self.columns.get(&(col_name.to_string())) {
-            Some(ref c) => {
-                self.crk_col.v = c.v.clone();
-                self.crk_col.crk = c.v.clone();
-                self.crk_col.base_idx = (0..self.count).collect();
-                self.crk_col.run_lengths = vec![1;self.count];
-            },
+        // The current cracker column is parked, so that use_crk_col can switch back to it.
+        if self.crk_col_name != col_name {
+            self.crk_col.park(&self.crk_col_name, &mut self.crk_cols);
+        }
+        self.crk_cols.remove(col_name);
+        self.crk_col = match self.columns.get(col_name) {
+            Some(c) => IntCol::cracking(&c.v, &self.deleted, self.settings.index_kind),
             None => panic!("set_crk_col: no such col"),
         };
+        self.crk_col_name = col_name.to_string();
+        self.crk_col.run_lengths = vec![1; self.crk_col.crk.len()];
+    }
+
+    // Makes the cracker column on COL_NAME the one which queries crack, parking the current one. The
+    // cracker column is built if the table has none on COL_NAME yet.
+    pub fn use_crk_col(&mut self, col_name: &str) {
+        if !self.crk_col.unpark(&mut self.crk_col_name, col_name, &mut self.crk_cols) {
+            self.set_crk_col(col_name);
+        }
     }
 
     // TODO: Improve exception handling in this function
     pub fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        let old_count = self.count;
         let mut n_new_tuples = 0;
         for (key, val) in self.columns.iter_mut() {
             let new_elements = new_values.get_mut(&*(key.clone())).unwrap();
@@ -90,10 +122,18 @@ impl UnderswapRLETable {
             }
         }
         self.count += n_new_tuples;
+
+        // Rows inserted after the cracker column is set are merged into it by the queries which need them
+        if !self.crk_col_name.is_empty() {
+            self.crk_col.add_pending(&self.columns[&self.crk_col_name].v, old_count..self.count);
+        }
+        for (name, c) in self.crk_cols.iter_mut() {
+            c.add_pending(&self.columns[name].v, old_count..self.count);
+        }
     }
 
     pub fn get_col(&self, col: &str) -> &IntCol {
-        self.columns.get(&(col.to_string())).unwrap()
+        self.columns.get(col).unwrap()
     }
 
     pub fn get_indices(&self, indices: Iter<usize>) -> UnderswapRLETable {
@@ -109,18 +149,19 @@ impl UnderswapRLETable {
         }
 
         let mut t = UnderswapRLETable::new();
+        t.settings = self.settings.clone();
         t.columns = selection;
         t.count = indices.len();
 
         let mut indexed_crk_v = Vec::with_capacity(indices.len());
-        if self.crk_col.crk.len() > 0 {
+        if !self.crk_col.crk.is_empty() {
             let mut indexed_crk_col = Vec::with_capacity(indices.len());
             for &i in indices.clone() {
                 indexed_crk_col.push(self.crk_col.crk[i]);
                 indexed_crk_v.push(self.crk_col.v[i]);
             }
             t.crk_col.crk = indexed_crk_col;
-            t.crk_col.crk_idx = AVLCrackerIndex::new();
+            t.crk_col.crk_idx = self.settings.index_kind.new_index();
         } else {
             for &i in indices.clone() {
                 indexed_crk_v.push(self.crk_col.v[i]);
@@ -143,27 +184,55 @@ impl UnderswapRLETable {
             col.rearrange(indices.clone());
         }
         self.crk_col.rearrange(indices.clone());
+        for c in self.crk_cols.values_mut() {
+            c.rearrange(indices.clone());
+        }
     }
 
-    // Returns the elements of T where the cracker columns's value equals X
-    pub fn cracker_select_specific(&mut self, x: i64, col: &str) -> Vec<i64> {
+    // Cracks the column on X and X + 1, returning the positions of the elements equal to X.
+    pub fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        let x1 = match x.successor() {
+            Some(x1) => x1,
+            // Nothing lies above i64::MAX, so its elements are those from it onwards.
+            None => return self.crack_select_range(x, x, true, true),
+        };
+        self.merge_pending(x, Some(x1));
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(x, Some(x1));
+        }
+        self.crk_col.stochastic_crack(x, &mut self.settings);
+        // A piece already cracked out for X is found from the cracker index alone.
+        if let (Some(p_x), Some(p_x1)) = (self.crk_col.crk_idx.get(x), self.crk_col.crk_idx.get(x1)) {
+            return p_x..p_x1;
+        }
+
         // Setup
         let mut p_low  = self.crk_col.crk_idx.lower_bound(&x).unwrap_or(0);
-        if p_low == self.count {
-            return vec![];
+        if p_low == self.crk_col.crk.len() {
+            return 0..0;
+        }
+        let p_end = self.crk_col.crk_idx.upper_bound(&x1).unwrap_or(self.crk_col.crk.len());
+        if p_end == 0 { return 0..0 };
+        if p_end - p_low > self.settings.parallel_threshold {
+            // A large piece is split around X in one stable pass of the parallel kernel, which keeps
+            // its runs contiguous.
+            let (p_x, p_x1) = self.crk_col.partition_stable_parallel(p_low, p_end, x, x1, worker_threads());
+            self.crk_col.crk_idx.insert(x, p_x);
+            self.crk_col.crk_idx.insert(x1, p_x1);
+            return p_x..p_x1;
         }
-        let mut p_high = self.crk_col.crk_idx.upper_bound(&(x + 1)).unwrap_or(self.count) - 1;
-        if p_high + 1 == 0 { return vec![] };
+        let mut p_high = p_end - 1;
 
         // Tighten
         while self.crk_col.crk[p_low] < x && p_low < p_high {
             let mut rl = self.crk_col.run_lengths[p_low];
 
-            while p_low + rl >= self.count && p_low + 1 < self.count { // Evade overflow.
+            while p_low + rl >= self.crk_col.crk.len() && p_low + 1 < self.crk_col.crk.len() { // Evade overflow.
                 p_low += 1;
                 rl = self.crk_col.run_lengths[p_low];
             }
-            if p_low + rl >= self.count {
+            if p_low + rl >= self.crk_col.crk.len() {
                 break;
             }
 
@@ -183,12 +252,10 @@ impl UnderswapRLETable {
 
         while self.crk_col.crk[p_high] > x && p_high > p_low {
             let mut rl = self.crk_col.run_lengths[p_high];
-            if self.crk_col.crk[p_high - rl] == self.crk_col.crk[p_high] {
+            if p_high >= rl && self.crk_col.crk[p_high - rl] == self.crk_col.crk[p_high] {
                 while self.crk_col.crk[p_high - rl] == self.crk_col.crk[p_high] {
                     let inc = self.crk_col.run_lengths[p_high - rl];
-                    if p_high < rl + inc {
-                        break;
-                    } else if p_high - (rl + inc) < p_low {
+                    if p_high < rl + inc || p_high - (rl + inc) < p_low {
                         break;
                     }
                     rl += inc;
@@ -196,19 +263,23 @@ impl UnderswapRLETable {
                 self.crk_col.run_lengths[p_high]            = rl;
                 self.crk_col.run_lengths[(p_high - rl) + 1] = rl;
             }
+            if p_high < p_low + rl {
+                // The run reaches back to P_LOW, so no element left equals X.
+                return 0..0;
+            }
             p_high -= rl;
         }
 
         if p_low == p_high {
             if self.crk_col.crk[p_low] == x {
-                return self.get_values(self.crk_col.base_idx[p_low..(p_low + 1)].iter(), col);
+                return p_low..(p_low + 1);
             } else {
-                return vec![];
+                return 0..0;
             }
         }
 
         // Scan
-        let mut p_itr = p_low.clone();
+        let mut p_itr = p_low;
         while p_itr <= p_high {
             if self.crk_col.crk[p_itr] < x {
                 let rl_itr = self.crk_col.run_lengths[p_itr];
@@ -250,11 +321,11 @@ impl UnderswapRLETable {
                 while self.crk_col.crk[p_low] < x && p_low < p_high {
                     let mut rl = self.crk_col.run_lengths[p_low];
 
-                    while p_low + rl >= self.count && p_low + 1 < self.count { // Evade overflow.
+                    while p_low + rl >= self.crk_col.crk.len() && p_low + 1 < self.crk_col.crk.len() { // Evade overflow.
                         p_low += 1;
                         rl = self.crk_col.run_lengths[p_low];
                     }
-                    if p_low + rl >= self.count {
+                    if p_low + rl >= self.crk_col.crk.len() {
                         break;
                     }
 
@@ -273,7 +344,7 @@ impl UnderswapRLETable {
                 }
 
                 if p_itr < p_low {
-                    p_itr = p_low.clone();
+                    p_itr = p_low;
                 }
             } else if self.crk_col.crk[p_itr] > x {
                 let rl_itr = self.crk_col.run_lengths[p_itr];
@@ -301,12 +372,10 @@ impl UnderswapRLETable {
 
                 while self.crk_col.crk[p_high] > x && p_high > p_low {
                     let mut rl = self.crk_col.run_lengths[p_high];
-                    if self.crk_col.crk[p_high - rl] == self.crk_col.crk[p_high] {
+                    if p_high >= rl && self.crk_col.crk[p_high - rl] == self.crk_col.crk[p_high] {
                         while self.crk_col.crk[p_high - rl] == self.crk_col.crk[p_high] {
                             let inc = self.crk_col.run_lengths[p_high - rl];
-                            if p_high < rl + inc {
-                                break;
-                            } else if p_high - (rl + inc) < p_low {
+                            if p_high < rl + inc || p_high - (rl + inc) < p_low {
                                 break;
                             }
                             rl += inc;
@@ -314,6 +383,10 @@ impl UnderswapRLETable {
                         self.crk_col.run_lengths[p_high]            = rl;
                         self.crk_col.run_lengths[(p_high - rl) + 1] = rl;
                     }
+                    if p_high < p_low + rl {
+                        // The run reaches back to P_LOW, so no element left equals X.
+                        return 0..0;
+                    }
                     p_high -= rl;
                 }
             } else {
@@ -323,7 +396,7 @@ impl UnderswapRLETable {
 
         // If nothing is selected, then return nothing
         if p_high < p_low {
-            return vec![];
+            return 0..0;
         }
 
         // Memo
@@ -332,13 +405,177 @@ impl UnderswapRLETable {
         self.crk_col.run_lengths[p_high] = p_high - p_low + 1;
         //Store in cracker index
         self.crk_col.crk_idx.insert(x, p_low);
-        self.crk_col.crk_idx.insert(x + 1, p_high + 1);
-        self.get_values(self.crk_col.base_idx[p_low..(p_high + 1)].iter(), col)
+        self.crk_col.crk_idx.insert(x1, p_high + 1);
+        p_low..(p_high + 1)
+    }
+
+    // Adds a run-length encoded cracker map for COL, which from then on is cracked along with the
+    // cracker column and decoded as it is selected.
+    pub fn add_rle_map(&mut self, col: &str) {
+        if !self.crk_col.rle_maps.contains_key(col) {
+            let map = RunLengthColumn::encode(&self.get_values(self.crk_col.base_idx.iter(), col));
+            self.crk_col.rle_maps.insert(col.to_string(), map);
+        }
+    }
+
+    // Merges the rows inserted since the cracker column was set whose values lie in [L, H) into it.
+    fn merge_pending(&mut self, l: i64, h: Option<i64>) {
+        self.crk_col.merge_pending(l, h, &self.columns);
+        // A column which was empty when cracked has no run lengths to shift, so give the new rows theirs.
+        let n = self.crk_col.crk.len();
+        self.crk_col.run_lengths.resize(n, 1);
+    }
+
+    // Cracks the column so that the elements whose values are between LOW and HIGH, with inclusivity
+    // given by INC_L and INC_H, are contiguous, returning their positions.
+    pub fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        let (l, h) = match half_open(low, high, inc_l, inc_h) {
+            Some(bounds) => bounds,
+            None => return 0..0,
+        };
+        self.merge_pending(l, h);
+        self.crk_col.converge(self.settings.convergence_threshold);
+        if self.crk_col.sorted {
+            return self.crk_col.sorted_range(l, h);
+        }
+        self.crk_col.stochastic_crack(l, &mut self.settings);
+        if let Some(h) = h {
+            self.crk_col.stochastic_crack(h, &mut self.settings);
+        }
+        let (p_low, p_high) = self.crk_col.crack_range(l, h, self.settings.parallel_threshold);
+        p_low..p_high
+    }
+
+    // Returns the elements of COL at the positions R of the cracker column, borrowing them from the table.
+    pub fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        self.crk_col.values(r, col, &self.columns[col].v)
+    }
+
+    // Returns the positions of the pieces holding the elements in [L, H) if they are small enough to be
+    // scanned rather than cracked.
+    pub fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        self.merge_pending(l, h);
+        self.crk_col.small_piece(l, h, self.settings.min_piece_size)
+    }
+
+    // Returns the elements of COL at the positions R whose cracker column values lie in [L, H).
+    pub fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        self.crk_col.filtered_values(r, l, h, &self.columns[col].v)
+    }
+
+    // Returns statistics on how far the cracker column has been cracked.
+    pub fn stats(&self) -> CrackStats {
+        let mut stats = self.crk_col.stats();
+        let runs = self.crk_col.recognised_runs();
+        if runs > 0 {
+            stats.avg_run_length = Some(self.crk_col.run_lengths.len() as f64 / runs as f64);
+        }
+        stats
+    }
+
+}
+
+impl Default for UnderswapRLETable {
+    fn default() -> UnderswapRLETable {
+        UnderswapRLETable::new()
+    }
+}
+
+impl CrackedTable for UnderswapRLETable {
+    fn new() -> UnderswapRLETable {
+        UnderswapRLETable::new()
+    }
+
+    fn new_columns(&mut self, col_names: Vec<&str>) {
+        UnderswapRLETable::new_columns(self, col_names)
+    }
+
+    fn insert(&mut self, new_values: &mut HashMap<&str, Vec<i64>>) {
+        UnderswapRLETable::insert(self, new_values)
+    }
+
+    fn use_crk_col(&mut self, col_name: &str) {
+        UnderswapRLETable::use_crk_col(self, col_name)
+    }
+
+    fn row_count(&self) -> usize {
+        self.count
+    }
+
+    fn deleted(&self) -> &HashSet<usize> {
+        &self.deleted
+    }
+
+    fn deleted_mut(&mut self) -> &mut HashSet<usize> {
+        &mut self.deleted
+    }
+
+    fn settings(&self) -> &CrackSettings {
+        &self.settings
+    }
+
+    fn settings_mut(&mut self) -> &mut CrackSettings {
+        &mut self.settings
+    }
+
+    fn cracker_columns_mut(&mut self) -> Vec<&mut IntCol> {
+        let mut cols: Vec<&mut IntCol> = self.crk_cols.values_mut().collect();
+        cols.push(&mut self.crk_col);
+        cols
+    }
+
+    fn stats(&self) -> CrackStats {
+        UnderswapRLETable::stats(self)
+    }
+
+    fn set_crk_col(&mut self, col_name: &str) {
+        UnderswapRLETable::set_crk_col(self, col_name)
+    }
+
+    fn crack_specific(&mut self, x: i64) -> Range<usize> {
+        UnderswapRLETable::crack_specific(self, x)
+    }
+
+    fn crack_select_range(&mut self, low: i64, high: i64, inc_l: bool, inc_h: bool) -> Range<usize> {
+        UnderswapRLETable::crack_select_range(self, low, high, inc_l, inc_h)
+    }
+
+    fn values<'a>(&'a self, r: Range<usize>, col: &str) -> Values<'a, i64> {
+        UnderswapRLETable::values(self, r, col)
+    }
+
+    fn scan_piece(&mut self, l: i64, h: Option<i64>) -> Option<Range<usize>> {
+        UnderswapRLETable::scan_piece(self, l, h)
+    }
+
+    fn filtered_values<'a>(&'a self, r: Range<usize>, l: i64, h: Option<i64>, col: &str) -> Values<'a, i64> {
+        UnderswapRLETable::filtered_values(self, r, l, h, col)
+    }
+
+    fn column_values<'a>(&'a self, col: &str) -> Values<'a, i64> {
+        Values::Mapped(UnderswapRLETable::get_col(self, col).v.iter())
+    }
+
+    fn get_indices(&self, indices: Iter<usize>) -> UnderswapRLETable {
+        UnderswapRLETable::get_indices(self, indices)
+    }
+
+    fn rearrange(&mut self, indices: Iter<usize>) {
+        UnderswapRLETable::rearrange(self, indices)
+    }
+
+    fn column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.columns.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn crk_col_name(&self) -> &str {
+        &self.crk_col_name
     }
 
-    // Counts the places where a given column equals a given value
-    pub fn count_col_eq(&self, col: &str, eq: i64) -> i64 {
-        self.get_col(col).v.iter().map(|&x|(x==eq)as i64).fold(0,|sum,x|sum+x) as i64
+    fn cracks(&self) -> Vec<(i64, usize)> {
+        self.crk_col.crk_idx.pairs()
     }
 }
 
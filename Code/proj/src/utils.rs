@@ -46,11 +46,11 @@ macro_rules! map (
     );
 
 // Prints a float vec where each float is to 4dp.
-pub fn pretty_println_f64vec(floats: &Vec<f64>) {
+pub fn pretty_println_f64vec(floats: &[f64]) {
     print!("[");
     print!("{}", floats[0]);
-    for i in 1..floats.len() {
-        print!(", {:.4}", floats[i]);
+    for f in floats.iter().skip(1) {
+        print!(", {:.4}", f);
     }
-    print!("]\n");
+    println!("]");
 }
\ No newline at end of file